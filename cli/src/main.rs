@@ -1,6 +1,6 @@
 use std::{fs, io::Write, path::Path};
 
-use shared::Year;
+use shared::{Problem, Year};
 use y2023;
 use y2024;
 use y2025;
@@ -43,6 +43,8 @@ fn main() {
         verify(args);
     } else if args.get(1) == Some(&"new".to_string()) {
         new(args);
+    } else if args.get(1) == Some(&"debug".to_string()) {
+        debug(args);
     } else if args.len() != 3 {
         usage(args);
     } else {
@@ -55,17 +57,18 @@ fn usage(args: Vec<String>) {
     eprintln!("  {} verify [<year>]", args[0]);
     eprintln!("  {} <year> <day>", args[0]);
     eprintln!("  {} new <year> <day>", args[0]);
+    eprintln!("  {} debug <year> <day>", args[0]);
 }
 
-fn run_specific_day(args: Vec<String>) {
-    let year = args[1]
+fn get_problem(year_str: &str, day_str: &str) -> Box<dyn Problem> {
+    let year = year_str
         .parse::<u16>()
-        .expect(&format!("Could not parse {} into a year", args[1]));
-    let day = args[2]
+        .unwrap_or_else(|_| panic!("Could not parse {} into a year", year_str));
+    let day = day_str
         .parse::<u8>()
-        .expect(&format!("Could not parse {} into a day", args[2]));
+        .unwrap_or_else(|_| panic!("Could not parse {} into a day", day_str));
 
-    let problems = match year {
+    let mut problems = match year {
         2023 => y2023::Year {}.problems(),
         2024 => y2024::Year {}.problems(),
         2025 => y2025::Year {}.problems(),
@@ -73,9 +76,36 @@ fn run_specific_day(args: Vec<String>) {
     };
 
     problems
-        .get(&day)
-        .expect(&format!("Unsupported day {} for year {}", day, year))
-        .run(false); // TODO: Add show_times as a CLI param
+        .remove(&day)
+        .unwrap_or_else(|| panic!("Unsupported day {} for year {}", day, year))
+}
+
+fn run_specific_day(args: Vec<String>) {
+    get_problem(&args[1], &args[2]).run(false); // TODO: Add show_times as a CLI param
+}
+
+fn debug(args: Vec<String>) {
+    if args.len() != 4 {
+        usage(args);
+        return;
+    }
+    let problem = get_problem(&args[2], &args[3]);
+    let input_file = problem.input();
+    let contents = match fs::read_to_string(&input_file) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("Trouble reading file {input_file}: {e}");
+            return;
+        }
+    };
+    let result = problem.debug(
+        &contents,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout(),
+    );
+    if let Err(e) = result {
+        eprintln!("Error debugging: {e}");
+    }
 }
 
 fn new(args: Vec<String>) {
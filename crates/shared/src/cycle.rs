@@ -0,0 +1,222 @@
+//! Cycle detection for states that are iterated with a deterministic `step` function.
+//!
+//! Step `0` is the starting state, and step `n` is the result of applying `step` `n` times.
+//! Every function here loops forever if the states never repeat, which can't happen when
+//! there are only finitely many of them.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The states from step `prefix` onwards repeat every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The earliest step that has the same state as step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+}
+
+/// Every state up to the point where they start repeating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<S> {
+    /// The states at steps `0..cycle.prefix + cycle.period`.
+    pub states: Vec<S>,
+    pub cycle: Cycle,
+}
+
+impl<S> History<S> {
+    /// The state at step `n`, e.g. after a billion spins, without simulating them all.
+    pub fn nth(&self, n: usize) -> &S {
+        &self.states[self.cycle.reduce(n)]
+    }
+}
+
+/// Detect the cycle by remembering every state in a hash map, which takes the fewest calls
+/// to `step` and keeps the states around for [`History::nth`].
+pub fn detect<S, F>(start: S, step: F) -> History<S>
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    detect_by_key(start, step, S::clone)
+}
+
+/// Like [`detect`], but states are compared using `fingerprint`, which can be a cheaper or
+/// smaller summary of the state (e.g. just the positions that matter). States with the same
+/// fingerprint must step to states with the same fingerprint.
+pub fn detect_by_key<S, K, F, G>(start: S, mut step: F, mut fingerprint: G) -> History<S>
+where
+    K: Hash + Eq,
+    F: FnMut(&S) -> S,
+    G: FnMut(&S) -> K,
+{
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    let mut states = vec![];
+    let mut state = start;
+    loop {
+        if let Some(&prefix) = seen.get(&fingerprint(&state)) {
+            let period = states.len() - prefix;
+            return History {
+                states,
+                cycle: Cycle { prefix, period },
+            };
+        }
+        seen.insert(fingerprint(&state), states.len());
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+/// Brent's algorithm, which only keeps two states in memory at a time.
+/// https://en.wikipedia.org/wiki/Cycle_detection#Brent's_algorithm
+pub fn brent<S, F>(start: &S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // Find the period by moving the tortoise to the hare at every power of two.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // Then find the prefix with two pointers that are one period apart.
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// Floyd's "tortoise and hare" algorithm, which only keeps two states in memory at a time.
+/// [`brent`] usually needs fewer calls to `step`.
+/// https://en.wikipedia.org/wiki/Cycle_detection#Floyd's_tortoise_and_hare
+pub fn floyd<S, F>(start: &S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut tortoise = step(start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        hare = step(&hare);
+    }
+
+    let mut prefix = 0;
+    let mut tortoise = start.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    let mut period = 1;
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+    Cycle { prefix, period }
+}
+
+/// The state at step `n`, using [`detect`] to skip over repeats of the cycle.
+pub fn nth_state<S, F>(start: S, step: F, n: usize) -> S
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    detect(start, step).nth(n).clone()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 0, 1, 2, 3, 4, 5, 2, 3, 4, 5, ...
+    fn step(x: &u32) -> u32 {
+        if *x < 5 { x + 1 } else { 2 }
+    }
+
+    #[test]
+    fn test_detectors_agree() {
+        let expected = Cycle {
+            prefix: 2,
+            period: 4,
+        };
+        assert_eq!(detect(0, step).cycle, expected);
+        assert_eq!(brent(&0, step), expected);
+        assert_eq!(floyd(&0, step), expected);
+
+        // A pure cycle with no prefix
+        let expected = Cycle {
+            prefix: 0,
+            period: 4,
+        };
+        assert_eq!(detect(2, step).cycle, expected);
+        assert_eq!(brent(&2, step), expected);
+        assert_eq!(floyd(&2, step), expected);
+
+        for start in 0..50_u64 {
+            let step = |x: &u64| (x * x + 1) % 255;
+            let cycle = detect(start, step).cycle;
+            assert_eq!(brent(&start, step), cycle);
+            assert_eq!(floyd(&start, step), cycle);
+        }
+    }
+
+    #[test]
+    fn test_nth() {
+        let history = detect(0, step);
+        assert_eq!(history.states, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(*history.nth(1), 1);
+        assert_eq!(*history.nth(6), 2);
+        assert_eq!(*history.nth(1_000_000_000), 4);
+        assert_eq!(nth_state(0, step, 9), 5);
+    }
+
+    #[test]
+    fn test_detect_by_key() {
+        // The counter keeps growing, but the remainder (which is all that matters) repeats.
+        let history = detect_by_key(
+            (0_u32, 0_u32),
+            |&(n, count)| ((n + 1) % 3, count + 1),
+            |s| s.0,
+        );
+        assert_eq!(
+            history.cycle,
+            Cycle {
+                prefix: 0,
+                period: 3
+            }
+        );
+        assert_eq!(history.nth(7).0, 1);
+    }
+}
@@ -0,0 +1,240 @@
+use std::str::FromStr;
+
+/// One of the four cardinal directions on a grid where row indices grow downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir4 {
+    /// All directions, in clockwise order starting from `Up`.
+    pub const ALL: [Dir4; 4] = [Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+    /// Rotate 90 degrees clockwise.
+    pub fn turn_right(self) -> Dir4 {
+        match self {
+            Dir4::Up => Dir4::Right,
+            Dir4::Right => Dir4::Down,
+            Dir4::Down => Dir4::Left,
+            Dir4::Left => Dir4::Up,
+        }
+    }
+
+    /// Rotate 90 degrees counter-clockwise.
+    pub fn turn_left(self) -> Dir4 {
+        match self {
+            Dir4::Up => Dir4::Left,
+            Dir4::Right => Dir4::Up,
+            Dir4::Down => Dir4::Right,
+            Dir4::Left => Dir4::Down,
+        }
+    }
+
+    pub fn opposite(self) -> Dir4 {
+        match self {
+            Dir4::Up => Dir4::Down,
+            Dir4::Right => Dir4::Left,
+            Dir4::Down => Dir4::Up,
+            Dir4::Left => Dir4::Right,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Dir4::Up | Dir4::Down)
+    }
+
+    /// The `(di, dj)` change in (row, column) from taking one step in this direction.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Dir4::Up => (-1, 0),
+            Dir4::Right => (0, 1),
+            Dir4::Down => (1, 0),
+            Dir4::Left => (0, -1),
+        }
+    }
+
+    /// Take one step from `(i, j)`, returning `None` if that would go below zero.
+    pub fn step(self, i: usize, j: usize) -> Option<(usize, usize)> {
+        step(i, j, self.delta())
+    }
+}
+
+impl TryFrom<char> for Dir4 {
+    type Error = String;
+
+    /// Accepts arrows (`^>v<`), compass points (`NESW`) and `UDLR`.
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '^' | 'N' | 'U' => Ok(Dir4::Up),
+            '>' | 'E' | 'R' => Ok(Dir4::Right),
+            'v' | 'S' | 'D' => Ok(Dir4::Down),
+            '<' | 'W' | 'L' => Ok(Dir4::Left),
+            _ => Err(format!("Unknown direction {c:?}")),
+        }
+    }
+}
+
+impl FromStr for Dir4 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Dir4::try_from(c),
+            _ => Err(format!("Unknown direction {s:?}")),
+        }
+    }
+}
+
+/// One of the four cardinal or four diagonal directions on a grid where row indices grow
+/// downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Dir8 {
+    /// All directions, in clockwise order starting from `Up`.
+    pub const ALL: [Dir8; 8] = [
+        Dir8::Up,
+        Dir8::UpRight,
+        Dir8::Right,
+        Dir8::DownRight,
+        Dir8::Down,
+        Dir8::DownLeft,
+        Dir8::Left,
+        Dir8::UpLeft,
+    ];
+
+    fn from_index(index: usize) -> Dir8 {
+        Dir8::ALL[index % 8]
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Rotate 45 degrees clockwise.
+    pub fn turn_right(self) -> Dir8 {
+        Dir8::from_index(self.index() + 1)
+    }
+
+    /// Rotate 45 degrees counter-clockwise.
+    pub fn turn_left(self) -> Dir8 {
+        Dir8::from_index(self.index() + 7)
+    }
+
+    pub fn opposite(self) -> Dir8 {
+        Dir8::from_index(self.index() + 4)
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    /// The `(di, dj)` change in (row, column) from taking one step in this direction.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Dir8::Up => (-1, 0),
+            Dir8::UpRight => (-1, 1),
+            Dir8::Right => (0, 1),
+            Dir8::DownRight => (1, 1),
+            Dir8::Down => (1, 0),
+            Dir8::DownLeft => (1, -1),
+            Dir8::Left => (0, -1),
+            Dir8::UpLeft => (-1, -1),
+        }
+    }
+
+    /// Take one step from `(i, j)`, returning `None` if that would go below zero.
+    pub fn step(self, i: usize, j: usize) -> Option<(usize, usize)> {
+        step(i, j, self.delta())
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(dir: Dir4) -> Dir8 {
+        match dir {
+            Dir4::Up => Dir8::Up,
+            Dir4::Right => Dir8::Right,
+            Dir4::Down => Dir8::Down,
+            Dir4::Left => Dir8::Left,
+        }
+    }
+}
+
+impl FromStr for Dir8 {
+    type Err = String;
+
+    /// Accepts anything [`Dir4`] does, plus the compass points `NE`, `SE`, `SW` and `NW`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NE" => Ok(Dir8::UpRight),
+            "SE" => Ok(Dir8::DownRight),
+            "SW" => Ok(Dir8::DownLeft),
+            "NW" => Ok(Dir8::UpLeft),
+            _ => Dir4::from_str(s).map(Dir8::from),
+        }
+    }
+}
+
+fn step(i: usize, j: usize, (di, dj): (isize, isize)) -> Option<(usize, usize)> {
+    Some((i.checked_add_signed(di)?, j.checked_add_signed(dj)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dir4_rotations() {
+        for dir in Dir4::ALL {
+            assert_eq!(dir.turn_right().turn_left(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+            assert_eq!(dir.opposite().opposite(), dir);
+        }
+        assert_eq!(Dir4::Up.turn_right(), Dir4::Right);
+        assert_eq!(Dir4::Up.turn_left(), Dir4::Left);
+    }
+
+    #[test]
+    fn test_dir8_rotations() {
+        for dir in Dir8::ALL {
+            assert_eq!(dir.turn_right().turn_left(), dir);
+            assert_eq!(dir.opposite().opposite(), dir);
+            let (di, dj) = dir.delta();
+            assert_eq!(dir.opposite().delta(), (-di, -dj));
+        }
+        assert_eq!(Dir8::UpLeft.turn_right(), Dir8::Up);
+        assert_eq!(Dir8::Up.turn_left(), Dir8::UpLeft);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Dir4::try_from('^'), Ok(Dir4::Up));
+        assert_eq!(Dir4::try_from('E'), Ok(Dir4::Right));
+        assert_eq!(Dir4::try_from('D'), Ok(Dir4::Down));
+        assert_eq!("<".parse(), Ok(Dir4::Left));
+        assert!("UU".parse::<Dir4>().is_err());
+        assert!(Dir4::try_from('x').is_err());
+        assert_eq!("NW".parse(), Ok(Dir8::UpLeft));
+        assert_eq!("S".parse(), Ok(Dir8::Down));
+    }
+
+    #[test]
+    fn test_step() {
+        assert_eq!(Dir4::Up.step(0, 0), None);
+        assert_eq!(Dir4::Down.step(0, 0), Some((1, 0)));
+        assert_eq!(Dir8::DownLeft.step(1, 1), Some((2, 0)));
+    }
+}
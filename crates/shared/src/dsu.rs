@@ -0,0 +1,161 @@
+//! A disjoint set union (a.k.a. union-find) over the elements `0..n`.
+//! https://en.wikipedia.org/wiki/Disjoint-set_data_structure
+
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    /// Only meaningful for roots.
+    size: Vec<usize>,
+    num_components: usize,
+}
+
+impl UnionFind {
+    /// Every element starts off in its own component.
+    pub fn new(n: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            num_components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The representative of the component containing `x`, compressing the path to it along
+    /// the way.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut curr = x;
+        while curr != root {
+            let next = self.parent[curr];
+            self.parent[curr] = root;
+            curr = next;
+        }
+        root
+    }
+
+    /// Merge the components containing `a` and `b`, returning false if they were already the
+    /// same component. The smaller component is attached to the larger one.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut root_a, mut root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+        if self.size[root_a] < self.size[root_b] {
+            (root_a, root_b) = (root_b, root_a);
+        }
+        self.parent[root_b] = root_a;
+        self.size[root_a] += self.size[root_b];
+        self.num_components -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the component containing `x`.
+    pub fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.num_components
+    }
+
+    /// The size of every component, in no particular order.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&x| self.parent[x] == x)
+            .map(|root| self.size[root])
+            .collect()
+    }
+
+    /// The elements of every component, each in increasing order. Components are ordered by
+    /// their smallest element.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index_of_root = vec![usize::MAX; self.len()];
+        let mut components: Vec<Vec<usize>> = vec![];
+        for x in 0..self.len() {
+            let root = self.find(x);
+            if index_of_root[root] == usize::MAX {
+                index_of_root[root] = components.len();
+                components.push(vec![]);
+            }
+            components[index_of_root[root]].push(x);
+        }
+        components
+    }
+}
+
+/// Kruskal's algorithm for a minimum spanning forest over the nodes `0..num_nodes`, given
+/// edges as `(weight, a, b)`. Returns the edges used, in the order they were added (i.e. by
+/// increasing weight, with ties kept in their original order).
+/// https://en.wikipedia.org/wiki/Kruskal%27s_algorithm
+pub fn kruskal<W: Ord>(
+    num_nodes: usize,
+    edges: impl IntoIterator<Item = (W, usize, usize)>,
+) -> Vec<(W, usize, usize)> {
+    let mut edges: Vec<(W, usize, usize)> = edges.into_iter().collect();
+    edges.sort_by(|(w1, _, _), (w2, _, _)| w1.cmp(w2));
+    let mut components = UnionFind::new(num_nodes);
+    let mut tree = vec![];
+    for (weight, a, b) in edges {
+        if components.num_components() <= 1 {
+            break;
+        }
+        if components.union(a, b) {
+            tree.push((weight, a, b));
+        }
+    }
+    tree
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.num_components(), 6);
+        assert!(uf.union(0, 1));
+        assert!(uf.union(2, 3));
+        assert!(uf.union(1, 3));
+        assert!(!uf.union(0, 2));
+        assert!(uf.connected(0, 3));
+        assert!(!uf.connected(0, 4));
+        assert_eq!(uf.component_size(2), 4);
+        assert_eq!(uf.num_components(), 3);
+        let mut sizes = uf.component_sizes();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 1, 4]);
+        assert_eq!(uf.components(), vec![vec![0, 1, 2, 3], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn test_kruskal() {
+        let edges = [
+            (4, 0, 1),
+            (1, 1, 2),
+            (3, 0, 2),
+            (2, 2, 3),
+            (5, 3, 0),
+            (7, 4, 5),
+        ];
+        let tree = kruskal(6, edges);
+        assert_eq!(tree, vec![(1, 1, 2), (2, 2, 3), (3, 0, 2), (7, 4, 5)]);
+        let total: i32 = tree.iter().map(|(w, _, _)| w).sum();
+        assert_eq!(total, 13);
+    }
+}
@@ -0,0 +1,310 @@
+//! Exact cover problems, solved with Knuth's Algorithm X using Dancing Links.
+//! https://en.wikipedia.org/wiki/Knuth%27s_Algorithm_X
+//! https://en.wikipedia.org/wiki/Dancing_Links
+//!
+//! Each row is a set of columns, and the goal is to pick rows so that every primary column is
+//! covered exactly once (or exactly as many times as its multiplicity), while every secondary
+//! column is covered at most once.
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    left: usize,
+    right: usize,
+    up: usize,
+    down: usize,
+    /// The column's header node, which is also the column's index plus one.
+    header: usize,
+    row: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExactCover {
+    /// Node `0` is the root, nodes `1..=num_columns` are the column headers, and the rest make
+    /// up the rows. Only primary columns are linked into the root's list.
+    nodes: Vec<Node>,
+    /// The number of rows still in each column.
+    len: Vec<usize>,
+    /// How many more times each column has to be covered.
+    remaining: Vec<usize>,
+    num_primary: usize,
+    num_rows: usize,
+}
+
+impl ExactCover {
+    /// Columns `0..num_primary` are primary, and the next `num_secondary` are secondary.
+    pub fn new(num_primary: usize, num_secondary: usize) -> ExactCover {
+        let num_columns = num_primary + num_secondary;
+        let mut nodes = vec![];
+        for i in 0..=num_columns {
+            nodes.push(Node {
+                left: i,
+                right: i,
+                up: i,
+                down: i,
+                header: i,
+                row: usize::MAX,
+            });
+        }
+        // Link the primary headers (and the root) into a circular list
+        for (i, node) in nodes[..=num_primary].iter_mut().enumerate() {
+            node.right = if i == num_primary { 0 } else { i + 1 };
+            node.left = if i == 0 { num_primary } else { i - 1 };
+        }
+        ExactCover {
+            nodes,
+            len: vec![0; num_columns + 1],
+            remaining: vec![1; num_columns + 1],
+            num_primary,
+            num_rows: 0,
+        }
+    }
+
+    /// Require a primary column to be covered `times` times (by different rows) instead of
+    /// once.
+    pub fn set_multiplicity(&mut self, column: usize, times: usize) {
+        assert!(
+            column < self.num_primary,
+            "Only primary columns have multiplicities"
+        );
+        self.remaining[column + 1] = times;
+    }
+
+    /// Add a row covering `columns`, returning its index (rows are numbered in the order
+    /// they're added).
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        assert!(!columns.is_empty(), "Rows must cover something");
+        let row = self.num_rows;
+        let first = self.nodes.len();
+        self.num_rows += 1;
+        for (k, &column) in columns.iter().enumerate() {
+            let header = column + 1;
+            assert!(header < self.len.len(), "Column {column} is out of range");
+            let i = self.nodes.len();
+            let up = self.nodes[header].up;
+            self.nodes.push(Node {
+                left: if k == 0 { i + columns.len() - 1 } else { i - 1 },
+                right: if k + 1 == columns.len() { first } else { i + 1 },
+                up,
+                down: header,
+                header,
+                row,
+            });
+            self.nodes[up].down = i;
+            self.nodes[header].up = i;
+            self.len[header] += 1;
+        }
+        row
+    }
+
+    /// Take a node out of its column.
+    fn unlink_vertical(&mut self, i: usize) {
+        let Node {
+            up, down, header, ..
+        } = self.nodes[i];
+        self.nodes[up].down = down;
+        self.nodes[down].up = up;
+        self.len[header] -= 1;
+    }
+
+    /// Put a node back in its column, undoing [`ExactCover::unlink_vertical`].
+    fn relink_vertical(&mut self, i: usize) {
+        let Node {
+            up, down, header, ..
+        } = self.nodes[i];
+        self.nodes[up].down = i;
+        self.nodes[down].up = i;
+        self.len[header] += 1;
+    }
+
+    /// Remove a column, along with every row that uses it.
+    fn cover(&mut self, header: usize) {
+        let Node { left, right, .. } = self.nodes[header];
+        self.nodes[left].right = right;
+        self.nodes[right].left = left;
+        let mut i = self.nodes[header].down;
+        while i != header {
+            let mut j = self.nodes[i].right;
+            while j != i {
+                self.unlink_vertical(j);
+                j = self.nodes[j].right;
+            }
+            i = self.nodes[i].down;
+        }
+    }
+
+    /// Undo [`ExactCover::cover`], putting everything back in the reverse order.
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.nodes[header].up;
+        while i != header {
+            let mut j = self.nodes[i].left;
+            while j != i {
+                self.relink_vertical(j);
+                j = self.nodes[j].left;
+            }
+            i = self.nodes[i].up;
+        }
+        let Node { left, right, .. } = self.nodes[header];
+        self.nodes[left].right = header;
+        self.nodes[right].left = header;
+    }
+
+    /// Take the row containing node `i` out of every column.
+    fn hide_row(&mut self, i: usize) {
+        let mut j = i;
+        loop {
+            self.unlink_vertical(j);
+            j = self.nodes[j].right;
+            if j == i {
+                break;
+            }
+        }
+    }
+
+    fn unhide_row(&mut self, i: usize) {
+        let mut j = self.nodes[i].left;
+        loop {
+            self.relink_vertical(j);
+            if j == i {
+                break;
+            }
+            j = self.nodes[j].left;
+        }
+    }
+
+    /// Use the row containing node `i`: take it out of every column, and cover the columns
+    /// that don't need covering any more. Returns the columns that were covered.
+    fn select(&mut self, i: usize) -> Vec<usize> {
+        self.hide_row(i);
+        let mut covered = vec![];
+        let mut j = i;
+        loop {
+            let header = self.nodes[j].header;
+            self.remaining[header] -= 1;
+            if self.remaining[header] == 0 {
+                self.cover(header);
+                covered.push(header);
+            }
+            j = self.nodes[j].right;
+            if j == i {
+                break;
+            }
+        }
+        covered
+    }
+
+    fn unselect(&mut self, i: usize, covered: Vec<usize>) {
+        for header in covered.into_iter().rev() {
+            self.uncover(header);
+        }
+        let mut j = i;
+        loop {
+            self.remaining[self.nodes[j].header] += 1;
+            j = self.nodes[j].right;
+            if j == i {
+                break;
+            }
+        }
+        self.unhide_row(i);
+    }
+
+    fn search(&mut self, solution: &mut Vec<usize>) -> bool {
+        // Branch on the column with the fewest choices, failing if any column can't be
+        // covered enough times.
+        let mut best: Option<(usize, usize)> = None;
+        let mut header = self.nodes[0].right;
+        while header != 0 {
+            if self.len[header] < self.remaining[header] {
+                return false;
+            }
+            let choices = self.len[header] + 1 - self.remaining[header];
+            if best.is_none_or(|(_, fewest)| choices < fewest) {
+                best = Some((header, choices));
+            }
+            header = self.nodes[header].right;
+        }
+        let Some((header, _)) = best else {
+            return true;
+        };
+
+        // Once a row has been tried, every solution that uses it has been ruled out, so it's
+        // hidden while trying the rest. Without this, a column that has to be covered several
+        // times would try the same set of rows in every possible order.
+        let mut rows = vec![];
+        let mut i = self.nodes[header].down;
+        while i != header {
+            rows.push(i);
+            i = self.nodes[i].down;
+        }
+        let mut found = false;
+        let mut tried = 0;
+        for &i in &rows {
+            solution.push(self.nodes[i].row);
+            let covered = self.select(i);
+            found = self.search(solution);
+            self.unselect(i, covered);
+            if found {
+                break;
+            }
+            solution.pop();
+            self.hide_row(i);
+            tried += 1;
+        }
+        for &i in rows[..tried].iter().rev() {
+            self.unhide_row(i);
+        }
+        found
+    }
+
+    /// Find a set of rows that covers every column the right number of times, if there is one.
+    pub fn solve(&mut self) -> Option<Vec<usize>> {
+        let mut solution = vec![];
+        self.search(&mut solution).then_some(solution)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exact_cover() {
+        // The example from Knuth's paper, where the only solution is rows 0, 3 and 4.
+        let mut problem = ExactCover::new(7, 0);
+        for row in [
+            vec![2, 4, 5],
+            vec![0, 3, 6],
+            vec![1, 2, 5],
+            vec![0, 3],
+            vec![1, 6],
+            vec![3, 4, 6],
+        ] {
+            problem.add_row(&row);
+        }
+        let mut solution = problem.solve().unwrap();
+        solution.sort();
+        assert_eq!(solution, vec![0, 3, 4]);
+        // Solving leaves everything as it was
+        assert!(problem.solve().is_some());
+
+        let mut problem = ExactCover::new(2, 0);
+        problem.add_row(&[0]);
+        assert_eq!(problem.solve(), None);
+    }
+
+    #[test]
+    fn test_secondary_and_multiplicity() {
+        // Column 0 needs covering twice, column 1 is optional
+        let mut problem = ExactCover::new(1, 1);
+        problem.set_multiplicity(0, 2);
+        problem.add_row(&[0, 1]);
+        problem.add_row(&[0, 1]);
+        assert_eq!(problem.solve(), None);
+        problem.add_row(&[0]);
+        let mut solution = problem.solve().unwrap();
+        solution.sort();
+        assert!(solution == vec![0, 2] || solution == vec![1, 2]);
+
+        // Nothing needs covering
+        assert_eq!(ExactCover::new(0, 3).solve(), Some(vec![]));
+    }
+}
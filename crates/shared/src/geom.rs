@@ -0,0 +1,304 @@
+pub mod polygon;
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// `|a - b|`, written so that it works for unsigned types too.
+fn abs_diff<T: Sub<Output = T> + PartialOrd>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+/// Parse exactly `N` comma separated values, allowing whitespace around each one.
+fn parse_parts<T: FromStr, const N: usize>(s: &str) -> Result<[T; N], String> {
+    let parts = s
+        .split(',')
+        .map(|part| {
+            part.trim()
+                .parse::<T>()
+                .map_err(|_| format!("Unable to parse {part:?}"))
+        })
+        .collect::<Result<Vec<T>, String>>()?;
+    let num_parts = parts.len();
+    parts
+        .try_into()
+        .map_err(|_| format!("Expected {N} parts, found {num_parts} in {s:?}"))
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    /// Convert each coordinate, e.g. `v.map(i128::from)` to widen before doing arithmetic
+    /// that might overflow.
+    pub fn map<U, F: Fn(T) -> U>(self, f: F) -> Vec2<U> {
+        Vec2::new(f(self.x), f(self.y))
+    }
+}
+
+impl<T: Clone + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd> Vec2<T> {
+    pub fn dot(&self, other: &Self) -> T {
+        self.x.clone() * other.x.clone() + self.y.clone() * other.y.clone()
+    }
+
+    /// The z component of the 3D cross product, i.e. the signed area of the parallelogram.
+    pub fn cross(&self, other: &Self) -> T {
+        self.x.clone() * other.y.clone() - self.y.clone() * other.x.clone()
+    }
+
+    pub fn manhattan(&self, other: &Self) -> T {
+        abs_diff(self.x.clone(), other.x.clone()) + abs_diff(self.y.clone(), other.y.clone())
+    }
+
+    pub fn dist_squared(&self, other: &Self) -> T {
+        let dx = abs_diff(self.x.clone(), other.x.clone());
+        let dy = abs_diff(self.y.clone(), other.y.clone());
+        dx.clone() * dx + dy.clone() * dy
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vec2<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vec2<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Vec2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Clone + Mul<Output = T>> Mul<T> for Vec2<T> {
+    type Output = Self;
+
+    fn mul(self, scalar: T) -> Self {
+        Self::new(self.x * scalar.clone(), self.y * scalar)
+    }
+}
+
+impl<T: Clone + Div<Output = T>> Div<T> for Vec2<T> {
+    type Output = Self;
+
+    fn div(self, scalar: T) -> Self {
+        Self::new(self.x / scalar.clone(), self.y / scalar)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Vec2<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Vec2<T> {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl<T: FromStr> FromStr for Vec2<T> {
+    type Err = String;
+
+    /// Parses `"x,y"`, with optional whitespace around each number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y] = parse_parts(s)?;
+        Ok(Self::new(x, y))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Vec2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Vec3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Convert each coordinate, e.g. `v.map(i128::from)` to widen before doing arithmetic
+    /// that might overflow.
+    pub fn map<U, F: Fn(T) -> U>(self, f: F) -> Vec3<U> {
+        Vec3::new(f(self.x), f(self.y), f(self.z))
+    }
+}
+
+impl<T: Clone + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + PartialOrd> Vec3<T> {
+    pub fn dot(&self, other: &Self) -> T {
+        self.x.clone() * other.x.clone()
+            + self.y.clone() * other.y.clone()
+            + self.z.clone() * other.z.clone()
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self::new(
+            self.y.clone() * other.z.clone() - self.z.clone() * other.y.clone(),
+            self.z.clone() * other.x.clone() - self.x.clone() * other.z.clone(),
+            self.x.clone() * other.y.clone() - self.y.clone() * other.x.clone(),
+        )
+    }
+
+    pub fn manhattan(&self, other: &Self) -> T {
+        abs_diff(self.x.clone(), other.x.clone())
+            + abs_diff(self.y.clone(), other.y.clone())
+            + abs_diff(self.z.clone(), other.z.clone())
+    }
+
+    pub fn dist_squared(&self, other: &Self) -> T {
+        let dx = abs_diff(self.x.clone(), other.x.clone());
+        let dy = abs_diff(self.y.clone(), other.y.clone());
+        let dz = abs_diff(self.z.clone(), other.z.clone());
+        dx.clone() * dx + dy.clone() * dy + dz.clone() * dz
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vec3<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vec3<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Vec3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Clone + Mul<Output = T>> Mul<T> for Vec3<T> {
+    type Output = Self;
+
+    fn mul(self, scalar: T) -> Self {
+        Self::new(
+            self.x * scalar.clone(),
+            self.y * scalar.clone(),
+            self.z * scalar,
+        )
+    }
+}
+
+impl<T: Clone + Div<Output = T>> Div<T> for Vec3<T> {
+    type Output = Self;
+
+    fn div(self, scalar: T) -> Self {
+        Self::new(
+            self.x / scalar.clone(),
+            self.y / scalar.clone(),
+            self.z / scalar,
+        )
+    }
+}
+
+impl<T: AddAssign> AddAssign for Vec3<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Vec3<T> {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+        self.z -= other.z;
+    }
+}
+
+impl<T: FromStr> FromStr for Vec3<T> {
+    type Err = String;
+
+    /// Parses `"x,y,z"`, with optional whitespace around each number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y, z] = parse_parts(s)?;
+        Ok(Self::new(x, y, z))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Vec3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_vec2_arithmetic() {
+        let a = Vec2::new(1, 2);
+        let b = Vec2::new(4, -2);
+        assert_eq!(a + b, Vec2::new(5, 0));
+        assert_eq!(a - b, Vec2::new(-3, 4));
+        assert_eq!(-a, Vec2::new(-1, -2));
+        assert_eq!(a * 3, Vec2::new(3, 6));
+        assert_eq!(a.dot(&b), 0);
+        assert_eq!(a.cross(&b), -10);
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.dist_squared(&b), 25);
+        assert_eq!(Vec2::new(1_u64, 5).manhattan(&Vec2::new(3, 2)), 5);
+    }
+
+    #[test]
+    fn test_vec3_arithmetic() {
+        let a = Vec3::new(1, 0, 0);
+        let b = Vec3::new(0, 1, 0);
+        assert_eq!(a.cross(&b), Vec3::new(0, 0, 1));
+        assert_eq!(b.cross(&a), Vec3::new(0, 0, -1));
+        assert_eq!(a.dot(&b), 0);
+        assert_eq!(Vec3::new(1_u64, 2, 3).dist_squared(&Vec3::new(3, 2, 1)), 8);
+        assert_eq!(Vec3::new(1, 2, 3).map(i128::from), Vec3::new(1_i128, 2, 3));
+    }
+
+    #[test]
+    fn test_fromstr() {
+        assert_eq!("1,2".parse::<Vec2<i64>>(), Ok(Vec2::new(1, 2)));
+        assert_eq!("-1, 2".parse::<Vec2<i64>>(), Ok(Vec2::new(-1, 2)));
+        assert_eq!("1, 2,  3".parse::<Vec3<u64>>(), Ok(Vec3::new(1, 2, 3)));
+        assert!("1,2".parse::<Vec3<u64>>().is_err());
+        assert!("1,2,3,4".parse::<Vec3<u64>>().is_err());
+        assert!("1,x".parse::<Vec2<u64>>().is_err());
+    }
+}
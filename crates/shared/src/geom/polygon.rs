@@ -0,0 +1,308 @@
+//! Simple polygons with integer vertices, e.g. loops traced out on a grid.
+//!
+//! Vertices are listed in order (in either direction) and the polygon closes itself, so the
+//! last vertex doesn't need to repeat the first (although it's harmless if it does).
+//! Everything is computed exactly, using `i128` so that large coordinates can't overflow.
+
+use super::Vec2;
+use crate::math::gcd;
+
+/// Each edge as a pair of consecutive vertices, including the one that closes the polygon.
+fn edges(vertices: &[Vec2<i64>]) -> impl Iterator<Item = (Vec2<i128>, Vec2<i128>)> + '_ {
+    vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| (a.map(i128::from), b.map(i128::from)))
+}
+
+/// Twice the signed area, which is positive when the vertices go counter-clockwise (with `y`
+/// pointing up). Doubling it keeps it an integer.
+/// https://en.wikipedia.org/wiki/Shoelace_formula
+pub fn double_signed_area(vertices: &[Vec2<i64>]) -> i128 {
+    edges(vertices).map(|(a, b)| a.cross(&b)).sum()
+}
+
+/// Twice the area, which is always an integer for a polygon with integer vertices.
+pub fn double_area(vertices: &[Vec2<i64>]) -> i128 {
+    double_signed_area(vertices).abs()
+}
+
+/// The number of integer points on the boundary. For an axis-aligned polygon, this is also
+/// the length of the boundary.
+pub fn boundary_points(vertices: &[Vec2<i64>]) -> i128 {
+    edges(vertices)
+        .map(|(a, b)| gcd(b.x - a.x, b.y - a.y))
+        .sum()
+}
+
+/// The number of integer points strictly inside the polygon, using Pick's theorem
+/// (`A = I + B / 2 - 1`). The polygon must not intersect itself.
+/// https://en.wikipedia.org/wiki/Pick%27s_theorem
+pub fn interior_points(vertices: &[Vec2<i64>]) -> i128 {
+    (double_area(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+/// The number of integer points inside or on the boundary of the polygon, e.g. the number of
+/// grid cells enclosed by a loop that goes through the middle of each cell.
+pub fn lattice_points(vertices: &[Vec2<i64>]) -> i128 {
+    interior_points(vertices) + boundary_points(vertices)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Where `point` is relative to the polygon, by counting how many edges a ray cast to the
+/// right of it crosses.
+/// https://en.wikipedia.org/wiki/Point_in_polygon#Ray_casting_algorithm
+pub fn locate(vertices: &[Vec2<i64>], point: Vec2<i64>) -> Location {
+    let p = point.map(i128::from);
+    let mut inside = false;
+    for (a, b) in edges(vertices) {
+        let side = (b - a).cross(&(p - a));
+        if side == 0
+            && a.x.min(b.x) <= p.x
+            && p.x <= a.x.max(b.x)
+            && a.y.min(b.y) <= p.y
+            && p.y <= a.y.max(b.y)
+        {
+            return Location::Boundary;
+        }
+        // Treating each edge as including its lower end but not its upper end means that a
+        // ray passing through a vertex is counted correctly.
+        if (a.y > p.y) != (b.y > p.y) && (side > 0) == (b.y > a.y) {
+            inside = !inside;
+        }
+    }
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// Split the integers between the smallest and largest value into inclusive ranges: one for
+/// each value, and one for each gap between consecutive values (if the gap isn't empty).
+fn compress(mut values: Vec<i64>) -> Vec<(i64, i64)> {
+    values.sort_unstable();
+    values.dedup();
+    let mut cells = vec![];
+    for (i, &value) in values.iter().enumerate() {
+        cells.push((value, value));
+        if let Some(&next) = values.get(i + 1)
+            && next - value > 1
+        {
+            cells.push((value + 1, next - 1));
+        }
+    }
+    cells
+}
+
+/// The index of the cell containing `value`, if any.
+fn cell_index(cells: &[(i64, i64)], value: i64) -> Option<usize> {
+    let i = cells.partition_point(|&(_, hi)| hi < value);
+    (i < cells.len() && cells[i].0 <= value).then_some(i)
+}
+
+/// An axis-aligned polygon that answers "is every integer point in this rectangle inside
+/// (or on the boundary of) the polygon" in constant time.
+///
+/// The coordinates are compressed so that every integer point in a compressed cell is in the
+/// same place relative to the polygon, and then the cells outside the polygon are counted
+/// with 2D prefix sums.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RectilinearPolygon {
+    xs: Vec<(i64, i64)>,
+    ys: Vec<(i64, i64)>,
+    /// `outside[i][j]` is the number of cells outside the polygon with `y` index below `i`
+    /// and `x` index below `j`.
+    outside: Vec<Vec<u32>>,
+}
+
+impl RectilinearPolygon {
+    /// Panics if any edge isn't horizontal or vertical.
+    pub fn new(vertices: &[Vec2<i64>]) -> RectilinearPolygon {
+        assert!(
+            edges(vertices).all(|(a, b)| a.x == b.x || a.y == b.y),
+            "Every edge must be horizontal or vertical"
+        );
+        let xs = compress(vertices.iter().map(|v| v.x).collect());
+        let ys = compress(vertices.iter().map(|v| v.y).collect());
+
+        // Work out which cells are strictly inside by casting a ray to the left from each one.
+        let mut inside = vec![vec![false; xs.len()]; ys.len()];
+        for (row, &(y, _)) in inside.iter_mut().zip(ys.iter()) {
+            let mut crossings: Vec<i64> = vertices
+                .iter()
+                .zip(vertices.iter().cycle().skip(1))
+                .filter(|(a, b)| a.x == b.x && a.y.min(b.y) <= y && y < a.y.max(b.y))
+                .map(|(a, _)| a.x)
+                .collect();
+            crossings.sort_unstable();
+            let mut num_crossings = 0;
+            for (cell, &(x, _)) in row.iter_mut().zip(xs.iter()) {
+                while num_crossings < crossings.len() && crossings[num_crossings] < x {
+                    num_crossings += 1;
+                }
+                *cell = num_crossings % 2 == 1;
+            }
+        }
+
+        // Then add the cells on the boundary. The vertices all have cells of their own.
+        for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+            let (x0, x1) = (cell_index(&xs, a.x).unwrap(), cell_index(&xs, b.x).unwrap());
+            let (y0, y1) = (cell_index(&ys, a.y).unwrap(), cell_index(&ys, b.y).unwrap());
+            for row in &mut inside[y0.min(y1)..=y0.max(y1)] {
+                row[x0.min(x1)..=x0.max(x1)].fill(true);
+            }
+        }
+
+        let mut outside = vec![vec![0; xs.len() + 1]; ys.len() + 1];
+        for (i, row) in inside.iter().enumerate() {
+            for (j, &cell) in row.iter().enumerate() {
+                outside[i + 1][j + 1] =
+                    outside[i][j + 1] + outside[i + 1][j] - outside[i][j] + u32::from(!cell);
+            }
+        }
+
+        RectilinearPolygon { xs, ys, outside }
+    }
+
+    /// Whether `point` is inside or on the boundary of the polygon.
+    pub fn contains(&self, point: Vec2<i64>) -> bool {
+        self.contains_rect(point, point)
+    }
+
+    /// Whether every integer point in the rectangle with opposite corners `corner_0` and
+    /// `corner_1` (inclusive) is inside or on the boundary of the polygon.
+    pub fn contains_rect(&self, corner_0: Vec2<i64>, corner_1: Vec2<i64>) -> bool {
+        let cells = (
+            cell_index(&self.xs, corner_0.x.min(corner_1.x)),
+            cell_index(&self.xs, corner_0.x.max(corner_1.x)),
+            cell_index(&self.ys, corner_0.y.min(corner_1.y)),
+            cell_index(&self.ys, corner_0.y.max(corner_1.y)),
+        );
+        // Anything beyond the outermost vertices is outside.
+        let (Some(x0), Some(x1), Some(y0), Some(y1)) = cells else {
+            return false;
+        };
+        let outside = &self.outside;
+        outside[y1 + 1][x1 + 1] + outside[y0][x0] == outside[y0][x1 + 1] + outside[y1 + 1][x0]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn polygon(vertices: &[(i64, i64)]) -> Vec<Vec2<i64>> {
+        vertices.iter().map(|&(x, y)| Vec2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn test_area_and_lattice_points() {
+        let triangle = polygon(&[(0, 0), (4, 0), (0, 4)]);
+        assert_eq!(double_signed_area(&triangle), 16);
+        assert_eq!(boundary_points(&triangle), 12);
+        assert_eq!(interior_points(&triangle), 3);
+        assert_eq!(lattice_points(&triangle), 15);
+
+        // Clockwise, and with the first vertex repeated at the end
+        let square = polygon(&[(0, 0), (0, 3), (3, 3), (3, 0), (0, 0)]);
+        assert_eq!(double_signed_area(&square), -18);
+        assert_eq!(double_area(&square), 18);
+        assert_eq!(boundary_points(&square), 12);
+        assert_eq!(interior_points(&square), 4);
+
+        let big = polygon(&[(0, 0), (i64::MAX, 0), (i64::MAX, i64::MAX), (0, i64::MAX)]);
+        let side = i128::from(i64::MAX);
+        assert_eq!(double_area(&big), 2 * side * side);
+        assert_eq!(lattice_points(&big), (side + 1) * (side + 1));
+    }
+
+    #[test]
+    fn test_locate() {
+        // A U shape, with a notch in the top
+        let u = polygon(&[
+            (0, 0),
+            (6, 0),
+            (6, 4),
+            (4, 4),
+            (4, 2),
+            (2, 2),
+            (2, 4),
+            (0, 4),
+        ]);
+        assert_eq!(locate(&u, Vec2::new(1, 1)), Location::Inside);
+        assert_eq!(locate(&u, Vec2::new(1, 3)), Location::Inside);
+        assert_eq!(locate(&u, Vec2::new(3, 3)), Location::Outside);
+        assert_eq!(locate(&u, Vec2::new(3, 2)), Location::Boundary);
+        assert_eq!(locate(&u, Vec2::new(6, 4)), Location::Boundary);
+        assert_eq!(locate(&u, Vec2::new(-1, 2)), Location::Outside);
+        assert_eq!(locate(&u, Vec2::new(7, 2)), Location::Outside);
+
+        let diamond = polygon(&[(0, -2), (2, 0), (0, 2), (-2, 0)]);
+        assert_eq!(locate(&diamond, Vec2::new(0, 0)), Location::Inside);
+        assert_eq!(locate(&diamond, Vec2::new(1, 1)), Location::Boundary);
+        assert_eq!(locate(&diamond, Vec2::new(2, 1)), Location::Outside);
+        assert_eq!(locate(&diamond, Vec2::new(-3, 0)), Location::Outside);
+    }
+
+    #[test]
+    fn test_rectilinear_polygon() {
+        let shapes = [
+            polygon(&[
+                (7, 1),
+                (11, 1),
+                (11, 7),
+                (9, 7),
+                (9, 5),
+                (2, 5),
+                (2, 3),
+                (7, 3),
+            ]),
+            // The gap between the prongs has no integer points strictly inside it
+            polygon(&[
+                (0, 0),
+                (5, 0),
+                (5, 4),
+                (3, 4),
+                (3, 1),
+                (2, 1),
+                (2, 4),
+                (0, 4),
+            ]),
+            polygon(&[(0, 0), (3, 0), (3, 1), (0, 1)]),
+        ];
+        for vertices in shapes {
+            let shape = RectilinearPolygon::new(&vertices);
+            let points: Vec<Vec2<i64>> = (-1..13)
+                .flat_map(|x| (-1..9).map(move |y| Vec2::new(x, y)))
+                .collect();
+            for &p in &points {
+                assert_eq!(
+                    shape.contains(p),
+                    locate(&vertices, p) != Location::Outside,
+                    "{p:?}"
+                );
+            }
+            for &a in &points {
+                for &b in &points {
+                    let expected = (a.x.min(b.x)..=a.x.max(b.x)).all(|x| {
+                        (a.y.min(b.y)..=a.y.max(b.y)).all(|y| shape.contains(Vec2::new(x, y)))
+                    });
+                    assert_eq!(shape.contains_rect(a, b), expected, "{a:?} {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rectilinear_polygon_diagonal() {
+        RectilinearPolygon::new(&polygon(&[(0, 0), (2, 0), (0, 2)]));
+    }
+}
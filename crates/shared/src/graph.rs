@@ -0,0 +1,84 @@
+//! Algorithms on explicit graphs.
+//!
+//! Most of them expect the nodes to be numbered `0..n`, which [`NodeIds`] can do for nodes
+//! that have names.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+pub mod cliques;
+pub mod longest_path;
+pub mod min_cut;
+pub mod toposort;
+
+/// Assigns the ids `0, 1, 2, ...` to nodes in the order they're first seen.
+#[derive(Debug, Clone)]
+pub struct NodeIds<T> {
+    ids: HashMap<T, usize>,
+    nodes: Vec<T>,
+}
+
+impl<T: Clone + Hash + Eq> Default for NodeIds<T> {
+    fn default() -> Self {
+        NodeIds::new()
+    }
+}
+
+impl<T: Clone + Hash + Eq> NodeIds<T> {
+    pub fn new() -> NodeIds<T> {
+        NodeIds {
+            ids: HashMap::new(),
+            nodes: vec![],
+        }
+    }
+
+    /// The id of `node`, giving it the next one if it hasn't been seen before.
+    pub fn id(&mut self, node: T) -> usize {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        self.nodes.push(node.clone());
+        self.ids.insert(node, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    pub fn get(&self, node: &T) -> Option<usize> {
+        self.ids.get(node).copied()
+    }
+
+    /// The node with the given id. Panics if there isn't one.
+    pub fn node(&self, id: usize) -> &T {
+        &self.nodes[id]
+    }
+
+    /// Every node, indexed by id.
+    pub fn nodes(&self) -> &[T] {
+        &self.nodes
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_node_ids() {
+        let mut ids = NodeIds::new();
+        assert_eq!(ids.id("b"), 0);
+        assert_eq!(ids.id("a"), 1);
+        assert_eq!(ids.id("b"), 0);
+        assert_eq!(ids.get(&"a"), Some(1));
+        assert_eq!(ids.get(&"c"), None);
+        assert_eq!(*ids.node(1), "a");
+        assert_eq!(ids.nodes(), &["b", "a"]);
+        assert_eq!(ids.len(), 2);
+    }
+}
@@ -0,0 +1,303 @@
+//! Cliques (sets of nodes that are all connected to each other) in undirected graphs.
+//!
+//! Graphs are given as a map from each node to its neighbours, and every clique is returned
+//! sorted. Nodes only need to appear on one side of each edge.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+/// A fixed size set of `0..n`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn new(n: usize) -> BitSet {
+        BitSet {
+            words: vec![0; n.div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    fn remove(&mut self, i: usize) {
+        self.words[i / 64] &= !(1 << (i % 64));
+    }
+
+    fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    fn intersection(&self, other: &BitSet) -> BitSet {
+        BitSet {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| a & b)
+                .collect(),
+        }
+    }
+
+    fn difference(&self, other: &BitSet) -> BitSet {
+        BitSet {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| a & !b)
+                .collect(),
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+/// The graph with its nodes numbered in sorted order, so that sorting ids sorts the nodes.
+struct Graph<N> {
+    nodes: Vec<N>,
+    neighbors: Vec<BitSet>,
+}
+
+impl<N: Clone + Hash + Ord> Graph<N> {
+    fn new(adjacency: &HashMap<N, HashSet<N>>) -> Graph<N> {
+        let mut nodes: Vec<N> = adjacency
+            .iter()
+            .flat_map(|(node, neighbors)| std::iter::once(node).chain(neighbors))
+            .cloned()
+            .collect::<HashSet<N>>()
+            .into_iter()
+            .collect();
+        nodes.sort();
+        let ids: HashMap<&N, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+        let mut neighbors = vec![BitSet::new(nodes.len()); nodes.len()];
+        for (node, node_neighbors) in adjacency {
+            for neighbor in node_neighbors {
+                let (a, b) = (ids[node], ids[neighbor]);
+                if a != b {
+                    neighbors[a].insert(b);
+                    neighbors[b].insert(a);
+                }
+            }
+        }
+        Graph { nodes, neighbors }
+    }
+
+    fn to_nodes(&self, clique: &[usize]) -> Vec<N> {
+        // The ids are always added in increasing order, or sorted first
+        clique.iter().map(|&i| self.nodes[i].clone()).collect()
+    }
+
+    /// Repeatedly remove the node with the fewest remaining neighbours.
+    /// https://en.wikipedia.org/wiki/Degeneracy_(graph_theory)
+    fn degeneracy_order(&self) -> Vec<usize> {
+        let mut degrees: Vec<usize> = self.neighbors.iter().map(BitSet::len).collect();
+        let mut heap: BinaryHeap<Reverse<(usize, usize)>> = degrees
+            .iter()
+            .enumerate()
+            .map(|(node, &degree)| Reverse((degree, node)))
+            .collect();
+        let mut removed = vec![false; self.nodes.len()];
+        let mut order = vec![];
+        while let Some(Reverse((degree, node))) = heap.pop() {
+            if removed[node] || degree != degrees[node] {
+                continue;
+            }
+            removed[node] = true;
+            order.push(node);
+            for neighbor in self.neighbors[node].iter() {
+                if !removed[neighbor] {
+                    degrees[neighbor] -= 1;
+                    heap.push(Reverse((degrees[neighbor], neighbor)));
+                }
+            }
+        }
+        order
+    }
+
+    /// Bron–Kerbosch with pivoting: extend `clique` by the `candidates`, without reporting
+    /// anything that could also be extended by one of the `excluded` nodes.
+    /// https://en.wikipedia.org/wiki/Bron%E2%80%93Kerbosch_algorithm
+    fn bron_kerbosch<F: FnMut(&[usize])>(
+        &self,
+        clique: &mut Vec<usize>,
+        mut candidates: BitSet,
+        mut excluded: BitSet,
+        report: &mut F,
+    ) {
+        if candidates.is_empty() {
+            if excluded.is_empty() {
+                report(clique);
+            }
+            return;
+        }
+        // Anything maximal contains the pivot or one of its non-neighbours, so only those need
+        // to be tried.
+        let pivot = candidates
+            .iter()
+            .chain(excluded.iter())
+            .max_by_key(|&node| candidates.intersection(&self.neighbors[node]).len())
+            .unwrap();
+        for node in candidates.difference(&self.neighbors[pivot]).iter() {
+            clique.push(node);
+            self.bron_kerbosch(
+                clique,
+                candidates.intersection(&self.neighbors[node]),
+                excluded.intersection(&self.neighbors[node]),
+                report,
+            );
+            clique.pop();
+            candidates.remove(node);
+            excluded.insert(node);
+        }
+    }
+
+    /// Call `report` with every maximal clique, with ids in any order.
+    fn maximal_cliques<F: FnMut(&[usize])>(&self, mut report: F) {
+        // Going in degeneracy order keeps the number of candidates at the top level small.
+        let mut earlier = BitSet::new(self.nodes.len());
+        let mut later = BitSet::new(self.nodes.len());
+        for node in 0..self.nodes.len() {
+            later.insert(node);
+        }
+        for node in self.degeneracy_order() {
+            later.remove(node);
+            self.bron_kerbosch(
+                &mut vec![node],
+                later.intersection(&self.neighbors[node]),
+                earlier.intersection(&self.neighbors[node]),
+                &mut report,
+            );
+            earlier.insert(node);
+        }
+    }
+
+    /// Call `report` with every clique of size `k` whose ids are in increasing order and
+    /// start with `clique`, using nodes from `candidates`.
+    fn k_cliques<F: FnMut(&[usize])>(
+        &self,
+        clique: &mut Vec<usize>,
+        candidates: &BitSet,
+        k: usize,
+        report: &mut F,
+    ) {
+        if clique.len() == k {
+            report(clique);
+            return;
+        }
+        // Only extend by bigger ids, so that each clique is found once
+        let mut bigger = candidates.clone();
+        for node in candidates.iter() {
+            bigger.remove(node);
+            clique.push(node);
+            self.k_cliques(
+                clique,
+                &bigger.intersection(&self.neighbors[node]),
+                k,
+                report,
+            );
+            clique.pop();
+        }
+    }
+}
+
+/// Every clique that can't be made any bigger, sorted.
+pub fn maximal_cliques<N: Clone + Hash + Ord>(adjacency: &HashMap<N, HashSet<N>>) -> Vec<Vec<N>> {
+    let graph = Graph::new(adjacency);
+    let mut cliques = vec![];
+    graph.maximal_cliques(|clique| {
+        let mut clique = clique.to_vec();
+        clique.sort_unstable();
+        cliques.push(graph.to_nodes(&clique));
+    });
+    cliques.sort();
+    cliques
+}
+
+/// The largest clique (the first one, in sorted order, if there's a tie). It's empty if there
+/// are no nodes.
+pub fn maximum_clique<N: Clone + Hash + Ord>(adjacency: &HashMap<N, HashSet<N>>) -> Vec<N> {
+    let graph = Graph::new(adjacency);
+    let mut best: Vec<usize> = vec![];
+    graph.maximal_cliques(|clique| {
+        let mut clique = clique.to_vec();
+        clique.sort_unstable();
+        if clique.len() > best.len() || (clique.len() == best.len() && clique < best) {
+            best = clique;
+        }
+    });
+    graph.to_nodes(&best)
+}
+
+/// Every clique with exactly `k` nodes, sorted.
+pub fn k_cliques<N: Clone + Hash + Ord>(
+    adjacency: &HashMap<N, HashSet<N>>,
+    k: usize,
+) -> Vec<Vec<N>> {
+    let graph = Graph::new(adjacency);
+    let mut all = BitSet::new(graph.nodes.len());
+    for node in 0..graph.nodes.len() {
+        all.insert(node);
+    }
+    let mut cliques = vec![];
+    graph.k_cliques(&mut vec![], &all, k, &mut |clique| {
+        cliques.push(graph.to_nodes(clique))
+    });
+    cliques
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn graph(edges: &[(u32, u32)]) -> HashMap<u32, HashSet<u32>> {
+        let mut adjacency: HashMap<u32, HashSet<u32>> = HashMap::new();
+        for &(a, b) in edges {
+            adjacency.entry(a).or_default().insert(b);
+        }
+        adjacency
+    }
+
+    #[test]
+    fn test_maximal_cliques() {
+        // A square 1-2-3-4 with the diagonal 1-3, a pendant 4-5 and a lone edge 6-7
+        let g = graph(&[(1, 2), (2, 3), (3, 4), (4, 1), (1, 3), (4, 5), (6, 7)]);
+        assert_eq!(
+            maximal_cliques(&g),
+            vec![vec![1, 2, 3], vec![1, 3, 4], vec![4, 5], vec![6, 7]]
+        );
+        assert_eq!(maximum_clique(&g), vec![1, 2, 3]);
+        assert_eq!(k_cliques(&g, 3), vec![vec![1, 2, 3], vec![1, 3, 4]]);
+        assert_eq!(k_cliques(&g, 2).len(), 7);
+        assert!(k_cliques(&g, 4).is_empty());
+    }
+
+    #[test]
+    fn test_complete_graph() {
+        let edges: Vec<(u32, u32)> = (0..70)
+            .flat_map(|a| (a + 1..70).map(move |b| (a, b)))
+            .collect();
+        let g = graph(&edges);
+        assert_eq!(maximum_clique(&g), (0..70).collect::<Vec<u32>>());
+        assert_eq!(maximal_cliques(&g).len(), 1);
+        assert_eq!(k_cliques(&g, 3).len(), 70 * 69 * 68 / 6);
+        assert_eq!(maximum_clique(&HashMap::<u32, HashSet<u32>>::new()), vec![]);
+    }
+}
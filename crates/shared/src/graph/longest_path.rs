@@ -0,0 +1,367 @@
+//! The longest simple path (i.e. one that never revisits a node) between two nodes of a
+//! directed, weighted graph. This is NP-hard in general, so it's a pruned exhaustive search,
+//! which is fast enough for mazes once the corridors have been contracted away.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// A graph that only has the junctions of the original, with a weighted edge for each corridor
+/// between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractedGraph {
+    /// The original node that each junction came from, in increasing order.
+    pub nodes: Vec<usize>,
+    /// The `(junction, weight)` edges leaving each junction.
+    pub adjacency: Vec<Vec<(usize, u64)>>,
+}
+
+impl ContractedGraph {
+    /// The junction for an original node, if it is one.
+    pub fn index_of(&self, node: usize) -> Option<usize> {
+        self.nodes.binary_search(&node).ok()
+    }
+}
+
+/// Contract the directed graph with nodes `0..num_nodes` down to its junctions. A node is part
+/// of a corridor if it's connected to exactly two other nodes (ignoring direction) and isn't in
+/// `keep`; every other node is a junction, except for ones with no edges at all (which can't
+/// be on any path) that aren't in `keep`. A corridor can only be followed in the directions
+/// that all of its edges allow. When there are several edges between two junctions, only the
+/// heaviest is kept, since the others can never be part of a longest path.
+pub fn contract_corridors(
+    num_nodes: usize,
+    edges: impl IntoIterator<Item = (usize, usize, u64)>,
+    keep: &[usize],
+) -> ContractedGraph {
+    let mut out_edges: Vec<Vec<(usize, u64)>> = vec![vec![]; num_nodes];
+    let mut neighbors: Vec<Vec<usize>> = vec![vec![]; num_nodes];
+    for (from, to, weight) in edges {
+        out_edges[from].push((to, weight));
+        for (a, b) in [(from, to), (to, from)] {
+            if !neighbors[a].contains(&b) {
+                neighbors[a].push(b);
+            }
+        }
+    }
+    let is_corridor = |node: usize| neighbors[node].len() == 2 && !keep.contains(&node);
+
+    let is_isolated = |node: usize| neighbors[node].is_empty() && !keep.contains(&node);
+
+    let nodes: Vec<usize> = (0..num_nodes)
+        .filter(|&node| !is_corridor(node) && !is_isolated(node))
+        .collect();
+    let mut graph = ContractedGraph {
+        adjacency: vec![vec![]; nodes.len()],
+        nodes,
+    };
+    for i in 0..graph.nodes.len() {
+        let junction = graph.nodes[i];
+        for &(first, first_weight) in &out_edges[junction] {
+            // Walk along the corridor until reaching another junction (or a one way edge that
+            // goes the wrong way). The length check stops corridors that loop forever.
+            let (mut prev, mut curr, mut dist) = (junction, first, first_weight);
+            let mut blocked = false;
+            for _ in 0..num_nodes {
+                if !is_corridor(curr) {
+                    break;
+                }
+                let next = if neighbors[curr][0] == prev {
+                    neighbors[curr][1]
+                } else {
+                    neighbors[curr][0]
+                };
+                match out_edges[curr].iter().find(|(to, _)| *to == next) {
+                    Some(&(_, weight)) => (prev, curr, dist) = (curr, next, dist + weight),
+                    None => {
+                        blocked = true;
+                        break;
+                    }
+                }
+            }
+            if blocked || is_corridor(curr) || curr == junction {
+                continue;
+            }
+            let to = graph.index_of(curr).unwrap();
+            match graph.adjacency[i].iter_mut().find(|(j, _)| *j == to) {
+                Some((_, weight)) => *weight = (*weight).max(dist),
+                None => graph.adjacency[i].push((to, dist)),
+            }
+        }
+    }
+    graph
+}
+
+/// The nodes visited so far, as a bitmask when it fits in one word.
+trait Visited: Clone + Send + Sync {
+    fn empty(num_nodes: usize) -> Self;
+    fn contains(&self, node: usize) -> bool;
+    fn insert(&mut self, node: usize);
+    fn remove(&mut self, node: usize);
+}
+
+impl Visited for u64 {
+    fn empty(_: usize) -> Self {
+        0
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        self & (1 << node) != 0
+    }
+
+    fn insert(&mut self, node: usize) {
+        *self |= 1 << node;
+    }
+
+    fn remove(&mut self, node: usize) {
+        *self &= !(1 << node);
+    }
+}
+
+impl Visited for Vec<bool> {
+    fn empty(num_nodes: usize) -> Self {
+        vec![false; num_nodes]
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        self[node]
+    }
+
+    fn insert(&mut self, node: usize) {
+        self[node] = true;
+    }
+
+    fn remove(&mut self, node: usize) {
+        self[node] = false;
+    }
+}
+
+/// A partial path from the start.
+#[derive(Clone)]
+struct State<V> {
+    node: usize,
+    len: u64,
+    visited: V,
+    /// An upper bound on how much longer the path can get: the heaviest edge into each node
+    /// that hasn't been visited, since the rest of the path enters each of them at most once.
+    bound: u64,
+}
+
+struct Search<'a> {
+    adjacency: &'a [Vec<(usize, u64)>],
+    end: usize,
+    max_in: Vec<u64>,
+    /// One more than the longest path found so far, or zero if there isn't one yet. This is
+    /// shared between threads so that they can all prune using each other's results.
+    best: AtomicU64,
+}
+
+impl Search<'_> {
+    fn can_beat_best(&self, len: u64, bound: u64) -> bool {
+        len + bound + 1 > self.best.load(Ordering::Relaxed)
+    }
+
+    /// Move along every edge out of `state` (without checking whether they're worth trying).
+    fn step<V: Visited>(&self, state: &State<V>) -> Vec<State<V>> {
+        self.adjacency[state.node]
+            .iter()
+            .filter(|(next, _)| !state.visited.contains(*next))
+            .map(|&(next, weight)| {
+                let mut visited = state.visited.clone();
+                visited.insert(next);
+                State {
+                    node: next,
+                    len: state.len + weight,
+                    visited,
+                    bound: state.bound - self.max_in[next],
+                }
+            })
+            .collect()
+    }
+
+    fn dfs<V: Visited>(&self, node: usize, len: u64, visited: &mut V, bound: u64) {
+        if node == self.end {
+            self.best.fetch_max(len + 1, Ordering::Relaxed);
+            return;
+        }
+        if !self.can_beat_best(len, bound) {
+            return;
+        }
+        for &(next, weight) in &self.adjacency[node] {
+            if !visited.contains(next) {
+                visited.insert(next);
+                self.dfs(next, len + weight, visited, bound - self.max_in[next]);
+                visited.remove(next);
+            }
+        }
+    }
+
+    fn run<V: Visited>(&self, start: usize, num_threads: usize) {
+        let num_nodes = self.adjacency.len();
+        let mut visited = V::empty(num_nodes);
+        visited.insert(start);
+        let mut frontier = vec![State {
+            node: start,
+            len: 0,
+            bound: self.max_in.iter().sum::<u64>() - self.max_in[start],
+            visited,
+        }];
+
+        // Split the search up by extending the partial paths a step at a time until there's
+        // plenty of work to go around.
+        if num_threads > 1 {
+            let mut depth = 0;
+            while frontier.len() < 16 * num_threads && depth < num_nodes {
+                let mut next_frontier = vec![];
+                for state in &frontier {
+                    if state.node == self.end {
+                        self.best.fetch_max(state.len + 1, Ordering::Relaxed);
+                    } else {
+                        next_frontier.extend(self.step(state));
+                    }
+                }
+                frontier = next_frontier;
+                depth += 1;
+            }
+        }
+
+        let next_job = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..num_threads.clamp(1, frontier.len().max(1)) {
+                scope.spawn(|| {
+                    while let Some(state) = frontier.get(next_job.fetch_add(1, Ordering::Relaxed)) {
+                        let mut visited = state.visited.clone();
+                        self.dfs(state.node, state.len, &mut visited, state.bound);
+                    }
+                });
+            }
+        });
+    }
+}
+
+/// The length of the longest path from `start` to `end` that doesn't visit any node twice, or
+/// `None` if there's no path at all. `adjacency[node]` has the `(next node, weight)` edges out
+/// of `node`.
+pub fn longest_path(adjacency: &[Vec<(usize, u64)>], start: usize, end: usize) -> Option<u64> {
+    longest_path_parallel(adjacency, start, end, 1)
+}
+
+/// Like [`longest_path`], but with the search split across `num_threads` threads.
+pub fn longest_path_parallel(
+    adjacency: &[Vec<(usize, u64)>],
+    start: usize,
+    end: usize,
+    num_threads: usize,
+) -> Option<u64> {
+    let mut max_in = vec![0; adjacency.len()];
+    for edges in adjacency {
+        for &(next, weight) in edges {
+            max_in[next] = max_in[next].max(weight);
+        }
+    }
+    let search = Search {
+        adjacency,
+        end,
+        max_in,
+        best: AtomicU64::new(0),
+    };
+    if adjacency.len() <= 64 {
+        search.run::<u64>(start, num_threads);
+    } else {
+        search.run::<Vec<bool>>(start, num_threads);
+    }
+    search.best.into_inner().checked_sub(1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn undirected(num_nodes: usize, edges: &[(usize, usize, u64)]) -> Vec<Vec<(usize, u64)>> {
+        let mut adjacency = vec![vec![]; num_nodes];
+        for &(a, b, w) in edges {
+            adjacency[a].push((b, w));
+            adjacency[b].push((a, w));
+        }
+        adjacency
+    }
+
+    #[test]
+    fn test_longest_path() {
+        // 0 - 1 - 3 is short, 0 - 2 - 1 - 3 is longer
+        let adjacency = undirected(5, &[(0, 1, 1), (1, 3, 1), (0, 2, 5), (2, 1, 5)]);
+        assert_eq!(longest_path(&adjacency, 0, 3), Some(11));
+        assert_eq!(longest_path(&adjacency, 0, 0), Some(0));
+        assert_eq!(longest_path(&adjacency, 0, 4), None);
+        for num_threads in 1..4 {
+            assert_eq!(
+                longest_path_parallel(&adjacency, 0, 3, num_threads),
+                Some(11)
+            );
+        }
+    }
+
+    #[test]
+    fn test_longest_path_grid() {
+        // On a 3 x n grid with unit weights, a path between opposite corners can visit every
+        // node. Big enough to need more than one word for the visited nodes.
+        let n = 30;
+        let mut edges = vec![];
+        for i in 0..3 {
+            for j in 0..n {
+                if i + 1 < 3 {
+                    edges.push((i * n + j, (i + 1) * n + j, 1));
+                }
+                if j + 1 < n {
+                    edges.push((i * n + j, i * n + j + 1, 1));
+                }
+            }
+        }
+        let adjacency = undirected(3 * n, &edges);
+        assert_eq!(
+            longest_path(&adjacency, 0, 3 * n - 1),
+            Some(3 * n as u64 - 1)
+        );
+        assert_eq!(
+            longest_path_parallel(&adjacency, 0, 3 * n - 1, 4),
+            Some(3 * n as u64 - 1)
+        );
+    }
+
+    #[test]
+    fn test_contract_corridors() {
+        // A loop 0 -> 1 -> 2 -> 3 -> 0 with a one way tail 3 -> 4 -> 5, keeping 0 and 5.
+        let edges = [
+            (0, 1, 1),
+            (1, 0, 1),
+            (1, 2, 1),
+            (2, 1, 1),
+            (2, 3, 1),
+            (3, 2, 1),
+            (3, 0, 2),
+            (0, 3, 2),
+            (3, 4, 1),
+            (4, 5, 1),
+        ];
+        // Node 6 has no edges, so it's dropped
+        let graph = contract_corridors(7, edges, &[0, 5]);
+        assert_eq!(graph.nodes, vec![0, 3, 5]);
+        let (a, b, c) = (0, 1, 2);
+        let mut adjacency = graph.adjacency.clone();
+        adjacency.iter_mut().for_each(|edges| edges.sort());
+        // The two corridors between 0 and 3 collapse into the longer one
+        assert_eq!(adjacency[a], vec![(b, 3)]);
+        assert_eq!(adjacency[b], vec![(a, 3), (c, 2)]);
+        assert_eq!(adjacency[c], vec![]);
+        assert_eq!(graph.index_of(3), Some(b));
+        assert_eq!(graph.index_of(4), None);
+        assert_eq!(graph.index_of(6), None);
+        assert_eq!(longest_path(&graph.adjacency, a, c), Some(5));
+
+        // Going against a one way edge doesn't work
+        let graph = contract_corridors(3, [(0, 1, 1), (2, 1, 1)], &[0, 2]);
+        assert_eq!(graph.adjacency, vec![vec![], vec![]]);
+
+        // Unless it's kept
+        let graph = contract_corridors(3, [(0, 1, 1), (1, 0, 1)], &[0, 1, 2]);
+        assert_eq!(graph.nodes, vec![0, 1, 2]);
+    }
+}
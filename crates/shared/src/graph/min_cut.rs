@@ -0,0 +1,174 @@
+//! The global minimum cut of an undirected, weighted graph, using the Stoer–Wagner algorithm.
+//! https://en.wikipedia.org/wiki/Stoer%E2%80%93Wagner_algorithm
+
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::search::Cost;
+
+/// The cheapest way to split the nodes into two non-empty sides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinCut<W> {
+    /// The total weight of the edges between the two sides.
+    pub weight: W,
+    /// The nodes on each side, sorted.
+    pub sides: (Vec<usize>, Vec<usize>),
+    /// The edges between the two sides, as they were given.
+    pub cut_edges: Vec<(usize, usize, W)>,
+}
+
+/// Find a minimum cut of the graph with nodes `0..num_nodes`. Parallel edges add up, and
+/// self loops are ignored. Returns `None` if there are fewer than two nodes.
+///
+/// Each phase uses a heap, so this takes `O(n m log n)` time and `O(n + m)` memory.
+pub fn min_cut<W: Cost>(
+    num_nodes: usize,
+    edges: impl IntoIterator<Item = (usize, usize, W)>,
+) -> Option<MinCut<W>> {
+    if num_nodes < 2 {
+        return None;
+    }
+    let edges: Vec<(usize, usize, W)> = edges.into_iter().collect();
+
+    // The weights between the merged nodes that are still around.
+    let mut adj: Vec<HashMap<usize, W>> = vec![HashMap::new(); num_nodes];
+    for &(a, b, w) in &edges {
+        if a != b {
+            let weight = adj[a].entry(b).or_default();
+            *weight = *weight + w;
+            let weight = adj[b].entry(a).or_default();
+            *weight = *weight + w;
+        }
+    }
+    // The original nodes that each merged node is made of.
+    let mut members: Vec<Vec<usize>> = (0..num_nodes).map(|node| vec![node]).collect();
+    let mut active: Vec<usize> = (0..num_nodes).collect();
+
+    let mut best: Option<(W, Vec<usize>)> = None;
+    let mut connectivity = vec![W::default(); num_nodes];
+    let mut added = vec![false; num_nodes];
+    while active.len() > 1 {
+        // Minimum cut phase: keep adding the node that's most tightly connected to everything
+        // added so far. The cut between the last node and the rest is a minimum cut between
+        // the last two nodes.
+        let mut heap = BinaryHeap::new();
+        for &node in &active {
+            connectivity[node] = W::default();
+            added[node] = false;
+            heap.push((W::default(), node));
+        }
+        let (mut s, mut t) = (usize::MAX, usize::MAX);
+        while let Some((weight, node)) = heap.pop() {
+            if added[node] || weight != connectivity[node] {
+                continue;
+            }
+            added[node] = true;
+            (s, t) = (t, node);
+            for (&neighbor, &w) in &adj[node] {
+                if !added[neighbor] {
+                    connectivity[neighbor] = connectivity[neighbor] + w;
+                    heap.push((connectivity[neighbor], neighbor));
+                }
+            }
+        }
+
+        if best
+            .as_ref()
+            .is_none_or(|(weight, _)| connectivity[t] < *weight)
+        {
+            best = Some((connectivity[t], members[t].clone()));
+        }
+
+        // Merge t into s.
+        let t_edges = std::mem::take(&mut adj[t]);
+        for (neighbor, w) in t_edges {
+            adj[neighbor].remove(&t);
+            if neighbor != s {
+                let weight = adj[s].entry(neighbor).or_default();
+                *weight = *weight + w;
+                let weight = adj[neighbor].entry(s).or_default();
+                *weight = *weight + w;
+            }
+        }
+        let t_members = std::mem::take(&mut members[t]);
+        members[s].extend(t_members);
+        active.retain(|&node| node != t);
+    }
+
+    let (weight, side) = best.unwrap();
+    let mut in_side = vec![false; num_nodes];
+    for &node in &side {
+        in_side[node] = true;
+    }
+    let (mut side_0, side_1): (Vec<usize>, Vec<usize>) =
+        (0..num_nodes).partition(|&node| in_side[node]);
+    side_0.sort_unstable();
+    let cut_edges = edges
+        .into_iter()
+        .filter(|&(a, b, _)| in_side[a] != in_side[b])
+        .collect();
+    Some(MinCut {
+        weight,
+        sides: (side_0, side_1),
+        cut_edges,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_min_cut() {
+        // Two triangles, joined by a single edge (and a heavier triangle on the right)
+        let edges = [
+            (0, 1, 1),
+            (1, 2, 1),
+            (2, 0, 1),
+            (3, 4, 5),
+            (4, 5, 5),
+            (5, 3, 5),
+            (2, 3, 1),
+        ];
+        let cut = min_cut(6, edges).unwrap();
+        assert_eq!(cut.weight, 1);
+        assert_eq!(cut.cut_edges, vec![(2, 3, 1)]);
+        let mut sides = [cut.sides.0, cut.sides.1];
+        sides.sort();
+        assert_eq!(sides, [vec![0, 1, 2], vec![3, 4, 5]]);
+    }
+
+    #[test]
+    fn test_min_cut_weighted() {
+        // The example from the original paper, whose minimum cut has weight 4
+        let edges = [
+            (1, 2, 2),
+            (1, 5, 3),
+            (2, 3, 3),
+            (2, 5, 2),
+            (2, 6, 2),
+            (3, 4, 4),
+            (3, 7, 2),
+            (4, 7, 2),
+            (4, 8, 2),
+            (5, 6, 3),
+            (6, 7, 1),
+            (7, 8, 3),
+        ];
+        let edges = edges.map(|(a, b, w)| (a - 1, b - 1, w));
+        let cut = min_cut(8, edges).unwrap();
+        assert_eq!(cut.weight, 4);
+        let mut sides = [cut.sides.0, cut.sides.1];
+        sides.sort();
+        assert_eq!(sides, [vec![0, 1, 4, 5], vec![2, 3, 6, 7]]);
+        assert_eq!(cut.cut_edges.iter().map(|e| e.2).sum::<i32>(), 4);
+    }
+
+    #[test]
+    fn test_min_cut_disconnected() {
+        let cut = min_cut(4, [(0, 1, 3_u64), (2, 3, 3)]).unwrap();
+        assert_eq!(cut.weight, 0);
+        assert!(cut.cut_edges.is_empty());
+        assert_eq!(cut.sides.0.len(), 2);
+        assert!(min_cut::<u64>(1, []).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+//! Topological sorting, i.e. ordering things so that every `(before, after)` rule holds.
+//!
+//! Rules that mention anything other than the nodes being sorted are ignored, so one big set
+//! of rules can be used to order lots of different subsets.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+
+/// The rules contradict each other. Each node in `cycle` has to come before the next one, and
+/// the last one has to come before the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError<N> {
+    pub cycle: Vec<N>,
+}
+
+impl<N: fmt::Display> fmt::Display for CycleError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Rules form a cycle: ")?;
+        for node in &self.cycle {
+            write!(f, "{node} -> ")?;
+        }
+        write!(f, "{}", self.cycle[0])
+    }
+}
+
+/// Why the nodes couldn't be sorted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToposortError<N> {
+    /// The node appears more than once, so there's no telling which copy the rules are about.
+    Repeated(N),
+    Cycle(CycleError<N>),
+}
+
+impl<N: fmt::Display> fmt::Display for ToposortError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToposortError::Repeated(node) => write!(f, "{node} appears more than once"),
+            ToposortError::Cycle(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// The position of each node, keeping the first if a node is repeated.
+fn positions<N: Hash + Eq>(nodes: &[N]) -> HashMap<&N, usize> {
+    let mut positions = HashMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        positions.entry(node).or_insert(i);
+    }
+    positions
+}
+
+/// Order `nodes` so that `before` comes first for every `(before, after)` rule, using Kahn's
+/// algorithm. When there's a choice, the node that's earliest in `nodes` goes first, so
+/// anything that's already in order stays where it is. Every node has to be different.
+/// https://en.wikipedia.org/wiki/Topological_sorting#Kahn's_algorithm
+pub fn toposort<N: Clone + Hash + Eq>(
+    nodes: &[N],
+    rules: impl IntoIterator<Item = (N, N)>,
+) -> Result<Vec<N>, ToposortError<N>> {
+    let positions = positions(nodes);
+    if let Some(i) = (0..nodes.len()).find(|&i| positions[&nodes[i]] != i) {
+        return Err(ToposortError::Repeated(nodes[i].clone()));
+    }
+    let mut successors: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+    let mut predecessors: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+    for (before, after) in rules {
+        if let (Some(&a), Some(&b)) = (positions.get(&before), positions.get(&after)) {
+            successors[a].push(b);
+            predecessors[b].push(a);
+        }
+    }
+
+    let mut num_predecessors: Vec<usize> = predecessors.iter().map(Vec::len).collect();
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..nodes.len())
+        .filter(|&i| num_predecessors[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = vec![];
+    let mut placed = vec![false; nodes.len()];
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(nodes[i].clone());
+        placed[i] = true;
+        for &next in &successors[i] {
+            num_predecessors[next] -= 1;
+            if num_predecessors[next] == 0 {
+                ready.push(Reverse(next));
+            }
+        }
+    }
+    if order.len() == nodes.len() {
+        return Ok(order);
+    }
+
+    // Everything left over has a predecessor that's also left over, so walking backwards
+    // through them has to end up going round a cycle.
+    let mut node = placed.iter().position(|&placed| !placed).unwrap();
+    let mut seen_at = HashMap::new();
+    let mut path = vec![];
+    while !seen_at.contains_key(&node) {
+        seen_at.insert(node, path.len());
+        path.push(node);
+        node = *predecessors[node].iter().find(|&&p| !placed[p]).unwrap();
+    }
+    let mut cycle: Vec<N> = path[seen_at[&node]..]
+        .iter()
+        .map(|&i| nodes[i].clone())
+        .collect();
+    cycle.reverse();
+    Err(ToposortError::Cycle(CycleError { cycle }))
+}
+
+/// Whether `nodes` are already in an order that satisfies every rule between them.
+pub fn is_ordered<N: Hash + Eq>(nodes: &[N], rules: impl IntoIterator<Item = (N, N)>) -> bool {
+    let positions = positions(nodes);
+    rules.into_iter().all(
+        |(before, after)| match (positions.get(&before), positions.get(&after)) {
+            (Some(a), Some(b)) => a < b,
+            _ => true,
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_toposort() {
+        let rules = [(5, 1), (1, 3), (2, 3), (9, 9_u32)];
+        assert_eq!(toposort(&[1, 2, 3, 4, 5], rules), Ok(vec![2, 4, 5, 1, 3]));
+        // Only the rules between these nodes matter
+        assert_eq!(toposort(&[3, 2, 1], rules), Ok(vec![2, 1, 3]));
+        assert_eq!(toposort(&[4, 1, 2], rules), Ok(vec![4, 1, 2]));
+        assert!(is_ordered(&[4, 1, 2], rules));
+        assert!(is_ordered(&[5, 2, 1, 3], rules));
+        assert!(!is_ordered(&[1, 5], rules));
+        assert_eq!(toposort::<u32>(&[], rules), Ok(vec![]));
+        assert_eq!(toposort(&[5, 1, 5], rules), Err(ToposortError::Repeated(5)));
+        assert_eq!(
+            ToposortError::Repeated(5).to_string(),
+            "5 appears more than once"
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let rules = [("a", "b"), ("b", "c"), ("c", "d"), ("d", "b"), ("x", "a")];
+        let Err(ToposortError::Cycle(mut error)) = toposort(&["x", "a", "b", "c", "d"], rules)
+        else {
+            panic!("Expected a cycle");
+        };
+        // The cycle can start anywhere, but it has to go in the right direction
+        let start = error.cycle.iter().position(|&n| n == "b").unwrap();
+        error.cycle.rotate_left(start);
+        assert_eq!(error.cycle, vec!["b", "c", "d"]);
+        assert_eq!(
+            CycleError { cycle: vec![1, 2] }.to_string(),
+            "Rules form a cycle: 1 -> 2 -> 1"
+        );
+
+        assert_eq!(
+            toposort(&[7], [(7, 7)]),
+            Err(ToposortError::Cycle(CycleError { cycle: vec![7] }))
+        );
+    }
+}
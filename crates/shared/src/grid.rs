@@ -0,0 +1,354 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::{Dir4, Dir8};
+
+/// A rectangular 2D grid, stored row-major in a single flat `Vec`.
+///
+/// Positions are `(i, j)` pairs where `i` is the row and `j` is the column.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    elements: Vec<T>,
+    num_rows: usize,
+    num_cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid from a list of rows. Panics if the rows are not all the same length.
+    pub fn new(rows: Vec<Vec<T>>) -> Grid<T> {
+        let num_rows = rows.len();
+        let num_cols = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == num_cols),
+            "All rows of a grid must have the same length"
+        );
+        let elements = rows.into_iter().flatten().collect();
+        Grid {
+            elements,
+            num_rows,
+            num_cols,
+        }
+    }
+
+    /// Create a grid by calling `f(i, j)` for every position.
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(
+        num_rows: usize,
+        num_cols: usize,
+        mut f: F,
+    ) -> Grid<T> {
+        let mut elements = Vec::with_capacity(num_rows * num_cols);
+        for i in 0..num_rows {
+            for j in 0..num_cols {
+                elements.push(f(i, j));
+            }
+        }
+        Grid {
+            elements,
+            num_rows,
+            num_cols,
+        }
+    }
+
+    /// Parse a block of text into a grid, one row per line, converting each character with `f`.
+    pub fn parse<F: FnMut(char) -> T>(contents: &str, mut f: F) -> Grid<T> {
+        Grid::new(
+            contents
+                .trim()
+                .lines()
+                .map(|line| line.chars().map(&mut f).collect())
+                .collect(),
+        )
+    }
+
+    /// Like [`Grid::parse`], but the conversion can fail. The first failure is returned.
+    pub fn try_parse<E, F: FnMut(char) -> Result<T, E>>(
+        contents: &str,
+        mut f: F,
+    ) -> Result<Grid<T>, E> {
+        let rows = contents
+            .trim()
+            .lines()
+            .map(|line| line.chars().map(&mut f).collect::<Result<Vec<T>, E>>())
+            .collect::<Result<Vec<Vec<T>>, E>>()?;
+        Ok(Grid::new(rows))
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn in_bounds(&self, i: usize, j: usize) -> bool {
+        i < self.num_rows && j < self.num_cols
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> Option<&T> {
+        if self.in_bounds(i, j) {
+            Some(&self.elements[i * self.num_cols + j])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, i: usize, j: usize) -> Option<&mut T> {
+        if self.in_bounds(i, j) {
+            Some(&mut self.elements[i * self.num_cols + j])
+        } else {
+            None
+        }
+    }
+
+    /// Set the value at `(i, j)`, returning `false` if the position is out of bounds.
+    pub fn set(&mut self, i: usize, j: usize, value: T) -> bool {
+        match self.get_mut(i, j) {
+            Some(element) => {
+                *element = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterate over all positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let num_cols = self.num_cols;
+        (0..self.num_rows).flat_map(move |i| (0..num_cols).map(move |j| (i, j)))
+    }
+
+    /// Iterate over all elements in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.elements.iter()
+    }
+
+    /// Iterate over `((i, j), element)` pairs in row-major order.
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.elements.iter())
+    }
+
+    /// The position of the first element (in row-major order) matching the predicate.
+    pub fn position<P: FnMut(&T) -> bool>(&self, predicate: P) -> Option<(usize, usize)> {
+        self.elements
+            .iter()
+            .position(predicate)
+            .map(|k| (k / self.num_cols, k % self.num_cols))
+    }
+
+    /// Take one step from `(i, j)` in `dir`, returning `None` if that leaves the grid.
+    pub fn step<D: Into<Dir8>>(&self, i: usize, j: usize, dir: D) -> Option<(usize, usize)> {
+        dir.into().step(i, j).filter(|&(a, b)| self.in_bounds(a, b))
+    }
+
+    /// The in-bounds up, right, down and left neighbours of `(i, j)`.
+    pub fn neighbors4(&self, i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        self.neighbors4_with_dir(i, j).map(|(_, pos)| pos)
+    }
+
+    /// Like [`Grid::neighbors4`], but also yields the direction of each neighbour.
+    pub fn neighbors4_with_dir(
+        &self,
+        i: usize,
+        j: usize,
+    ) -> impl Iterator<Item = (Dir4, (usize, usize))> + use<T> {
+        let (num_rows, num_cols) = (self.num_rows, self.num_cols);
+        Dir4::ALL.into_iter().filter_map(move |dir| {
+            let (a, b) = dir.step(i, j)?;
+            (a < num_rows && b < num_cols).then_some((dir, (a, b)))
+        })
+    }
+
+    /// Like [`Grid::neighbors8`], but also yields the direction of each neighbour.
+    pub fn neighbors8_with_dir(
+        &self,
+        i: usize,
+        j: usize,
+    ) -> impl Iterator<Item = (Dir8, (usize, usize))> + use<T> {
+        let (num_rows, num_cols) = (self.num_rows, self.num_cols);
+        Dir8::ALL.into_iter().filter_map(move |dir| {
+            let (a, b) = dir.step(i, j)?;
+            (a < num_rows && b < num_cols).then_some((dir, (a, b)))
+        })
+    }
+
+    /// The in-bounds neighbours of `(i, j)`, including diagonals, clockwise from up.
+    pub fn neighbors8(&self, i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        self.neighbors8_with_dir(i, j).map(|(_, pos)| pos)
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.elements[i * self.num_cols..(i + 1) * self.num_cols]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a chunk size of 0, and there are no elements to chunk in that case.
+        self.elements.chunks(self.num_cols.max(1))
+    }
+
+    pub fn col(&self, j: usize) -> impl Iterator<Item = &T> {
+        assert!(j < self.num_cols, "Column {j} out of bounds");
+        self.elements.iter().skip(j).step_by(self.num_cols)
+    }
+
+    /// Elements along the down-right diagonal starting at `(i, j)`.
+    pub fn diagonal(&self, i: usize, j: usize) -> impl Iterator<Item = &T> {
+        (0..).map_while(move |k| self.get(i + k, j + k))
+    }
+
+    /// Elements along the down-left diagonal starting at `(i, j)`.
+    pub fn anti_diagonal(&self, i: usize, j: usize) -> impl Iterator<Item = &T> {
+        (0..).map_while(move |k| self.get(i + k, j.checked_sub(k)?))
+    }
+
+    /// Apply `f` to every element, keeping the shape.
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            elements: self.elements.iter().map(f).collect(),
+            num_rows: self.num_rows,
+            num_cols: self.num_cols,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Create a grid where every element is `value`.
+    pub fn filled(num_rows: usize, num_cols: usize, value: T) -> Grid<T> {
+        Grid {
+            elements: vec![value; num_rows * num_cols],
+            num_rows,
+            num_cols,
+        }
+    }
+
+    pub fn transpose(&self) -> Grid<T> {
+        Grid::from_fn(self.num_cols, self.num_rows, |i, j| self[(j, i)].clone())
+    }
+
+    /// Rotate 90 degrees clockwise.
+    pub fn rotate_cw(&self) -> Grid<T> {
+        let n = self.num_rows;
+        Grid::from_fn(self.num_cols, self.num_rows, |i, j| {
+            self[(n - 1 - j, i)].clone()
+        })
+    }
+
+    /// Rotate 90 degrees counter-clockwise.
+    pub fn rotate_ccw(&self) -> Grid<T> {
+        let m = self.num_cols;
+        Grid::from_fn(self.num_cols, self.num_rows, |i, j| {
+            self[(j, m - 1 - i)].clone()
+        })
+    }
+
+    /// Mirror left-to-right, i.e. reverse every row.
+    pub fn flip_horizontal(&self) -> Grid<T> {
+        let m = self.num_cols;
+        Grid::from_fn(self.num_rows, self.num_cols, |i, j| {
+            self[(i, m - 1 - j)].clone()
+        })
+    }
+
+    /// Mirror top-to-bottom, i.e. reverse the order of the rows.
+    pub fn flip_vertical(&self) -> Grid<T> {
+        let n = self.num_rows;
+        Grid::from_fn(self.num_rows, self.num_cols, |i, j| {
+            self[(n - 1 - i, j)].clone()
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        self.get(i, j).unwrap_or_else(|| {
+            panic!(
+                "Position ({i}, {j}) out of bounds for {}x{} grid",
+                self.num_rows, self.num_cols
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        let (num_rows, num_cols) = (self.num_rows, self.num_cols);
+        self.get_mut(i, j).unwrap_or_else(|| {
+            panic!("Position ({i}, {j}) out of bounds for {num_rows}x{num_cols} grid")
+        })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for element in row {
+                write!(f, "{element}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example() -> Grid<char> {
+        Grid::parse("abc\ndef\n", |c| c)
+    }
+
+    #[test]
+    fn test_get() {
+        let grid = example();
+        assert_eq!(grid.num_rows(), 2);
+        assert_eq!(grid.num_cols(), 3);
+        assert_eq!(grid.get(0, 2), Some(&'c'));
+        assert_eq!(grid.get(1, 0), Some(&'d'));
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.get(0, 3), None);
+        assert_eq!(grid[(1, 2)], 'f');
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = example();
+        assert_eq!(
+            grid.neighbors4(0, 0).collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(
+            grid.neighbors8(1, 1).collect::<Vec<_>>(),
+            vec![(0, 1), (0, 2), (1, 2), (1, 0), (0, 0)]
+        );
+        assert_eq!(
+            grid.neighbors4_with_dir(1, 2).collect::<Vec<_>>(),
+            vec![(Dir4::Up, (0, 2)), (Dir4::Left, (1, 1))]
+        );
+        assert_eq!(grid.step(0, 0, Dir4::Right), Some((0, 1)));
+        assert_eq!(grid.step(0, 0, Dir8::DownRight), Some((1, 1)));
+        assert_eq!(grid.step(1, 2, Dir4::Right), None);
+    }
+
+    #[test]
+    fn test_views() {
+        let grid = example();
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.col(1).collect::<String>(), "be");
+        assert_eq!(grid.diagonal(0, 1).collect::<String>(), "bf");
+        assert_eq!(grid.anti_diagonal(0, 1).collect::<String>(), "bd");
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = example();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.flip_horizontal().to_string(), "cba\nfed\n");
+        assert_eq!(grid.flip_vertical().to_string(), "def\nabc\n");
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+    }
+}
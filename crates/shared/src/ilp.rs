@@ -0,0 +1,415 @@
+//! Integer linear programming over non-negative integers.
+//!
+//! The LP relaxations are solved exactly with a two phase simplex over [`Rational`]s (using
+//! Bland's rule, so it can't cycle), and integrality is enforced with branch and bound.
+//! This is aimed at the small "minimise the number of presses" systems that come up in
+//! puzzles, not at anything with thousands of variables.
+
+use crate::num::{Int, Rational};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Relation {
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Constraint<T> {
+    coefficients: Vec<Rational<T>>,
+    relation: Relation,
+    rhs: Rational<T>,
+}
+
+/// Minimise `objective · x` subject to linear constraints, where every `x_i` is a
+/// non-negative integer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegerProgram<T> {
+    objective: Vec<Rational<T>>,
+    constraints: Vec<Constraint<T>>,
+    node_limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegerSolution<T> {
+    pub values: Vec<T>,
+    pub objective: Rational<T>,
+    /// Whether the search finished, proving that no better solution exists. This can only be
+    /// false if a node limit was set.
+    pub proven_optimal: bool,
+}
+
+impl<T: Int> IntegerProgram<T> {
+    /// To maximise instead, negate the objective.
+    pub fn minimize(objective: Vec<Rational<T>>) -> IntegerProgram<T> {
+        IntegerProgram {
+            objective,
+            constraints: vec![],
+            node_limit: None,
+        }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.objective.len()
+    }
+
+    /// Add the constraint `coefficients · x <relation> rhs`.
+    pub fn add_constraint(
+        &mut self,
+        coefficients: Vec<Rational<T>>,
+        relation: Relation,
+        rhs: Rational<T>,
+    ) {
+        assert_eq!(
+            coefficients.len(),
+            self.num_vars(),
+            "Expected one coefficient per variable"
+        );
+        self.constraints.push(Constraint {
+            coefficients,
+            relation,
+            rhs,
+        });
+    }
+
+    /// Give up after solving this many LP relaxations, returning the best solution found so
+    /// far without a proof that it is optimal.
+    pub fn set_node_limit(&mut self, node_limit: usize) {
+        self.node_limit = Some(node_limit);
+    }
+
+    /// Find an optimal assignment with branch and bound. Returns `None` if there is no
+    /// integer solution (or none was found before hitting the node limit).
+    ///
+    /// Panics if the LP relaxation is unbounded, since then there's no optimum to find.
+    pub fn solve(&self) -> Option<IntegerSolution<T>> {
+        // With an integral objective, the objective of any integer solution is an integer,
+        // so a relaxation only needs to be explored if its ceiling beats the best so far.
+        let integral_objective = self.objective.iter().all(|c| c.integral().is_some());
+        let mut best: Option<(Vec<T>, Rational<T>)> = None;
+        let mut nodes = 0;
+        let mut proven_optimal = true;
+        // Each node is the extra bounds added by branching on fractional variables.
+        let mut to_visit: Vec<Vec<Constraint<T>>> = vec![vec![]];
+        while let Some(bounds) = to_visit.pop() {
+            if self.node_limit.is_some_and(|limit| nodes >= limit) {
+                proven_optimal = false;
+                break;
+            }
+            nodes += 1;
+            let constraints = self.constraints.iter().chain(bounds.iter());
+            let (x, value) = match solve_relaxation(&self.objective, constraints) {
+                Relaxation::Infeasible => continue,
+                Relaxation::Unbounded => panic!("The LP relaxation is unbounded"),
+                Relaxation::Optimal(x, value) => (x, value),
+            };
+            let lower_bound = if integral_objective {
+                Rational::whole_number(value.ceil())
+            } else {
+                value.clone()
+            };
+            if best
+                .as_ref()
+                .is_some_and(|(_, best_value)| &lower_bound >= best_value)
+            {
+                continue;
+            }
+            match x.iter().position(|x_i| x_i.integral().is_none()) {
+                None => {
+                    let values = x.iter().map(|x_i| x_i.integral().unwrap()).collect();
+                    best = Some((values, value));
+                }
+                Some(i) => {
+                    let floor = x[i].floor();
+                    let mut unit = vec![Rational::zero(); self.num_vars()];
+                    unit[i] = Rational::one();
+                    let mut up = bounds.clone();
+                    up.push(Constraint {
+                        coefficients: unit.clone(),
+                        relation: Relation::GreaterOrEqual,
+                        rhs: Rational::whole_number(floor.clone()) + Rational::one(),
+                    });
+                    let mut down = bounds;
+                    down.push(Constraint {
+                        coefficients: unit,
+                        relation: Relation::LessOrEqual,
+                        rhs: Rational::whole_number(floor),
+                    });
+                    // Depth first, rounding down first, tends to find a good solution early.
+                    to_visit.push(up);
+                    to_visit.push(down);
+                }
+            }
+        }
+        best.map(|(values, objective)| IntegerSolution {
+            values,
+            objective,
+            proven_optimal,
+        })
+    }
+}
+
+enum Relaxation<T> {
+    Infeasible,
+    Unbounded,
+    Optimal(Vec<Rational<T>>, Rational<T>),
+}
+
+/// A simplex tableau, with the right hand side as the last column.
+struct Tableau<T> {
+    rows: Vec<Vec<Rational<T>>>,
+    basis: Vec<usize>,
+}
+
+impl<T: Int> Tableau<T> {
+    fn rhs(&self, i: usize) -> &Rational<T> {
+        self.rows[i].last().unwrap()
+    }
+
+    fn pivot(&mut self, r: usize, c: usize) {
+        let lead = self.rows[r][c].clone();
+        for val in self.rows[r].iter_mut() {
+            *val /= lead.clone();
+        }
+        let pivot_row = self.rows[r].clone();
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            if i != r && !row[c].is_zero() {
+                let factor = row[c].clone();
+                for (val, pivot_val) in row.iter_mut().zip(pivot_row.iter()) {
+                    *val -= factor.clone() * pivot_val.clone();
+                }
+            }
+        }
+        self.basis[r] = c;
+    }
+
+    fn value(&self, cost: &[Rational<T>]) -> Rational<T> {
+        self.basis
+            .iter()
+            .enumerate()
+            .fold(Rational::zero(), |acc, (i, &b)| {
+                acc + cost[b].clone() * self.rhs(i).clone()
+            })
+    }
+
+    /// Minimise `cost`, only letting the first `num_allowed` columns enter the basis.
+    /// Returns false if the objective is unbounded.
+    fn optimize(&mut self, cost: &[Rational<T>], num_allowed: usize) -> bool {
+        loop {
+            // Bland's rule: the first column with a negative reduced cost enters...
+            let entering = (0..num_allowed).find(|&j| {
+                !self.basis.contains(&j) && {
+                    let reduced_cost = self
+                        .basis
+                        .iter()
+                        .enumerate()
+                        .fold(cost[j].clone(), |acc, (i, &b)| {
+                            acc - cost[b].clone() * self.rows[i][j].clone()
+                        });
+                    reduced_cost.is_negative()
+                }
+            });
+            let Some(c) = entering else {
+                return true;
+            };
+            // ...and the row with the smallest ratio leaves, breaking ties by the smallest
+            // basis index.
+            let leaving = (0..self.rows.len())
+                .filter(|&i| self.rows[i][c] > Rational::zero())
+                .min_by(|&i1, &i2| {
+                    let ratio1 = self.rhs(i1).clone() / self.rows[i1][c].clone();
+                    let ratio2 = self.rhs(i2).clone() / self.rows[i2][c].clone();
+                    ratio1
+                        .cmp(&ratio2)
+                        .then(self.basis[i1].cmp(&self.basis[i2]))
+                });
+            let Some(r) = leaving else {
+                return false;
+            };
+            self.pivot(r, c);
+        }
+    }
+}
+
+/// Solve the LP relaxation with the two phase simplex method.
+fn solve_relaxation<'a, T: Int + 'a>(
+    objective: &[Rational<T>],
+    constraints: impl Iterator<Item = &'a Constraint<T>>,
+) -> Relaxation<T> {
+    let constraints: Vec<&Constraint<T>> = constraints.collect();
+    let num_vars = objective.len();
+    let num_rows = constraints.len();
+    let num_slack = constraints
+        .iter()
+        .filter(|constraint| constraint.relation != Relation::Equal)
+        .count();
+    // Columns are the variables, then slack variables, then one artificial variable per row.
+    let num_real = num_vars + num_slack;
+    let width = num_real + num_rows;
+
+    let mut rows = vec![vec![Rational::zero(); width + 1]; num_rows];
+    let mut slack = num_vars;
+    for (i, constraint) in constraints.iter().enumerate() {
+        let row = &mut rows[i];
+        row[..num_vars].clone_from_slice(&constraint.coefficients);
+        row[width] = constraint.rhs.clone();
+        match constraint.relation {
+            Relation::LessOrEqual => row[slack] = Rational::one(),
+            Relation::GreaterOrEqual => row[slack] = -Rational::one(),
+            Relation::Equal => {}
+        }
+        if constraint.relation != Relation::Equal {
+            slack += 1;
+        }
+        // The artificial variables start off as the basis, so the right hand side must not
+        // be negative.
+        if row[width].is_negative() {
+            for val in row.iter_mut() {
+                *val = -val.clone();
+            }
+        }
+        row[num_real + i] = Rational::one();
+    }
+    let mut tableau = Tableau {
+        rows,
+        basis: (num_real..width).collect(),
+    };
+
+    // Phase one: find a feasible point by driving the artificial variables to zero.
+    let phase_one_cost: Vec<Rational<T>> = (0..width)
+        .map(|j| {
+            if j < num_real {
+                Rational::zero()
+            } else {
+                Rational::one()
+            }
+        })
+        .collect();
+    tableau.optimize(&phase_one_cost, width);
+    if !tableau.value(&phase_one_cost).is_zero() {
+        return Relaxation::Infeasible;
+    }
+    // Pivot any artificial variables left in the basis (at zero) out of it. If that's
+    // impossible then the row is redundant, and can be dropped.
+    let mut i = 0;
+    while i < tableau.rows.len() {
+        if tableau.basis[i] >= num_real {
+            match (0..num_real).find(|&j| !tableau.rows[i][j].is_zero()) {
+                Some(j) => tableau.pivot(i, j),
+                None => {
+                    tableau.rows.remove(i);
+                    tableau.basis.remove(i);
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    // Phase two: optimise the real objective, never letting an artificial variable back in.
+    let mut cost = objective.to_vec();
+    cost.resize(width, Rational::zero());
+    if !tableau.optimize(&cost, num_real) {
+        return Relaxation::Unbounded;
+    }
+    let mut x = vec![Rational::zero(); num_vars];
+    for (i, &b) in tableau.basis.iter().enumerate() {
+        if b < num_vars {
+            x[b] = tableau.rhs(i).clone();
+        }
+    }
+    let value = tableau.value(&cost);
+    Relaxation::Optimal(x, value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn whole(values: &[i64]) -> Vec<Rational<i64>> {
+        values.iter().copied().map(Rational::whole_number).collect()
+    }
+
+    #[test]
+    fn test_equality() {
+        // 3x + 5y = 19 only has the one non-negative integer solution
+        let mut program = IntegerProgram::minimize(whole(&[1, 1]));
+        program.add_constraint(whole(&[3, 5]), Relation::Equal, Rational::whole_number(19));
+        let solution = program.solve().unwrap();
+        assert_eq!(solution.values, vec![3, 2]);
+        assert_eq!(solution.objective, Rational::whole_number(5));
+        assert!(solution.proven_optimal);
+    }
+
+    #[test]
+    fn test_maximize() {
+        // The LP optimum is at (3, 1.5), but the best integer point is (4, 0)
+        let mut program = IntegerProgram::minimize(whole(&[-5, -4]));
+        program.add_constraint(
+            whole(&[6, 4]),
+            Relation::LessOrEqual,
+            Rational::whole_number(24),
+        );
+        program.add_constraint(
+            whole(&[1, 2]),
+            Relation::LessOrEqual,
+            Rational::whole_number(6),
+        );
+        let solution = program.solve().unwrap();
+        assert_eq!(solution.values, vec![4, 0]);
+        assert_eq!(solution.objective, Rational::whole_number(-20));
+    }
+
+    #[test]
+    fn test_greater_or_equal_and_redundant() {
+        // x + y >= 3 twice, and x - y = 1 (which is fine with negative right hand sides too)
+        let mut program = IntegerProgram::minimize(whole(&[2, 1]));
+        program.add_constraint(
+            whole(&[1, 1]),
+            Relation::GreaterOrEqual,
+            Rational::whole_number(3),
+        );
+        program.add_constraint(
+            whole(&[1, 1]),
+            Relation::GreaterOrEqual,
+            Rational::whole_number(3),
+        );
+        program.add_constraint(whole(&[-1, 1]), Relation::Equal, Rational::whole_number(-1));
+        let solution = program.solve().unwrap();
+        assert_eq!(solution.values, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // Feasible over the rationals, but not the integers
+        let mut program = IntegerProgram::minimize(whole(&[1, 1]));
+        program.add_constraint(whole(&[2, 2]), Relation::Equal, Rational::whole_number(3));
+        assert_eq!(program.solve(), None);
+
+        let mut program = IntegerProgram::minimize(whole(&[1]));
+        program.add_constraint(
+            whole(&[1]),
+            Relation::LessOrEqual,
+            Rational::whole_number(-1),
+        );
+        assert_eq!(program.solve(), None);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let mut program = IntegerProgram::minimize(whole(&[-5, -4]));
+        program.add_constraint(
+            whole(&[6, 4]),
+            Relation::LessOrEqual,
+            Rational::whole_number(24),
+        );
+        program.add_constraint(
+            whole(&[1, 2]),
+            Relation::LessOrEqual,
+            Rational::whole_number(6),
+        );
+        program.set_node_limit(3);
+        let solution = program.solve().unwrap();
+        assert!(!solution.proven_optimal);
+    }
+}
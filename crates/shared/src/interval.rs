@@ -0,0 +1,358 @@
+//! Sets of integers stored as sorted, disjoint half-open ranges, and piecewise translations
+//! of them.
+//!
+//! Everything uses `start..end` ranges (i.e. `end` is exclusive), so convert inclusive
+//! bounds with `lo..hi + 1`.
+
+use std::ops::Range;
+
+use crate::math::Integer;
+
+/// A set of integers, stored as sorted ranges that are disjoint and not adjacent (adjacent
+/// ranges are coalesced into one).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Integer> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { ranges: vec![] }
+    }
+
+    /// Sort and coalesce arbitrary (possibly empty or overlapping) ranges.
+    fn normalized(mut ranges: Vec<Range<T>>) -> IntervalSet<T> {
+        ranges.retain(|range| range.start < range.end);
+        ranges.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        IntervalSet { ranges: merged }
+    }
+
+    /// The sorted, disjoint ranges making up the set.
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of integers in the set.
+    pub fn len(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::ZERO, |acc, range| acc + (range.end - range.start))
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|range| range.end - T::ONE)
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.ranges.partition_point(|range| range.end <= x);
+        self.ranges.get(i).is_some_and(|range| range.start <= x)
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        *self = IntervalSet::normalized(ranges);
+    }
+
+    pub fn union(&self, other: &Self) -> IntervalSet<T> {
+        IntervalSet::normalized(
+            self.ranges
+                .iter()
+                .chain(other.ranges.iter())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    pub fn intersection(&self, other: &Self) -> IntervalSet<T> {
+        let mut out = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                out.push(start..end);
+            }
+            // Whichever range finishes first can't overlap anything else.
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges: out }
+    }
+
+    /// Everything in `self` that isn't in `other`.
+    pub fn difference(&self, other: &Self) -> IntervalSet<T> {
+        let mut out = vec![];
+        let mut j = 0;
+        for range in &self.ranges {
+            let mut start = range.start;
+            // Skip the ranges of `other` that finish before this one starts
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < range.end {
+                if start < other.ranges[k].start {
+                    out.push(start..other.ranges[k].start);
+                }
+                start = start.max(other.ranges[k].end);
+                k += 1;
+            }
+            if start < range.end {
+                out.push(start..range.end);
+            }
+        }
+        IntervalSet { ranges: out }
+    }
+}
+
+impl<T: Integer> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> IntervalSet<T> {
+        IntervalSet::normalized(vec![range])
+    }
+}
+
+impl<T: Integer> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> IntervalSet<T> {
+        IntervalSet::normalized(iter.into_iter().collect())
+    }
+}
+
+/// A function on the integers that translates each of a set of disjoint source ranges to a
+/// destination, and leaves everything else alone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PiecewiseMap<T> {
+    /// `(source, destination start)`, sorted by source.
+    segments: Vec<(Range<T>, T)>,
+}
+
+impl<T: Integer> Default for PiecewiseMap<T> {
+    fn default() -> Self {
+        PiecewiseMap::identity()
+    }
+}
+
+impl<T: Integer> PiecewiseMap<T> {
+    pub fn identity() -> PiecewiseMap<T> {
+        PiecewiseMap { segments: vec![] }
+    }
+
+    /// Map `source` onto the range of the same length starting at `destination`.
+    /// Panics if `source` overlaps a segment that was already added.
+    pub fn insert(&mut self, source: Range<T>, destination: T) {
+        if source.start >= source.end {
+            return;
+        }
+        let i = self
+            .segments
+            .partition_point(|(existing, _)| existing.start < source.start);
+        let overlaps_prev = i > 0 && self.segments[i - 1].0.end > source.start;
+        let overlaps_next = i < self.segments.len() && self.segments[i].0.start < source.end;
+        assert!(
+            !overlaps_prev && !overlaps_next,
+            "Segment {source:?} overlaps an existing one"
+        );
+        self.segments.insert(i, (source, destination));
+    }
+
+    /// The `(source, destination start)` segments, sorted by source.
+    pub fn segments(&self) -> &[(Range<T>, T)] {
+        &self.segments
+    }
+
+    pub fn apply(&self, x: T) -> T {
+        let i = self.segments.partition_point(|(source, _)| source.end <= x);
+        match self.segments.get(i) {
+            Some((source, destination)) if source.start <= x => *destination + (x - source.start),
+            _ => x,
+        }
+    }
+
+    /// Split `range` into the pieces that are translated by a single segment (along with that
+    /// piece's destination start) or that are left alone (`None`).
+    fn pieces(&self, range: Range<T>) -> Vec<(Range<T>, Option<T>)> {
+        let mut pieces = vec![];
+        let mut start = range.start;
+        let first = self
+            .segments
+            .partition_point(|(source, _)| source.end <= start);
+        for (source, destination) in &self.segments[first..] {
+            if source.start >= range.end {
+                break;
+            }
+            if start < source.start {
+                pieces.push((start..source.start, None));
+                start = source.start;
+            }
+            let end = source.end.min(range.end);
+            pieces.push((start..end, Some(*destination + (start - source.start))));
+            start = end;
+        }
+        if start < range.end {
+            pieces.push((start..range.end, None));
+        }
+        pieces
+    }
+
+    /// The image of every integer in `set`.
+    pub fn apply_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        set.ranges()
+            .iter()
+            .flat_map(|range| self.pieces(range.clone()))
+            .map(|(piece, destination)| match destination {
+                Some(destination) => destination..destination + (piece.end - piece.start),
+                None => piece,
+            })
+            .collect()
+    }
+
+    /// The map that applies `self` and then `next`.
+    pub fn then(&self, next: &PiecewiseMap<T>) -> PiecewiseMap<T> {
+        let mut segments = vec![];
+        // Where self translates things, follow the image through next.
+        for (source, destination) in &self.segments {
+            let image = *destination..*destination + (source.end - source.start);
+            for (piece, next_destination) in next.pieces(image) {
+                let start = source.start + (piece.start - *destination);
+                let end = start + (piece.end - piece.start);
+                segments.push((start..end, next_destination.unwrap_or(piece.start)));
+            }
+        }
+        // Everywhere else self is the identity, so only next does anything.
+        let domain: IntervalSet<T> = self
+            .segments
+            .iter()
+            .map(|(source, _)| source.clone())
+            .collect();
+        for (source, destination) in &next.segments {
+            for piece in IntervalSet::from(source.clone())
+                .difference(&domain)
+                .ranges()
+            {
+                segments.push((piece.clone(), *destination + (piece.start - source.start)));
+            }
+        }
+        segments.sort_by_key(|(source, _)| source.start);
+
+        // Tidy up by dropping segments that don't move anything, and merging neighbours that
+        // are translated by the same amount.
+        let mut merged: Vec<(Range<T>, T)> = vec![];
+        for (source, destination) in segments {
+            if source.start == destination {
+                continue;
+            }
+            match merged.last_mut() {
+                Some((last, last_destination))
+                    if last.end == source.start
+                        && *last_destination + (last.end - last.start) == destination =>
+                {
+                    last.end = source.end;
+                }
+                _ => merged.push((source, destination)),
+            }
+        }
+        PiecewiseMap { segments: merged }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(ranges: &[Range<i64>]) -> IntervalSet<i64> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_coalescing() {
+        let s = set(&[5..8, 0..2, 2..3, 7..10, 12..12]);
+        assert_eq!(s.ranges(), &[0..3, 5..10]);
+        assert_eq!(s.len(), 8);
+        assert_eq!((s.min(), s.max()), (Some(0), Some(9)));
+        assert!(s.contains(2) && s.contains(5) && s.contains(9));
+        assert!(!s.contains(3) && !s.contains(10) && !s.contains(-1));
+
+        let mut s = s;
+        s.insert(3..5);
+        assert_eq!(s, IntervalSet::from(0..10));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25, 28..40]);
+        assert_eq!(a.union(&b), IntervalSet::from(0..40));
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25, 28..30]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..28]);
+        assert_eq!(b.difference(&a).ranges(), &[10..20, 30..40]);
+        assert_eq!(a.difference(&a), IntervalSet::new());
+        assert_eq!(a.difference(&IntervalSet::new()), a);
+    }
+
+    #[test]
+    fn test_piecewise_map() {
+        let mut map = PiecewiseMap::identity();
+        map.insert(98..100, 50);
+        map.insert(50..98, 52);
+        assert_eq!(map.apply(79), 81);
+        assert_eq!(map.apply(99), 51);
+        assert_eq!(map.apply(10), 10);
+        assert_eq!(map.apply(100), 100);
+        assert_eq!(
+            map.apply_set(&set(&[45..55, 97..100])).ranges(),
+            &[45..57, 99..100]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_piecewise_map_overlap() {
+        let mut map = PiecewiseMap::identity();
+        map.insert(0..10, 100);
+        map.insert(5..6, 0);
+    }
+
+    #[test]
+    fn test_compose() {
+        let mut first = PiecewiseMap::identity();
+        first.insert(0..10, 100);
+        first.insert(20..30, 5);
+        let mut second = PiecewiseMap::identity();
+        second.insert(100..105, 0);
+        second.insert(0..8, 200);
+        let composed = first.then(&second);
+        for x in -5..150 {
+            assert_eq!(composed.apply(x), second.apply(first.apply(x)), "x = {x}");
+        }
+        let seeds = IntervalSet::from(0..50);
+        assert_eq!(
+            composed.apply_set(&seeds),
+            second.apply_set(&first.apply_set(&seeds))
+        );
+    }
+}
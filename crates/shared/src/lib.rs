@@ -1,5 +1,21 @@
+pub mod cycle;
+mod dir;
+pub mod dsu;
+pub mod exact_cover;
+pub mod geom;
+pub mod graph;
+mod grid;
+pub mod ilp;
+pub mod interval;
+pub mod linalg;
+pub mod math;
+pub mod num;
+pub mod polyomino;
 mod problem;
+pub mod search;
 mod year;
 
+pub use dir::{Dir4, Dir8};
+pub use grid::Grid;
 pub use problem::Problem;
 pub use year::Year;
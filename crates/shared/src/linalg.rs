@@ -0,0 +1,287 @@
+//! Exact linear algebra over the rationals.
+//!
+//! Everything is done with [`Rational`] entries, so there's no rounding error to worry about
+//! when checking whether a solution is integral. Use `i64` or `i128` when the entries are
+//! known to stay small, or [`crate::num::BigInt`] when they might not.
+
+use std::ops::{Index, IndexMut};
+
+use crate::num::{Int, Rational};
+
+/// A dense matrix of rationals, stored as a list of rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    data: Vec<Vec<Rational<T>>>,
+    num_rows: usize,
+    num_cols: usize,
+}
+
+/// The general solution to `Ax = b`, in terms of the free variables.
+///
+/// Every solution is `particular + sum(t_k * nullspace[k])`, where `t_k` is the value chosen
+/// for the free variable `free[k]`. Each nullspace vector has a one at its own free variable
+/// and a zero at all the others, so `t_k` really is the value of `x[free[k]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<T> {
+    pub particular: Vec<Rational<T>>,
+    pub free: Vec<usize>,
+    pub nullspace: Vec<Vec<Rational<T>>>,
+}
+
+impl<T: Int> Solution<T> {
+    /// Whether there is exactly one solution, i.e. there are no free variables.
+    pub fn is_unique(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// The solution with the free variables set to `free_values`, in the order of `free`.
+    pub fn evaluate(&self, free_values: &[Rational<T>]) -> Vec<Rational<T>> {
+        assert_eq!(
+            free_values.len(),
+            self.free.len(),
+            "Expected a value for every free variable"
+        );
+        let mut x = self.particular.clone();
+        for (t, basis) in free_values.iter().zip(self.nullspace.iter()) {
+            for (x_i, b_i) in x.iter_mut().zip(basis.iter()) {
+                *x_i += t.clone() * b_i.clone();
+            }
+        }
+        x
+    }
+}
+
+impl<T: Int> Matrix<T> {
+    /// Panics if the rows aren't all the same length.
+    pub fn new(data: Vec<Vec<Rational<T>>>) -> Matrix<T> {
+        let num_rows = data.len();
+        let num_cols = data.first().map_or(0, |row| row.len());
+        assert!(
+            data.iter().all(|row| row.len() == num_cols),
+            "All rows must have the same length"
+        );
+        Matrix {
+            data,
+            num_rows,
+            num_cols,
+        }
+    }
+
+    pub fn from_ints(data: Vec<Vec<T>>) -> Matrix<T> {
+        Matrix::new(
+            data.into_iter()
+                .map(|row| row.into_iter().map(Rational::whole_number).collect())
+                .collect(),
+        )
+    }
+
+    pub fn zeros(num_rows: usize, num_cols: usize) -> Matrix<T> {
+        Matrix::new(vec![vec![Rational::zero(); num_cols]; num_rows])
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn row(&self, i: usize) -> &[Rational<T>] {
+        &self.data[i]
+    }
+
+    /// Put the matrix into reduced row echelon form with Gauss-Jordan elimination, returning
+    /// the pivot column of each non-zero row (in order). Columns without a pivot correspond to
+    /// free variables.
+    /// https://en.wikipedia.org/wiki/Row_echelon_form#Reduced_row_echelon_form
+    pub fn reduced_row_echelon_form(&mut self) -> Vec<usize> {
+        let mut pivots = vec![];
+        for col in 0..self.num_cols {
+            let r = pivots.len();
+            if r == self.num_rows {
+                break;
+            }
+            let Some(pivot_row) = (r..self.num_rows).find(|&i| !self.data[i][col].is_zero()) else {
+                continue;
+            };
+            self.data.swap(pivot_row, r);
+            let lead = self.data[r][col].clone();
+            for val in self.data[r].iter_mut() {
+                *val /= lead.clone();
+            }
+            let reduced = self.data[r].clone();
+            for (i, row) in self.data.iter_mut().enumerate() {
+                if i != r && !row[col].is_zero() {
+                    let factor = row[col].clone();
+                    for (val, pivot_val) in row.iter_mut().zip(reduced.iter()) {
+                        *val -= factor.clone() * pivot_val.clone();
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().reduced_row_echelon_form().len()
+    }
+
+    /// A basis for the solutions to `Ax = 0`, with one vector per free variable.
+    pub fn nullspace(&self) -> Vec<Vec<Rational<T>>> {
+        let mut reduced = self.clone();
+        let pivots = reduced.reduced_row_echelon_form();
+        reduced.nullspace_from_rref(&pivots, self.num_cols).1
+    }
+
+    /// Solve `Ax = b`, returning `None` if there is no solution.
+    pub fn solve(&self, b: &[Rational<T>]) -> Option<Solution<T>> {
+        assert_eq!(b.len(), self.num_rows, "Expected one value per row");
+        let mut augmented = Matrix::new(
+            self.data
+                .iter()
+                .zip(b.iter())
+                .map(|(row, b_i)| {
+                    let mut row = row.clone();
+                    row.push(b_i.clone());
+                    row
+                })
+                .collect(),
+        );
+        // An empty system has no rows to take the width from.
+        augmented.num_cols = self.num_cols + 1;
+        let pivots = augmented.reduced_row_echelon_form();
+        if pivots.last() == Some(&self.num_cols) {
+            // A row that reads 0 = 1
+            return None;
+        }
+        let mut particular = vec![Rational::zero(); self.num_cols];
+        for (row, &col) in augmented.data.iter().zip(pivots.iter()) {
+            particular[col] = row[self.num_cols].clone();
+        }
+        let (free, nullspace) = augmented.nullspace_from_rref(&pivots, self.num_cols);
+        Some(Solution {
+            particular,
+            free,
+            nullspace,
+        })
+    }
+
+    /// Read off the free variables and nullspace basis from a matrix in reduced row echelon
+    /// form, only looking at the first `num_vars` columns.
+    fn nullspace_from_rref(
+        &self,
+        pivots: &[usize],
+        num_vars: usize,
+    ) -> (Vec<usize>, Vec<Vec<Rational<T>>>) {
+        let free: Vec<usize> = (0..num_vars).filter(|col| !pivots.contains(col)).collect();
+        let nullspace = free
+            .iter()
+            .map(|&free_col| {
+                let mut basis = vec![Rational::zero(); num_vars];
+                basis[free_col] = Rational::one();
+                for (row, &col) in self.data.iter().zip(pivots.iter()) {
+                    basis[col] = -row[free_col].clone();
+                }
+                basis
+            })
+            .collect();
+        (free, nullspace)
+    }
+
+    /// The matrix-vector product `Ax`.
+    pub fn mul_vec(&self, x: &[Rational<T>]) -> Vec<Rational<T>> {
+        assert_eq!(x.len(), self.num_cols, "Expected one value per column");
+        self.data
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(x.iter())
+                    .fold(Rational::zero(), |acc, (a, b)| acc + a.clone() * b.clone())
+            })
+            .collect()
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = Rational<T>;
+
+    fn index(&self, (i, j): (usize, usize)) -> &Rational<T> {
+        &self.data[i][j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Rational<T> {
+        &mut self.data[i][j]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Fraction = Rational<i64>;
+
+    fn whole(values: &[i64]) -> Vec<Fraction> {
+        values.iter().copied().map(Fraction::whole_number).collect()
+    }
+
+    #[test]
+    fn test_rref_and_rank() {
+        let mut matrix = Matrix::from_ints(vec![vec![1, 2, 3], vec![2, 4, 6], vec![1, 0, 1]]);
+        assert_eq!(matrix.rank(), 2);
+        let pivots = matrix.reduced_row_echelon_form();
+        assert_eq!(pivots, vec![0, 1]);
+        assert_eq!(
+            matrix,
+            Matrix::from_ints(vec![vec![1, 0, 1], vec![0, 1, 1], vec![0, 0, 0]])
+        );
+        assert_eq!(Matrix::<i64>::zeros(2, 3).rank(), 0);
+    }
+
+    #[test]
+    fn test_nullspace() {
+        let matrix = Matrix::from_ints(vec![vec![1, 2, 3, 4], vec![2, 4, 7, 9]]);
+        let nullspace = matrix.nullspace();
+        assert_eq!(nullspace.len(), 2);
+        for basis in nullspace {
+            assert_eq!(matrix.mul_vec(&basis), whole(&[0, 0]));
+        }
+    }
+
+    #[test]
+    fn test_solve_unique() {
+        // 2x + y = 5, x - y = 1
+        let matrix = Matrix::from_ints(vec![vec![2, 1], vec![1, -1]]);
+        let solution = matrix.solve(&whole(&[5, 1])).unwrap();
+        assert!(solution.is_unique());
+        assert_eq!(solution.particular, whole(&[2, 1]));
+
+        // Non-integral solutions are kept exact: 2x = 1
+        let matrix = Matrix::from_ints(vec![vec![2]]);
+        let solution = matrix.solve(&whole(&[1])).unwrap();
+        assert_eq!(solution.particular, vec![Fraction::new(1, 2).unwrap()]);
+    }
+
+    #[test]
+    fn test_solve_parametric() {
+        // x + y + z = 6, y - z = 1
+        let matrix = Matrix::from_ints(vec![vec![1, 1, 1], vec![0, 1, -1]]);
+        let b = whole(&[6, 1]);
+        let solution = matrix.solve(&b).unwrap();
+        assert_eq!(solution.free, vec![2]);
+        for t in -3..3 {
+            let x = solution.evaluate(&whole(&[t]));
+            assert_eq!(x[2], Fraction::whole_number(t));
+            assert_eq!(matrix.mul_vec(&x), b);
+        }
+    }
+
+    #[test]
+    fn test_solve_inconsistent() {
+        let matrix = Matrix::from_ints(vec![vec![1, 1], vec![2, 2]]);
+        assert_eq!(matrix.solve(&whole(&[1, 3])), None);
+    }
+}
@@ -0,0 +1,278 @@
+//! Number theory helpers: gcd/lcm, modular arithmetic and the Chinese Remainder Theorem.
+
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+/// The primitive integer types, so that helpers don't need to be written once per type.
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Display
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// The absolute value, which is the identity for unsigned types.
+    fn abs_value(self) -> Self;
+}
+
+macro_rules! impl_integer_unsigned {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn abs_value(self) -> Self {
+                self
+            }
+        })*
+    };
+}
+
+macro_rules! impl_integer_signed {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn abs_value(self) -> Self {
+                self.abs()
+            }
+        })*
+    };
+}
+
+impl_integer_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_integer_signed!(i8, i16, i32, i64, i128, isize);
+
+/// Implement Euclid's algorithm, the result is never negative.
+/// https://en.wikipedia.org/wiki/Euclidean_algorithm
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a.abs_value(), b.abs_value());
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, the result is never negative. `lcm(0, x) == 0`.
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO {
+        T::ZERO
+    } else {
+        (a / gcd(a, b) * b).abs_value()
+    }
+}
+
+/// The extended Euclidean algorithm, returning `(g, x, y)` such that `a * x + b * y == g`
+/// where `g` is the (non-negative) gcd of `a` and `b`.
+/// https://en.wikipedia.org/wiki/Extended_Euclidean_algorithm
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of `a` modulo `m`, in `0..m`, if it exists (i.e. if `a` and `m` are coprime).
+pub fn mod_inv(a: i128, m: i128) -> Option<i128> {
+    assert!(m > 0, "Modulus must be positive, got {m}");
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// `base.pow(exp) % m`, without overflowing along the way.
+pub fn mod_pow(base: u64, mut exp: u64, m: u64) -> u64 {
+    assert!(m > 0, "Modulus must be positive");
+    let m = m as u128;
+    let mut base = base as u128 % m;
+    let mut out = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            out = out * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    out as u64
+}
+
+/// Solve a system of congruences `x ≡ residue (mod modulus)` with the Chinese Remainder Theorem.
+///
+/// The moduli do not need to be pairwise coprime, and residues can be any integer (e.g. an
+/// offset larger than the modulus). Returns `Some((x, m))` where every solution is `x + k * m`
+/// and `0 <= x < m`, or `None` if the congruences are inconsistent.
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    let mut x: i128 = 0;
+    let mut m: i128 = 1;
+    for &(residue, modulus) in congruences {
+        assert!(modulus > 0, "Modulus must be positive, got {modulus}");
+        let residue = residue.rem_euclid(modulus);
+        let g = gcd(m, modulus);
+        let diff = residue - x;
+        if diff % g != 0 {
+            return None;
+        }
+        // Solve x + m * k ≡ residue (mod modulus) for k.
+        let reduced_modulus = modulus / g;
+        let k = if reduced_modulus == 1 {
+            0
+        } else {
+            (diff / g).rem_euclid(reduced_modulus) * mod_inv(m / g, reduced_modulus).unwrap()
+                % reduced_modulus
+        };
+        x += m * k;
+        m *= reduced_modulus;
+        x = x.rem_euclid(m);
+    }
+    Some((x, m))
+}
+
+/// The smallest solution to the congruences that is at least `lower_bound`, see [`crt`].
+///
+/// Useful for cycles that only start repeating after some warmup period, where a residue is
+/// only valid once the warmup is over.
+pub fn crt_at_least(congruences: &[(i128, i128)], lower_bound: i128) -> Option<i128> {
+    let (x, m) = crt(congruences)?;
+    if x >= lower_bound {
+        Some(x)
+    } else {
+        Some(x + (lower_bound - x + m - 1) / m * m)
+    }
+}
+
+/// For every way to pick one congruence from each list in `choices`, the smallest solution
+/// that is at least `lower_bound` (see [`crt_at_least`]), sorted and without repeats.
+///
+/// The number of combinations grows quickly, so if there are more than `max_combinations`
+/// this gives up and returns how many there are.
+pub fn crt_choices_at_least(
+    choices: &[Vec<(i128, i128)>],
+    lower_bound: i128,
+    max_combinations: usize,
+) -> Result<Vec<i128>, usize> {
+    let num_combinations = choices
+        .iter()
+        .try_fold(1_usize, |n, congruences| n.checked_mul(congruences.len()))
+        .unwrap_or(usize::MAX);
+    if num_combinations > max_combinations {
+        return Err(num_combinations);
+    }
+    // Add one list at a time, so that inconsistent choices are dropped as early as possible
+    let mut partial: Vec<(i128, i128)> = vec![(0, 1)];
+    for congruences in choices {
+        partial = partial
+            .iter()
+            .flat_map(|&solution| {
+                congruences
+                    .iter()
+                    .filter_map(move |&congruence| crt(&[solution, congruence]))
+            })
+            .collect();
+    }
+    let mut solutions: Vec<i128> = partial
+        .iter()
+        .filter_map(|&solution| crt_at_least(&[solution], lower_bound))
+        .collect();
+    solutions.sort();
+    solutions.dedup();
+    Ok(solutions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12_u64, 18), 6);
+        assert_eq!(gcd(-12_i64, 18), 6);
+        assert_eq!(gcd(0_u8, 0), 0);
+        assert_eq!(gcd(0_i32, -5), 5);
+        assert_eq!(gcd(18_u64, 12), 6);
+        assert_eq!(gcd(12_u64, 12), 12);
+        assert_eq!(gcd(12_u64, 1), 1);
+        assert_eq!(gcd(1_u64, 0), 1);
+        assert_eq!(lcm(4_usize, 6), 12);
+        assert_eq!(lcm(-4_i64, 6), 12);
+        assert_eq!(lcm(0_u32, 6), 0);
+        assert_eq!(lcm(18_u64, 12), 36);
+        assert_eq!(lcm(12_u64, 12), 12);
+        assert_eq!(lcm(12_u64, 1), 12);
+        assert_eq!(lcm(0_u64, 0), 0);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (-240, 46), (17, 5), (0, 7), (7, 0)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_inv(3, 11), Some(4));
+        assert_eq!(mod_inv(-3, 11), Some(7));
+        assert_eq!(mod_inv(2, 4), None);
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(3, 0, 1), 0);
+        assert_eq!(mod_pow(u64::MAX, u64::MAX, 1_000_000_007), 254_368_884);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Non-coprime moduli
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        // Residues larger than the modulus, or negative
+        assert_eq!(crt(&[(10, 4), (-2, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt_at_least(&[(2, 4), (4, 6)], 11), Some(22));
+        assert_eq!(crt_at_least(&[(2, 4), (4, 6)], 10), Some(10));
+    }
+
+    #[test]
+    fn test_crt_choices() {
+        let choices = vec![
+            vec![(0, 2), (1, 2)],
+            vec![(1, 3), (2, 3)],
+            vec![(1, 4), (2, 4)],
+        ];
+        // x ≡ 0 (mod 2) and x ≡ 1 (mod 4) can't both hold, and the same for 1 and 2
+        assert_eq!(crt_choices_at_least(&choices, 0, 8), Ok(vec![1, 2, 5, 10]));
+        assert_eq!(
+            crt_choices_at_least(&choices, 6, 8),
+            Ok(vec![10, 13, 14, 17])
+        );
+        assert_eq!(crt_choices_at_least(&choices, 0, 7), Err(8));
+        assert_eq!(
+            crt_choices_at_least(&[vec![(1, 2)], vec![]], 0, 8),
+            Ok(vec![])
+        );
+        assert_eq!(crt_choices_at_least(&[], 3, 1), Ok(vec![3]));
+        assert_eq!(
+            crt_choices_at_least(&vec![vec![(0, 2); 64]; 64], 0, usize::MAX - 1),
+            Err(usize::MAX)
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Exact number types.
+
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+mod bigint;
+mod rational;
+
+pub use bigint::BigInt;
+pub use rational::Rational;
+
+/// Signed integers that can be used inside a [`Rational`].
+///
+/// Every operation is checked, returning `None` instead of overflowing (or dividing by zero).
+pub trait Int: Clone + Ord + Debug + Display + FromStr {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    /// Division rounding towards zero.
+    fn checked_div(&self, other: &Self) -> Option<Self>;
+    /// Remainder with the same sign as `self`, matching [`Int::checked_div`].
+    fn checked_rem(&self, other: &Self) -> Option<Self>;
+    fn checked_neg(&self) -> Option<Self>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    fn is_negative(&self) -> bool {
+        *self < Self::zero()
+    }
+
+    fn checked_abs(&self) -> Option<Self> {
+        if self.is_negative() {
+            self.checked_neg()
+        } else {
+            Some(self.clone())
+        }
+    }
+
+    /// The non-negative greatest common divisor, see [`crate::math::gcd`].
+    fn checked_gcd(&self, other: &Self) -> Option<Self> {
+        let (mut a, mut b) = (self.checked_abs()?, other.checked_abs()?);
+        while !b.is_zero() {
+            let r = a.checked_rem(&b)?;
+            (a, b) = (b, r);
+        }
+        Some(a)
+    }
+}
+
+macro_rules! impl_int {
+    ($($t:ty),*) => {
+        $(impl Int for $t {
+            fn zero() -> Self {
+                0
+            }
+            fn one() -> Self {
+                1
+            }
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+            fn checked_sub(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *other)
+            }
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *other)
+            }
+            fn checked_div(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_div(*self, *other)
+            }
+            fn checked_rem(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_rem(*self, *other)
+            }
+            fn checked_neg(&self) -> Option<Self> {
+                <$t>::checked_neg(*self)
+            }
+        })*
+    };
+}
+
+impl_int!(i32, i64, i128, isize);
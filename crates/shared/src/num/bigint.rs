@@ -0,0 +1,506 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+use super::Int;
+
+/// An arbitrary precision signed integer.
+///
+/// Only the operations that have been needed so far are implemented, and they use the
+/// simple schoolbook algorithms, which is plenty fast for numbers with a few hundred digits.
+//
+// The magnitude is stored as little endian base 2^32 limbs without any trailing zeros, and
+// zero is never negative, so deriving PartialEq, Eq and Hash are valid.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    mag: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    fn from_parts(negative: bool, mut mag: Vec<u32>) -> BigInt {
+        while mag.last() == Some(&0) {
+            mag.pop();
+        }
+        BigInt {
+            negative: negative && !mag.is_empty(),
+            mag,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mag.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.mag.clone())
+    }
+
+    /// Quotient and remainder, rounding towards zero like the primitive integer types do, so the
+    /// remainder has the same sign as `self`. Returns `None` when dividing by zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (q, r) = div_rem_mag(&self.mag, &other.mag);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, q),
+            BigInt::from_parts(self.negative, r),
+        ))
+    }
+
+    /// The non-negative greatest common divisor.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        self.checked_gcd(other).unwrap()
+    }
+
+    /// The value as an `i128`, if it fits.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.mag.len() > 4 {
+            return None;
+        }
+        let mag = self
+            .mag
+            .iter()
+            .rev()
+            .fold(0_u128, |acc, &limb| (acc << 32) | limb as u128);
+        if self.negative {
+            0_i128.checked_sub_unsigned(mag)
+        } else {
+            i128::try_from(mag).ok()
+        }
+    }
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut out = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0_u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let sum = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        out.push(sum as u32);
+        carry = sum >> 32;
+    }
+    out.push(carry as u32);
+    out
+}
+
+/// Assumes `a >= b`.
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0_i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut diff = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        }
+        out.push(diff as u32);
+    }
+    assert_eq!(
+        borrow, 0,
+        "Subtracted a larger magnitude from a smaller one"
+    );
+    out
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = vec![0_u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0_u64;
+        for (j, &y) in b.iter().enumerate() {
+            let curr = out[i + j] as u64 + x as u64 * y as u64 + carry;
+            out[i + j] = curr as u32;
+            carry = curr >> 32;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+    out
+}
+
+fn div_rem_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut q = vec![0_u32; a.len()];
+    let mut r = 0_u64;
+    for (i, &limb) in a.iter().enumerate().rev() {
+        let curr = (r << 32) | limb as u64;
+        q[i] = (curr / d as u64) as u32;
+        r = curr % d as u64;
+    }
+    (q, r as u32)
+}
+
+/// Binary long division, one bit at a time.
+fn div_rem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if b.len() == 1 {
+        let (q, r) = div_rem_small(a, b[0]);
+        return (q, vec![r]);
+    }
+    let mut q = vec![0_u32; a.len()];
+    let mut r: Vec<u32> = vec![];
+    for bit in (0..a.len() * 32).rev() {
+        // r = (r << 1) | next bit of a
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for limb in r.iter_mut() {
+            let next_carry = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next_carry;
+        }
+        if carry != 0 {
+            r.push(carry);
+        }
+        if cmp_mag(&r, b) != Ordering::Less {
+            r = sub_mag(&r, b);
+            while r.last() == Some(&0) {
+                r.pop();
+            }
+            q[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (q, r)
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.mag, &other.mag),
+            (true, true) => cmp_mag(&other.mag, &self.mag),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.mag.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.mag)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_mag(&self.mag, &other.mag));
+        }
+        match cmp_mag(&self.mag, &other.mag) {
+            Ordering::Equal => BigInt::zero(),
+            Ordering::Greater => BigInt::from_parts(self.negative, sub_mag(&self.mag, &other.mag)),
+            Ordering::Less => BigInt::from_parts(other.negative, sub_mag(&other.mag, &self.mag)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &(-other)
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_mag(&self.mag, &other.mag),
+        )
+    }
+}
+
+impl Div for &BigInt {
+    type Output = BigInt;
+
+    /// Rounds towards zero. Panics when dividing by zero.
+    fn div(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).expect("Division by zero").0
+    }
+}
+
+impl Rem for &BigInt {
+    type Output = BigInt;
+
+    /// Has the same sign as `self`. Panics when dividing by zero.
+    fn rem(self, other: &BigInt) -> BigInt {
+        self.div_rem(other).expect("Division by zero").1
+    }
+}
+
+// Forward the owned versions of the operators to the borrowed ones.
+macro_rules! forward_op {
+    ($($op:ident, $method:ident);*) => {
+        $(
+            impl $op for BigInt {
+                type Output = BigInt;
+
+                fn $method(self, other: BigInt) -> BigInt {
+                    (&self).$method(&other)
+                }
+            }
+
+            impl $op<&BigInt> for BigInt {
+                type Output = BigInt;
+
+                fn $method(self, other: &BigInt) -> BigInt {
+                    (&self).$method(other)
+                }
+            }
+        )*
+    };
+}
+
+forward_op!(Add, add; Sub, sub; Mul, mul; Div, div; Rem, rem);
+
+impl AddAssign<&BigInt> for BigInt {
+    fn add_assign(&mut self, other: &BigInt) {
+        *self = &*self + other;
+    }
+}
+
+impl SubAssign<&BigInt> for BigInt {
+    fn sub_assign(&mut self, other: &BigInt) {
+        *self = &*self - other;
+    }
+}
+
+impl MulAssign<&BigInt> for BigInt {
+    fn mul_assign(&mut self, other: &BigInt) {
+        *self = &*self * other;
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(n: u128) -> BigInt {
+        BigInt::from_parts(false, (0..4).map(|i| (n >> (32 * i)) as u32).collect())
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(n: i128) -> BigInt {
+        let out = BigInt::from(n.unsigned_abs());
+        if n < 0 { -out } else { out }
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($via:ty; $($t:ty),*) => {
+        $(impl From<$t> for BigInt {
+            fn from(n: $t) -> BigInt {
+                BigInt::from(n as $via)
+            }
+        })*
+    };
+}
+
+impl_from_primitive!(u128; u8, u16, u32, u64, usize);
+impl_from_primitive!(i128; i8, i16, i32, i64, isize);
+
+impl Int for BigInt {
+    fn zero() -> Self {
+        BigInt::zero()
+    }
+    fn one() -> Self {
+        BigInt::from(1)
+    }
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+    fn checked_div(&self, other: &Self) -> Option<Self> {
+        Some(self.div_rem(other)?.0)
+    }
+    fn checked_rem(&self, other: &Self) -> Option<Self> {
+        Some(self.div_rem(other)?.1)
+    }
+    fn checked_neg(&self) -> Option<Self> {
+        Some(-self)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    /// Parses a base 10 integer with an optional leading sign.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Unable to parse {s:?} as an integer"));
+        }
+        let mut mag: Vec<u32> = vec![];
+        // Process nine digits at a time, since 10^9 fits in a limb.
+        let first_chunk_len = match digits.len() % 9 {
+            0 => 9,
+            n => n,
+        };
+        let mut start = 0;
+        let mut end = first_chunk_len.min(digits.len());
+        while start < digits.len() {
+            let chunk: u32 = digits[start..end].parse().unwrap();
+            let scale = 10_u32.pow((end - start) as u32);
+            let mut carry = chunk as u64;
+            for limb in mag.iter_mut() {
+                let curr = *limb as u64 * scale as u64 + carry;
+                *limb = curr as u32;
+                carry = curr >> 32;
+            }
+            if carry != 0 {
+                mag.push(carry as u32);
+            }
+            start = end;
+            end += 9;
+        }
+        Ok(BigInt::from_parts(negative, mag))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chunks = vec![];
+        let mut mag = self.mag.clone();
+        while !mag.is_empty() {
+            let (q, r) = div_rem_small(&mag, 1_000_000_000);
+            chunks.push(r);
+            mag = q;
+            while mag.last() == Some(&0) {
+                mag.pop();
+            }
+        }
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.into_iter().rev() {
+            digits.push_str(&format!("{chunk:0>9}"));
+        }
+        f.pad_integral(!self.negative, "", &digits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::num::Rational;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_display() {
+        for s in [
+            "0",
+            "1",
+            "-1",
+            "4294967296",
+            "1000000000",
+            "-123456789012345678901234567890123456789012345",
+        ] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert_eq!(big("-0"), BigInt::zero());
+        assert_eq!(big("+007").to_string(), "7");
+        assert!("".parse::<BigInt>().is_err());
+        assert!("12a".parse::<BigInt>().is_err());
+        assert_eq!(format!("{:>5}", BigInt::from(-12)), "  -12");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+        assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "-121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!(&a - &a, BigInt::zero());
+        assert_eq!(&(&a * &b) / &b, a);
+    }
+
+    #[test]
+    fn test_div_rem() {
+        // Matches the primitive integers, which round towards zero
+        for (x, y) in [
+            (7_i128, 2_i128),
+            (-7, 2),
+            (7, -2),
+            (-7, -2),
+            (1 << 100, 3),
+            (5, 1 << 70),
+        ] {
+            let (q, r) = BigInt::from(x).div_rem(&BigInt::from(y)).unwrap();
+            assert_eq!(q, BigInt::from(x / y));
+            assert_eq!(r, BigInt::from(x % y));
+        }
+        let a = big("-121932631137021795226185032733622923332237463801111263526901");
+        let b = big("987654321098765432109876543210");
+        let (q, r) = a.div_rem(&b).unwrap();
+        assert_eq!(q.to_string(), "-123456789012345678901234567890");
+        assert_eq!(r.to_string(), "-1");
+        assert!(a.div_rem(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn test_ord_and_conversions() {
+        let mut values: Vec<BigInt> = [5_i128, -3, 0, i128::MIN, i128::MAX, -4]
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        values.sort();
+        let sorted: Vec<i128> = values.iter().map(|v| v.to_i128().unwrap()).collect();
+        assert_eq!(sorted, vec![i128::MIN, -4, -3, 0, 5, i128::MAX]);
+        assert_eq!((BigInt::from(i128::MAX) + BigInt::from(1)).to_i128(), None);
+    }
+
+    #[test]
+    fn test_gcd_and_rational() {
+        assert_eq!(big("-12").gcd(&big("18")), big("6"));
+        let third = Rational::new(BigInt::from(1), BigInt::from(3)).unwrap();
+        let huge = Rational::whole_number(big("100000000000000000000000000000000000000000"));
+        let value = (huge + third) * Rational::whole_number(BigInt::from(3));
+        assert_eq!(
+            value.to_string(),
+            "300000000000000000000000000000000000000001"
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use super::Int;
+
+/// An exact fraction.
+///
+/// The operators panic on overflow (even in release mode) and on division by zero, use the
+/// `checked_*` methods to handle those cases instead.
+//
+// Note: since we're going to be guaranteeing a specific format
+// (reduced form, positive denominator, negative numerator if needed), then deriving
+// PartialEq, Eq and Hash are valid!
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational<T> {
+    num: T,
+    den: T,
+}
+
+impl<T: Int> Rational<T> {
+    /// Returns `None` if `den` is zero, or if the reduced fraction can't be represented.
+    pub fn new(num: T, den: T) -> Option<Rational<T>> {
+        if den.is_zero() {
+            return None;
+        }
+        let gcd = num.checked_gcd(&den)?;
+        let (mut num, mut den) = (num.checked_div(&gcd)?, den.checked_div(&gcd)?);
+        if den.is_negative() {
+            num = num.checked_neg()?;
+            den = den.checked_neg()?;
+        }
+        Some(Rational { num, den })
+    }
+
+    pub fn whole_number(num: T) -> Rational<T> {
+        Rational { num, den: T::one() }
+    }
+
+    pub fn zero() -> Rational<T> {
+        Rational::whole_number(T::zero())
+    }
+
+    pub fn one() -> Rational<T> {
+        Rational::whole_number(T::one())
+    }
+
+    pub fn numerator(&self) -> &T {
+        &self.num
+    }
+
+    /// Always positive.
+    pub fn denominator(&self) -> &T {
+        &self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.num.is_negative()
+    }
+
+    /// The value as an integer, if it is one.
+    pub fn integral(&self) -> Option<T> {
+        if self.den == T::one() {
+            Some(self.num.clone())
+        } else {
+            None
+        }
+    }
+
+    /// The largest integer less than or equal to the value.
+    pub fn floor(&self) -> T {
+        let quotient = self.num.checked_div(&self.den).unwrap();
+        if self.num.checked_rem(&self.den).unwrap().is_negative() {
+            quotient
+                .checked_sub(&T::one())
+                .expect("Overflow in Rational::floor")
+        } else {
+            quotient
+        }
+    }
+
+    /// The smallest integer greater than or equal to the value.
+    pub fn ceil(&self) -> T {
+        let quotient = self.num.checked_div(&self.den).unwrap();
+        if self.num.checked_rem(&self.den).unwrap() > T::zero() {
+            quotient
+                .checked_add(&T::one())
+                .expect("Overflow in Rational::ceil")
+        } else {
+            quotient
+        }
+    }
+
+    pub fn checked_neg(&self) -> Option<Rational<T>> {
+        Some(Rational {
+            num: self.num.checked_neg()?,
+            den: self.den.clone(),
+        })
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Rational<T>> {
+        // a/b + c/d = (a(d/g) + c(b/g)) / (b/g * d), where g = gcd(b, d), which keeps the
+        // intermediate values as small as possible.
+        let g = self.den.checked_gcd(&rhs.den)?;
+        let self_scale = rhs.den.checked_div(&g)?;
+        let rhs_scale = self.den.checked_div(&g)?;
+        let num = self
+            .num
+            .checked_mul(&self_scale)?
+            .checked_add(&rhs.num.checked_mul(&rhs_scale)?)?;
+        Rational::new(num, rhs_scale.checked_mul(&rhs.den)?)
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Rational<T>> {
+        self.checked_add(&rhs.checked_neg()?)
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Rational<T>> {
+        // Cancel common factors before multiplying, to avoid overflowing unnecessarily.
+        let g1 = self.num.checked_gcd(&rhs.den)?;
+        let g2 = rhs.num.checked_gcd(&self.den)?;
+        if g1.is_zero() || g2.is_zero() {
+            // One of the numerators is zero
+            return Some(Rational::zero());
+        }
+        let num = self
+            .num
+            .checked_div(&g1)?
+            .checked_mul(&rhs.num.checked_div(&g2)?)?;
+        let den = self
+            .den
+            .checked_div(&g2)?
+            .checked_mul(&rhs.den.checked_div(&g1)?)?;
+        Rational::new(num, den)
+    }
+
+    /// Returns `None` on overflow or division by zero.
+    pub fn checked_div(&self, rhs: &Self) -> Option<Rational<T>> {
+        let reciprocal = Rational::new(rhs.den.clone(), rhs.num.clone())?;
+        self.checked_mul(&reciprocal)
+    }
+}
+
+impl<T: Int> From<T> for Rational<T> {
+    fn from(num: T) -> Rational<T> {
+        Rational::whole_number(num)
+    }
+}
+
+impl<T: Int> Ord for Rational<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Cross-multiplying can overflow, so compare the integer parts first and then
+        // compare the fractional parts via their reciprocals, i.e. continued fractions.
+        let (self_floor, other_floor) = (self.floor(), other.floor());
+        if self_floor != other_floor {
+            return self_floor.cmp(&other_floor);
+        }
+        let self_rem = self.checked_sub(&self_floor.into()).unwrap();
+        let other_rem = other.checked_sub(&other_floor.into()).unwrap();
+        match (self_rem.is_zero(), other_rem.is_zero()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => {
+                let self_reciprocal = Rational::new(self_rem.den, self_rem.num).unwrap();
+                let other_reciprocal = Rational::new(other_rem.den, other_rem.num).unwrap();
+                other_reciprocal.cmp(&self_reciprocal)
+            }
+        }
+    }
+}
+
+impl<T: Int> PartialOrd for Rational<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Int> Neg for Rational<T> {
+    type Output = Rational<T>;
+
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("Overflow negating rational")
+    }
+}
+
+macro_rules! impl_op {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident, $checked:ident) => {
+        impl<T: Int> $op for Rational<T> {
+            type Output = Rational<T>;
+
+            fn $method(self, rhs: Self) -> Self::Output {
+                self.$checked(&rhs).expect(concat!(
+                    "Overflow or division by zero in Rational::",
+                    stringify!($method)
+                ))
+            }
+        }
+
+        impl<T: Int> $op_assign for Rational<T> {
+            fn $method_assign(&mut self, rhs: Self) {
+                *self = self.clone().$method(rhs);
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, AddAssign, add_assign, checked_add);
+impl_op!(Sub, sub, SubAssign, sub_assign, checked_sub);
+impl_op!(Mul, mul, MulAssign, mul_assign, checked_mul);
+impl_op!(Div, div, DivAssign, div_assign, checked_div);
+
+impl<T: Int> fmt::Display for Rational<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == T::one() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl<T: Int> FromStr for Rational<T> {
+    type Err = String;
+
+    /// Parses `"a/b"` or a plain integer `"a"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| {
+            part.trim()
+                .parse::<T>()
+                .map_err(|_| format!("Unable to parse {part:?}"))
+        };
+        match s.split_once('/') {
+            Some((num, den)) => Rational::new(parse(num)?, parse(den)?)
+                .ok_or_else(|| format!("Invalid fraction {s:?}")),
+            None => Ok(Rational::whole_number(parse(s)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Fraction = Rational<i64>;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(1_i64.checked_gcd(&1), Some(1));
+        assert_eq!(2_i64.checked_gcd(&2), Some(2));
+        assert_eq!(4_i64.checked_gcd(&2), Some(2));
+        assert_eq!((-4_i64).checked_gcd(&6), Some(2));
+    }
+
+    #[test]
+    fn test_new() {
+        let f = Fraction::new(0, 1).unwrap();
+        assert_eq!(f.num, 0);
+        assert_eq!(f.den, 1);
+
+        let f = Fraction::new(2, 4).unwrap();
+        assert_eq!(f.num, 1);
+        assert_eq!(f.den, 2);
+
+        let f = Fraction::new(-2, -4).unwrap();
+        assert_eq!(f.num, 1);
+        assert_eq!(f.den, 2);
+
+        assert_eq!(Fraction::new(2, -4), Fraction::new(-1, 2));
+        assert_eq!(Fraction::new(100, 200), Fraction::new(1, 2));
+        assert_eq!(Fraction::new(-40, 4), Fraction::new(-10, 1));
+
+        assert_eq!(Fraction::new(1, 0), None);
+        assert_eq!(Fraction::new(1, i64::MIN), None);
+    }
+
+    #[test]
+    fn test_is_zero() {
+        assert!(Fraction::new(0, 1).unwrap().is_zero());
+        assert!(!Fraction::new(-1, 1).unwrap().is_zero());
+        assert!(!Fraction::new(1, 1).unwrap().is_zero());
+    }
+
+    #[test]
+    fn test_is_negative() {
+        assert!(!Fraction::new(0, 1).unwrap().is_negative());
+        assert!(Fraction::new(-1, 1).unwrap().is_negative());
+        assert!(!Fraction::new(1, 1).unwrap().is_negative());
+    }
+
+    #[test]
+    fn test_integral() {
+        assert_eq!(Fraction::new(0, 1).unwrap().integral(), Some(0));
+        assert_eq!(Fraction::new(-1, 1).unwrap().integral(), Some(-1));
+        assert_eq!(Fraction::new(1, 1).unwrap().integral(), Some(1));
+        assert_eq!(Fraction::new(1, 2).unwrap().integral(), None);
+    }
+
+    #[test]
+    fn test_floor_ceil() {
+        assert_eq!(Fraction::new(7, 2).unwrap().floor(), 3);
+        assert_eq!(Fraction::new(7, 2).unwrap().ceil(), 4);
+        assert_eq!(Fraction::new(-7, 2).unwrap().floor(), -4);
+        assert_eq!(Fraction::new(-7, 2).unwrap().ceil(), -3);
+        assert_eq!(Fraction::whole_number(-3).floor(), -3);
+        assert_eq!(Fraction::whole_number(-3).ceil(), -3);
+    }
+
+    #[test]
+    fn test_mul() {
+        let f1 = Fraction::new(0, 1).unwrap();
+        let f2 = Fraction::new(1, 100).unwrap();
+        let f3 = Fraction::new(-1, 2).unwrap();
+        let f4 = Fraction::new(1, 7).unwrap();
+        let f5 = Fraction::new(10, 1).unwrap();
+
+        for fx in [f1, f2, f3, f4] {
+            for fy in [f1, f2, f3, f4] {
+                assert_eq!(fx * fy, fy * fx);
+            }
+        }
+
+        assert_eq!(f1 * f2, Fraction::whole_number(0));
+        assert_eq!(f1 * f3, Fraction::whole_number(0));
+
+        assert_eq!(f2 * f3, Fraction::new(-1, 200).unwrap());
+
+        assert_eq!(f2 * f2, Fraction::new(1, 10_000).unwrap());
+        assert_eq!(f3 * f3, Fraction::new(1, 4).unwrap());
+
+        assert_eq!(f2 * f4, Fraction::new(1, 700).unwrap());
+
+        assert_eq!(f2 * f5, Fraction::new(1, 10).unwrap());
+    }
+
+    #[test]
+    fn test_div_assign() {
+        let f1 = Fraction::new(0, 1).unwrap();
+        let f2 = Fraction::new(1, 100).unwrap();
+        let f3 = Fraction::new(-1, 2).unwrap();
+        let f4 = Fraction::new(1, 7).unwrap();
+        let f5 = Fraction::new(10, 1).unwrap();
+
+        let mut f = f1;
+        f /= f2;
+        assert_eq!(f, f1);
+
+        let mut f = f2;
+        f /= f2;
+        assert_eq!(f, Fraction::whole_number(1));
+
+        let mut f = f3;
+        f /= f3;
+        assert_eq!(f, Fraction::whole_number(1));
+
+        let mut f = f2;
+        f /= f3;
+        assert_eq!(f, Fraction::new(-1, 50).unwrap());
+
+        let mut f = f2;
+        f /= f4;
+        assert_eq!(f, Fraction::new(7, 100).unwrap());
+
+        let mut f = f2;
+        f /= f5;
+        assert_eq!(f, Fraction::new(1, 1000).unwrap());
+    }
+
+    #[test]
+    fn test_sub() {
+        let f1 = Fraction::new(0, 1).unwrap();
+        let f2 = Fraction::new(1, 100).unwrap();
+        let f3 = Fraction::new(-1, 2).unwrap();
+        let f4 = Fraction::new(1, 7).unwrap();
+        let f5 = Fraction::new(10, 1).unwrap();
+
+        assert_eq!(f1 - f2, Fraction::new(-1, 100).unwrap());
+        assert_eq!(f2 - f1, f2);
+
+        assert_eq!(f1 - f3, Fraction::new(1, 2).unwrap());
+        assert_eq!(f2 - f3, Fraction::new(51, 100).unwrap());
+        assert_eq!(f3 - f2, Fraction::new(-51, 100).unwrap());
+        assert_eq!(f2 - f4, Fraction::new(-93, 700).unwrap());
+        assert_eq!(f2 - f5, Fraction::new(-999, 100).unwrap());
+        assert_eq!(
+            Fraction::new(51, 100).unwrap() - Fraction::new(1, 100).unwrap(),
+            Fraction::new(1, 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sub_assign() {
+        let f1 = Fraction::new(0, 1).unwrap();
+        let f2 = Fraction::new(1, 100).unwrap();
+        let f3 = Fraction::new(-1, 2).unwrap();
+        let f4 = Fraction::new(1, 7).unwrap();
+
+        let mut f = f1;
+        f -= f2;
+        assert_eq!(f, Fraction::new(-1, 100).unwrap());
+
+        let mut f = f1;
+        f -= f3;
+        assert_eq!(f, Fraction::new(1, 2).unwrap());
+
+        let mut f = f2;
+        f -= f3;
+        assert_eq!(f, Fraction::new(51, 100).unwrap());
+
+        let mut f = f2;
+        f -= f4;
+        assert_eq!(f, Fraction::new(-93, 700).unwrap());
+
+        let mut f = Fraction::whole_number(-10);
+        f -= Fraction::whole_number(12) * Fraction::whole_number(0);
+        assert_eq!(f, Fraction::whole_number(-10));
+    }
+
+    #[test]
+    fn test_add() {
+        let f2 = Fraction::new(1, 6).unwrap();
+        let f3 = Fraction::new(-1, 4).unwrap();
+        assert_eq!(f2 + f3, Fraction::new(-1, 12).unwrap());
+        assert_eq!(f2 + -f2, Fraction::zero());
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut fractions: Vec<Fraction> = ["1/2", "-1/3", "2", "5/3", "-7/2", "1/3", "0"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        fractions.sort();
+        assert_eq!(
+            fractions.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+            ["-7/2", "-1/3", "0", "1/3", "1/2", "5/3", "2"]
+        );
+        // Cross-multiplying these would overflow
+        let big = Fraction::new(i64::MAX - 1, i64::MAX).unwrap();
+        let bigger = Fraction::new(i64::MAX - 2, i64::MAX - 1).unwrap();
+        assert!(bigger < big);
+    }
+
+    #[test]
+    fn test_checked() {
+        let max = Fraction::whole_number(i64::MAX);
+        assert_eq!(max.checked_add(&Fraction::one()), None);
+        assert_eq!(max.checked_mul(&Fraction::whole_number(2)), None);
+        assert_eq!(max.checked_div(&Fraction::zero()), None);
+        assert_eq!(
+            max.checked_mul(&Fraction::new(1, i64::MAX).unwrap()),
+            Some(Fraction::one())
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_overflow_panics() {
+        let _ = Fraction::whole_number(i64::MAX) + Fraction::one();
+    }
+
+    #[test]
+    fn test_fromstr_display() {
+        assert_eq!("3/-6".parse(), Ok(Fraction::new(-1, 2).unwrap()));
+        assert_eq!(" 4 ".parse(), Ok(Fraction::whole_number(4)));
+        assert!("1/0".parse::<Fraction>().is_err());
+        assert!("a/2".parse::<Fraction>().is_err());
+        assert_eq!(Rational::<i128>::new(4, -6).unwrap().to_string(), "-2/3");
+    }
+}
@@ -0,0 +1,370 @@
+//! Packing polyominoes (shapes made of grid cells) into a region of a grid, as an exact cover
+//! problem.
+//! https://en.wikipedia.org/wiki/Polyomino
+
+use crate::Grid;
+use crate::exact_cover::ExactCover;
+
+/// A shape made of cells, moved so that its top row and left column are both `0`. Cells are
+/// `(i, j)` pairs where `i` is the row and `j` is the column, kept sorted so that shapes with
+/// the same cells compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Polyomino {
+    cells: Vec<(usize, usize)>,
+}
+
+impl Polyomino {
+    /// Panics if there are no cells.
+    pub fn new(cells: impl IntoIterator<Item = (i64, i64)>) -> Polyomino {
+        let cells: Vec<(i64, i64)> = cells.into_iter().collect();
+        let min_i = cells.iter().map(|&(i, _)| i).min().expect("No cells");
+        let min_j = cells.iter().map(|&(_, j)| j).min().unwrap();
+        let mut cells: Vec<(usize, usize)> = cells
+            .into_iter()
+            .map(|(i, j)| ((i - min_i) as usize, (j - min_j) as usize))
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        Polyomino { cells }
+    }
+
+    /// Parse a picture where `#` is part of the shape and anything else isn't.
+    pub fn from_picture(picture: &str) -> Polyomino {
+        Polyomino::new(picture.trim().lines().enumerate().flat_map(|(i, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|&(_, c)| c == '#')
+                .map(move |(j, _)| (i as i64, j as i64))
+        }))
+    }
+
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.iter().map(|&(i, _)| i).max().unwrap() + 1
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.iter().map(|&(_, j)| j).max().unwrap() + 1
+    }
+
+    /// Rotate 90 degrees clockwise.
+    pub fn rotate(&self) -> Polyomino {
+        Polyomino::new(self.cells.iter().map(|&(i, j)| (j as i64, -(i as i64))))
+    }
+
+    /// Mirror left-to-right.
+    pub fn flip(&self) -> Polyomino {
+        Polyomino::new(self.cells.iter().map(|&(i, j)| (i as i64, -(j as i64))))
+    }
+
+    /// Every different way the shape can be rotated and flipped, starting with itself. There
+    /// are 8 at most, and fewer for symmetric shapes.
+    pub fn orientations(&self) -> Vec<Polyomino> {
+        let mut orientations: Vec<Polyomino> = vec![];
+        let mut shape = self.clone();
+        for _ in 0..4 {
+            for candidate in [shape.clone(), shape.flip()] {
+                if !orientations.contains(&candidate) {
+                    orientations.push(candidate);
+                }
+            }
+            shape = shape.rotate();
+        }
+        orientations
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    /// Has to be covered by a piece.
+    Required,
+    /// Can be covered by a piece, or left empty.
+    Optional,
+    /// Can't be covered.
+    Blocked,
+}
+
+/// The cells of a rectangle that pieces can be packed into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    cells: Grid<Cell>,
+}
+
+impl Region {
+    /// A region where every cell can be left empty.
+    pub fn new(height: usize, width: usize) -> Region {
+        Region {
+            cells: Grid::filled(height, width, Cell::Optional),
+        }
+    }
+
+    /// A region where every cell has to be covered.
+    pub fn exact(height: usize, width: usize) -> Region {
+        Region {
+            cells: Grid::filled(height, width, Cell::Required),
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.num_rows()
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.num_cols()
+    }
+
+    /// Make a cell have to be covered.
+    pub fn require(&mut self, i: usize, j: usize) {
+        self.cells[(i, j)] = Cell::Required;
+    }
+
+    /// Make a cell impossible to cover.
+    pub fn block(&mut self, i: usize, j: usize) {
+        self.cells[(i, j)] = Cell::Blocked;
+    }
+
+    /// The number of cells that can be covered.
+    pub fn area(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell != Cell::Blocked)
+            .count()
+    }
+}
+
+/// A piece that's been put somewhere in a region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacedPiece {
+    /// The index of the piece in the list that was packed.
+    pub piece: usize,
+    /// The piece after being rotated and/or flipped.
+    pub shape: Polyomino,
+    /// Where the shape's `(0, 0)` ended up.
+    pub offset: (usize, usize),
+}
+
+impl PlacedPiece {
+    /// The cells of the region that the piece covers.
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (di, dj) = self.offset;
+        self.shape
+            .cells()
+            .iter()
+            .map(move |&(i, j)| (i + di, j + dj))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub height: usize,
+    pub width: usize,
+    pub pieces: Vec<PlacedPiece>,
+}
+
+impl Placement {
+    /// Draw the placement, labelling each placed piece with its own letter (starting from `A`,
+    /// and going round again after `Z`) and leaving empty cells as `.`.
+    pub fn render(&self) -> Grid<char> {
+        let mut grid = Grid::filled(self.height, self.width, '.');
+        for (k, placed) in self.pieces.iter().enumerate() {
+            let label = (b'A' + (k % 26) as u8) as char;
+            for position in placed.cells() {
+                grid[position] = label;
+            }
+        }
+        grid
+    }
+}
+
+/// Find a way to put `count` copies of each `(piece, count)` into the region without any of
+/// them overlapping, covering every required cell. Pieces can be rotated and flipped.
+pub fn pack(region: &Region, pieces: &[(Polyomino, usize)]) -> Option<Placement> {
+    let (height, width) = (region.height(), region.width());
+    let piece_area: usize = pieces
+        .iter()
+        .map(|(piece, count)| piece.area() * count)
+        .sum();
+    let num_required = region
+        .cells
+        .iter()
+        .filter(|&&cell| cell == Cell::Required)
+        .count();
+    // The number of optional cells that have to be left empty
+    let num_holes = region.area().checked_sub(piece_area)?;
+    if num_holes > region.area() - num_required {
+        return None;
+    }
+
+    // Each usable cell is covered exactly once, either by a piece or by a "hole" (a monomino
+    // that only fits in optional cells, and that has to be used `num_holes` times). Each piece
+    // has its own column, which is covered once per copy.
+    let mut columns = Grid::filled(height, width, None);
+    let mut num_columns = 0;
+    for (position, &cell) in region.cells.indexed_iter() {
+        if cell != Cell::Blocked {
+            columns[position] = Some(num_columns);
+            num_columns += 1;
+        }
+    }
+    let used_pieces: Vec<usize> = (0..pieces.len()).filter(|&k| pieces[k].1 > 0).collect();
+    let hole_column = num_columns + used_pieces.len();
+    let mut problem = ExactCover::new(hole_column + usize::from(num_holes > 0), 0);
+
+    let mut rows: Vec<Option<PlacedPiece>> = vec![];
+    for (n, &k) in used_pieces.iter().enumerate() {
+        let (piece, count) = &pieces[k];
+        let piece_column = num_columns + n;
+        problem.set_multiplicity(piece_column, *count);
+        for shape in piece.orientations() {
+            for di in 0..(height + 1).saturating_sub(shape.height()) {
+                for dj in 0..(width + 1).saturating_sub(shape.width()) {
+                    let placed = PlacedPiece {
+                        piece: k,
+                        shape: shape.clone(),
+                        offset: (di, dj),
+                    };
+                    let cells: Option<Vec<usize>> =
+                        placed.cells().map(|position| columns[position]).collect();
+                    if let Some(mut cells) = cells {
+                        cells.push(piece_column);
+                        problem.add_row(&cells);
+                        rows.push(Some(placed));
+                    }
+                }
+            }
+        }
+    }
+    if num_holes > 0 {
+        problem.set_multiplicity(hole_column, num_holes);
+        for (position, &cell) in region.cells.indexed_iter() {
+            if cell == Cell::Optional {
+                problem.add_row(&[columns[position].unwrap(), hole_column]);
+                rows.push(None);
+            }
+        }
+    }
+
+    let solution = problem.solve()?;
+    let mut placed: Vec<PlacedPiece> = solution
+        .into_iter()
+        .filter_map(|row| rows[row].clone())
+        .collect();
+    placed.sort_by_key(|placed| placed.offset);
+    Some(Placement {
+        height,
+        width,
+        pieces: placed,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_orientations() {
+        let l = Polyomino::from_picture("#.\n#.\n##");
+        assert_eq!((l.area(), l.height(), l.width()), (4, 3, 2));
+        assert_eq!(l.rotate(), Polyomino::from_picture("###\n#.."));
+        assert_eq!(l.flip(), Polyomino::from_picture(".#\n.#\n##"));
+        assert_eq!(l.rotate().rotate().rotate().rotate(), l);
+        assert_eq!(l.orientations().len(), 8);
+        assert_eq!(l.orientations()[0], l);
+
+        let t = Polyomino::from_picture("###\n.#.");
+        assert_eq!(t.orientations().len(), 4);
+        assert_eq!(Polyomino::from_picture("##\n##").orientations().len(), 1);
+        assert_eq!(Polyomino::from_picture("#####").orientations().len(), 2);
+        // Anything that isn't a `#` is empty, wherever it is
+        assert_eq!(
+            Polyomino::from_picture("..\n.#\n.#"),
+            Polyomino::new([(5, 5), (6, 5)])
+        );
+    }
+
+    #[test]
+    fn test_pack() {
+        let l = Polyomino::from_picture("#.\n#.\n##");
+        let placement = pack(&Region::exact(2, 4), &[(l.clone(), 2)]).unwrap();
+        assert_eq!(placement.pieces.len(), 2);
+        let rendered = placement.render().to_string();
+        assert!(rendered == "AAAB\nABBB\n" || rendered == "ABBB\nAAAB\n");
+
+        // The pieces don't fill the region, so some cells have to be left empty
+        assert!(pack(&Region::exact(3, 3), &[(l.clone(), 2)]).is_none());
+        let placement = pack(&Region::new(3, 3), &[(l.clone(), 2)]).unwrap();
+        assert_eq!(placement.render().iter().filter(|&&c| c == '.').count(), 1);
+
+        // Force the empty cell into a corner, and the others all have to be covered
+        let mut region = Region::new(3, 3);
+        for (i, j) in [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 0),
+            (1, 1),
+            (1, 2),
+            (2, 0),
+            (2, 1),
+        ] {
+            region.require(i, j);
+        }
+        let domino = Polyomino::from_picture("##");
+        let placement = pack(&region, &[(domino.clone(), 4)]).unwrap();
+        assert_eq!(placement.render()[(2, 2)], '.');
+        assert!(pack(&region, &[(l.clone(), 2)]).is_none());
+
+        // Two L's or four dominoes go round a blocked centre, but it's not covered
+        let mut region = Region::exact(3, 3);
+        region.block(1, 1);
+        let placement = pack(&region, &[(l.clone(), 2)]).unwrap();
+        assert_eq!(placement.render()[(1, 1)], '.');
+        let placement = pack(&region, &[(l, 0), (domino, 4)]).unwrap();
+        assert_eq!(placement.pieces.len(), 4);
+        assert!(placement.pieces.iter().all(|placed| placed.piece == 1));
+
+        // Too much area
+        assert!(pack(&Region::new(2, 2), &[(Polyomino::from_picture("###"), 2)]).is_none());
+    }
+
+    #[test]
+    fn test_pack_pentominoes() {
+        // All 12 pentominoes fill a 6 x 10 rectangle
+        let pictures = [
+            "#####",
+            "####\n#...",
+            "###.\n..##",
+            "####\n.#..",
+            "###\n#.#",
+            "###\n##.",
+            "###\n.#.\n.#.",
+            "#..\n#..\n###",
+            "#..\n##.\n.##",
+            ".#.\n###\n.#.",
+            ".##\n##.\n.#.",
+            "##.\n.#.\n.##",
+        ];
+        let pieces: Vec<(Polyomino, usize)> = pictures
+            .iter()
+            .map(|picture| (Polyomino::from_picture(picture), 1))
+            .collect();
+        let placement = pack(&Region::exact(6, 10), &pieces).unwrap();
+        let rendered = placement.render();
+        for (k, placed) in placement.pieces.iter().enumerate() {
+            assert_eq!(
+                placed.shape.orientations().len(),
+                pieces[placed.piece].0.orientations().len()
+            );
+            let label = (b'A' + k as u8) as char;
+            assert_eq!(rendered.iter().filter(|&&c| c == label).count(), 5);
+        }
+        assert!(rendered.iter().all(|&c| c != '.'));
+    }
+}
@@ -1,3 +1,4 @@
+use std::io::{BufRead, Write};
 use std::time::Duration;
 
 struct ComputedValues {
@@ -54,6 +55,17 @@ pub trait Problem {
         "TODO".to_string()
     }
 
+    /// Interactively explore the problem, reading commands from `input`. Only a few problems
+    /// have something worth exploring, so by default there's nothing to do.
+    fn debug(
+        &self,
+        _contents: &str,
+        _input: &mut dyn BufRead,
+        _output: &mut dyn Write,
+    ) -> Result<(), String> {
+        Err("This problem doesn't have a debugger".to_string())
+    }
+
     #[allow(private_interfaces)]
     fn get_all_computed_values(&self) -> ComputedValues {
         let input_file = self.input();
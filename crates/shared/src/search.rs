@@ -0,0 +1,320 @@
+//! Shortest path searches over implicit graphs.
+//!
+//! States can be anything `Clone + Hash + Eq`, and the graph is described by a closure that
+//! returns the neighbours of a state (along with the cost of moving there, for the weighted
+//! searches). Searches stop as soon as a state matching the goal predicate is reached; pass
+//! `|_| false` to explore everything reachable instead.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Trait alias for the types that can be used as edge costs.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+
+impl<T: Copy + Ord + Default + Add<Output = Self>> Cost for T {}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult<S, C> {
+    /// The cost of the cheapest known path to every state the search discovered. This is exact
+    /// for every state the search got around to expanding, which includes the goals.
+    pub dist: HashMap<S, C>,
+    /// Goal states reached, all with the same optimal cost. Empty if no goal was reachable.
+    /// There will be at most one unless the search was asked for all optimal paths.
+    pub goals: Vec<S>,
+    predecessors: HashMap<S, Vec<S>>,
+}
+
+impl<S: Clone + Hash + Eq, C: Copy> SearchResult<S, C> {
+    /// The cost of the optimal path to a goal.
+    pub fn cost(&self) -> Option<C> {
+        self.goals.first().map(|goal| self.dist[goal])
+    }
+
+    /// An optimal path from the start to the first goal, inclusive of both.
+    pub fn path(&self) -> Option<Vec<S>> {
+        self.path_to(self.goals.first()?)
+    }
+
+    /// A cheapest known path from the start to `state`, inclusive of both.
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        if !self.dist.contains_key(state) {
+            return None;
+        }
+        let mut path = vec![state.clone()];
+        while let Some(prev) = self
+            .predecessors
+            .get(path.last().unwrap())
+            .and_then(|preds| preds.first())
+        {
+            path.push(prev.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The predecessors of `state` along optimal paths. If the search was run with
+    /// [`dijkstra_all_optimal`] then this is all of them, otherwise there is at most one.
+    pub fn predecessors(&self, state: &S) -> &[S] {
+        self.predecessors.get(state).map_or(&[], |preds| preds)
+    }
+
+    /// Every state that lies on an optimal path from the start to a goal.
+    ///
+    /// Only meaningful for results from [`dijkstra_all_optimal`], other searches only remember
+    /// a single optimal path.
+    pub fn optimal_states(&self) -> HashSet<S> {
+        let mut seen: HashSet<S> = HashSet::new();
+        let mut to_visit: Vec<S> = self.goals.clone();
+        while let Some(state) = to_visit.pop() {
+            if seen.insert(state.clone()) {
+                to_visit.extend(self.predecessors(&state).iter().cloned());
+            }
+        }
+        seen
+    }
+}
+
+/// Breadth first search, where every edge has a cost of 1.
+pub fn bfs<S, N, I, G>(start: S, mut neighbors: N, mut is_goal: G) -> SearchResult<S, usize>
+where
+    S: Clone + Hash + Eq,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut dist = HashMap::from([(start.clone(), 0)]);
+    let mut predecessors: HashMap<S, Vec<S>> = HashMap::new();
+    let mut to_visit = VecDeque::from([start]);
+    while let Some(state) = to_visit.pop_front() {
+        if is_goal(&state) {
+            return SearchResult {
+                dist,
+                goals: vec![state],
+                predecessors,
+            };
+        }
+        let next_cost = dist[&state] + 1;
+        for next in neighbors(&state) {
+            if !dist.contains_key(&next) {
+                dist.insert(next.clone(), next_cost);
+                predecessors.insert(next.clone(), vec![state.clone()]);
+                to_visit.push_back(next);
+            }
+        }
+    }
+    SearchResult {
+        dist,
+        goals: vec![],
+        predecessors,
+    }
+}
+
+/// Dijkstra's algorithm. `neighbors` returns `(state, cost)` pairs, and costs must not be negative.
+pub fn dijkstra<S, C, N, I, G>(start: S, neighbors: N, is_goal: G) -> SearchResult<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+{
+    best_first(start, neighbors, |_| C::default(), is_goal, false)
+}
+
+/// Like [`dijkstra`], but keeps going until every goal reachable at the optimal cost has been
+/// found, and remembers all optimal predecessors of every state instead of just one.
+///
+/// The result can be used with [`SearchResult::optimal_states`] to find everything that lies
+/// on any optimal path.
+pub fn dijkstra_all_optimal<S, C, N, I, G>(start: S, neighbors: N, is_goal: G) -> SearchResult<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+{
+    best_first(start, neighbors, |_| C::default(), is_goal, true)
+}
+
+/// A* search. The `heuristic` must never overestimate the remaining cost to a goal, and should
+/// be consistent (i.e. never decrease by more than the edge cost along an edge), otherwise the
+/// result may not be optimal.
+pub fn astar<S, C, N, I, H, G>(
+    start: S,
+    neighbors: N,
+    heuristic: H,
+    is_goal: G,
+) -> SearchResult<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    H: FnMut(&S) -> C,
+    G: FnMut(&S) -> bool,
+{
+    best_first(start, neighbors, heuristic, is_goal, false)
+}
+
+fn best_first<S, C, N, I, H, G>(
+    start: S,
+    mut neighbors: N,
+    mut heuristic: H,
+    mut is_goal: G,
+    all_optimal: bool,
+) -> SearchResult<S, C>
+where
+    S: Clone + Hash + Eq,
+    C: Cost,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    H: FnMut(&S) -> C,
+    G: FnMut(&S) -> bool,
+{
+    let mut dist = HashMap::from([(start.clone(), C::default())]);
+    let mut predecessors: HashMap<S, Vec<S>> = HashMap::new();
+    let mut expanded: HashSet<S> = HashSet::new();
+    let mut goals = vec![];
+    let mut goal_cost = None;
+
+    // The heap holds indexes into `pushed` so that states don't need to implement `Ord`.
+    let mut pushed = vec![];
+    let mut to_visit = BinaryHeap::new();
+    to_visit.push(Reverse((heuristic(&start), 0)));
+    pushed.push(start);
+
+    while let Some(Reverse((priority, index))) = to_visit.pop() {
+        if goal_cost.is_some_and(|goal_cost| priority > goal_cost) {
+            break;
+        }
+        let state: S = pushed[index].clone();
+        if !expanded.insert(state.clone()) {
+            continue;
+        }
+        let cost = dist[&state];
+        if is_goal(&state) {
+            goal_cost = Some(cost);
+            goals.push(state);
+            if all_optimal {
+                continue;
+            }
+            break;
+        }
+        for (next, edge_cost) in neighbors(&state) {
+            let next_cost = cost + edge_cost;
+            match dist.get(&next) {
+                Some(&best) if next_cost > best => {}
+                Some(&best) if next_cost == best => {
+                    // The start has no predecessors, even along zero cost edges back to it
+                    if all_optimal && next != pushed[0] {
+                        predecessors.get_mut(&next).unwrap().push(state.clone());
+                    }
+                }
+                _ => {
+                    dist.insert(next.clone(), next_cost);
+                    predecessors.insert(next.clone(), vec![state.clone()]);
+                    to_visit.push(Reverse((next_cost + heuristic(&next), pushed.len())));
+                    pushed.push(next);
+                }
+            }
+        }
+    }
+
+    SearchResult {
+        dist,
+        goals,
+        predecessors,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A small diamond with two equally short routes from 0 to 3, and a long detour via 4.
+    fn edges(state: &u32) -> Vec<(u32, u64)> {
+        match state {
+            0 => vec![(1, 1), (2, 1), (4, 1)],
+            1 => vec![(3, 2)],
+            2 => vec![(3, 2)],
+            4 => vec![(3, 5)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_bfs() {
+        let result = bfs(
+            (0_i32, 0_i32),
+            |&(x, y)| [(x + 1, y), (x, y + 1)],
+            |&p| p == (2, 3),
+        );
+        assert_eq!(result.cost(), Some(5));
+        let path = result.path().unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[5], (2, 3));
+
+        let result = bfs(0, |&n| [n + 1].into_iter().filter(|&n| n < 5), |_| false);
+        assert_eq!(result.cost(), None);
+        assert_eq!(result.dist.len(), 5);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let result = dijkstra(0, edges, |&s| s == 3);
+        assert_eq!(result.cost(), Some(3));
+        assert_eq!(result.path().unwrap().len(), 3);
+        assert_eq!(result.optimal_states().len(), 3);
+    }
+
+    #[test]
+    fn test_dijkstra_all_optimal() {
+        let result = dijkstra_all_optimal(0, edges, |&s| s == 3);
+        assert_eq!(result.cost(), Some(3));
+        assert_eq!(result.predecessors(&3).len(), 2);
+        assert_eq!(result.optimal_states(), HashSet::from([0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_dijkstra_all_optimal_zero_cost_edges() {
+        // A zero cost self loop on the start, and a zero cost edge back to it
+        let result = dijkstra_all_optimal(
+            0,
+            |&s: &u32| match s {
+                0 => vec![(0, 0), (1, 0), (2, 1)],
+                1 => vec![(0, 0), (2, 1)],
+                _ => vec![],
+            },
+            |&s| s == 2,
+        );
+        assert_eq!(result.cost(), Some(1));
+        assert_eq!(result.predecessors(&0), &[]);
+        assert_eq!(result.predecessors(&2).len(), 2);
+        assert_eq!(result.path(), Some(vec![0, 2]));
+        assert_eq!(result.optimal_states(), HashSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn test_astar() {
+        let target = (5_i64, 7_i64);
+        let result = astar(
+            (0_i64, 0_i64),
+            |&(x, y)| {
+                [
+                    ((x + 1, y), 1),
+                    ((x, y + 1), 1),
+                    ((x - 1, y), 1),
+                    ((x, y - 1), 1),
+                ]
+            },
+            |&(x, y)| (target.0 - x).abs() + (target.1 - y).abs(),
+            |&p| p == target,
+        );
+        assert_eq!(result.cost(), Some(12));
+        assert_eq!(result.path().unwrap().len(), 13);
+    }
+}
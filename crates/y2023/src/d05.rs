@@ -1,124 +1,53 @@
 use shared::Problem;
+use shared::interval::{IntervalSet, PiecewiseMap};
 
-use std::collections::HashSet;
-
-struct MapRange {
-    source: u64,
-    dest: u64,
-    range: u64,
-}
-
-struct Map {
-    ranges: Vec<MapRange>,
-}
-
-impl Map {
-    fn source_to_dest(&self, src: u64) -> u64 {
-        for range in &self.ranges {
-            if range.source <= src && src < range.source + range.range {
-                return range.dest + (src - range.source);
-            }
-        }
-        src
-    }
-    fn dest_to_source(&self, dst: u64) -> u64 {
-        for range in &self.ranges {
-            if range.dest <= dst && dst < range.dest + range.range {
-                return range.source + (dst - range.dest);
-            }
-        }
-        dst
-    }
-}
-
-fn parse_input(contents: &str) -> (Vec<u64>, Vec<Map>) {
+fn parse_input(contents: &str) -> (Vec<u64>, Vec<PiecewiseMap<u64>>) {
     let mut lines = contents.split('\n').filter(|s| !s.is_empty());
     let seeds = lines.next().unwrap()[7..]
         .split_whitespace()
         .map(|s| s.parse().unwrap())
         .collect();
     let mut maps = vec![];
-    let mut map_ranges = vec![];
+    let mut map = PiecewiseMap::identity();
     for line in lines.chain("terminate:".split_whitespace()) {
         if line.contains(':') {
-            maps.push(Map { ranges: map_ranges });
-            map_ranges = vec![];
+            maps.push(map);
+            map = PiecewiseMap::identity();
         } else {
             let mut line_split = line.split_whitespace();
-            let dest = line_split.next().unwrap().parse().unwrap();
-            let source = line_split.next().unwrap().parse().unwrap();
-            let range = line_split.next().unwrap().parse().unwrap();
-            map_ranges.push(MapRange {
-                dest,
-                source,
-                range,
-            })
+            let dest: u64 = line_split.next().unwrap().parse().unwrap();
+            let source: u64 = line_split.next().unwrap().parse().unwrap();
+            let range: u64 = line_split.next().unwrap().parse().unwrap();
+            map.insert(source..source + range, dest);
         }
     }
     (seeds, maps)
 }
 
+/// Compose every map into a single seed-to-location map.
+fn seed_to_location(maps: &[PiecewiseMap<u64>]) -> PiecewiseMap<u64> {
+    maps.iter()
+        .fold(PiecewiseMap::identity(), |composed, map| composed.then(map))
+}
+
 fn compute_1(contents: &str) -> u64 {
     let (seeds, maps) = parse_input(contents);
-    let mut curr = u64::MAX;
-    for mut seed in seeds {
-        for map in maps.iter() {
-            seed = map.source_to_dest(seed)
-        }
-        curr = std::cmp::min(curr, seed);
-    }
-    curr
+    let map = seed_to_location(&maps);
+    seeds.into_iter().map(|seed| map.apply(seed)).min().unwrap()
 }
 
 fn compute_2(contents: &str) -> u64 {
     let (seeds, maps) = parse_input(contents);
-    let seed_ranges: Vec<(u64, u64)> = seeds
-        .iter()
-        .step_by(2)
-        .zip(seeds.iter().skip(1).step_by(2))
-        .map(|(a, b)| (*a, *b))
+    let seed_ranges: IntervalSet<u64> = seeds
+        .chunks(2)
+        .map(|chunk| chunk[0]..chunk[0] + chunk[1])
         .collect();
-    // Find discontinuities, which are all of the critical points
-    // of a bunch of piecewise strictly monotonically increasing functions
-    // We want discontinuities in seed space, so work backwards.
-    let mut prev_crit_points: HashSet<u64> = HashSet::new();
-    prev_crit_points.extend(maps.last().unwrap().ranges.iter().map(|r| r.dest));
-    prev_crit_points.extend(
-        maps.last()
-            .unwrap()
-            .ranges
-            .iter()
-            .map(|r| r.dest + r.range + 1),
-    );
-    prev_crit_points.insert(0);
-    let mut crit_points: HashSet<u64> = HashSet::new();
-    for map in maps.iter().rev() {
-        // crit_points is the set of critical points in the SOURCE space
-        crit_points = HashSet::new();
-        crit_points.insert(0);
-        for range in &map.ranges {
-            crit_points.insert(map.dest_to_source(range.dest));
-            crit_points.insert(map.dest_to_source(range.dest + range.range + 1));
-        }
-        for prev_crit_point in prev_crit_points {
-            crit_points.insert(map.dest_to_source(prev_crit_point));
-        }
-        prev_crit_points = crit_points.clone();
-    }
-    crit_points.extend(seed_ranges.iter().map(|(a, _)| a));
-    crit_points.retain(|c| {
-        seed_ranges
-            .iter()
-            .any(|(start, range)| start <= c && *c < start + range)
-    });
-    let mut curr = u64::MAX;
-    for mut seed in crit_points {
-        for map in maps.iter() {
-            seed = map.source_to_dest(seed)
-        }
-        curr = std::cmp::min(curr, seed);
-    }
-    curr
+    // The maps are piecewise translations, so the image of every seed range can be worked out
+    // in one go, without looking at individual seeds.
+    seed_to_location(&maps)
+        .apply_set(&seed_ranges)
+        .min()
+        .unwrap()
 }
 
 pub(crate) struct Day {}
@@ -140,3 +69,53 @@ impl Problem for Day {
         "17729182".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 35);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 46);
+    }
+}
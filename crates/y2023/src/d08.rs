@@ -1,6 +1,10 @@
 use shared::Problem;
+use shared::math::crt_choices_at_least;
 use std::collections::HashMap;
 
+/// How many combinations of terminal nodes from each ghost's cycle to try with the CRT.
+const MAX_COMBINATIONS: usize = 100_000;
+
 #[derive(Debug, PartialEq, Eq)]
 enum NodeType {
     Start,
@@ -109,43 +113,8 @@ fn compute_1(contents: &str) -> u64 {
     counter
 }
 
-// fn argmin(v: &[usize]) -> usize {
-//     let mut i = 0;
-//     let mut curr_min = usize::MAX;
-//     for (j, v_j) in v.iter().enumerate() {
-//         if *v_j < curr_min {
-//             i = j;
-//             curr_min = *v_j;
-//         }
-//     }
-//     i
-// }
-
-/// Implement Euclid's algorithm
-/// https://en.wikipedia.org/wiki/Euclidean_algorithm
-fn gcd(a: usize, b: usize) -> usize {
-    if a == 0 && b == 0 {
-        return 0;
-    }
-    let (mut a, mut b) = { if b < a { (b, a) } else { (a, b) } };
-    while b != 0 {
-        let prev = b;
-        b = a % b;
-        a = prev;
-    }
-    a
-}
-
-fn lcm(a: usize, b: usize) -> usize {
-    if a == 0 && b == 0 {
-        0
-    } else {
-        a / gcd(a, b) * b
-    }
-}
-
 fn compute_2(contents: &str) -> usize {
-    let input = parse_inputs(&contents);
+    let input = parse_inputs(contents);
     let nodes = Nodes { nodes: input.nodes };
 
     let mut cycles = vec![];
@@ -178,29 +147,44 @@ fn compute_2(contents: &str) -> usize {
         }
     }
 
-    // for a given cycle with warmup W, length L, and terminal T,
+    // Before every ghost has settled into its cycle, they might all happen to be on
+    // terminal nodes at the same time, so check those steps directly.
+    let max_warmup = cycles.iter().map(|c| c.warmup).max().unwrap_or(0);
+    let mut node_indexes = nodes.initial_indexes();
+    for (step, move_left) in input.moves.iter().cycle().take(max_warmup).enumerate() {
+        if node_indexes
+            .iter()
+            .all(|n| nodes.nodes[*n].node_type == NodeType::Stop)
+        {
+            return step;
+        }
+        for node_index in node_indexes.iter_mut() {
+            *node_index = if *move_left {
+                nodes.nodes[*node_index].left
+            } else {
+                nodes.nodes[*node_index].right
+            };
+        }
+    }
+
+    // After that, for a given cycle with warmup W, length L, and terminal T,
     // the valid ending steps are given by the formula
-    // Wc + Tc + (Lc * i) for i = 0, 1, ...
-    let lengths: Vec<usize> = cycles
-        .iter()
-        .flat_map(|c| c.terminals.iter().map(|_| c.length))
-        .collect();
-    let steps: Vec<usize> = cycles
+    // W + T + (L * i) for i = 0, 1, ...
+    // Pick one terminal per cycle and solve the resulting congruences with the CRT.
+    let congruence_choices: Vec<Vec<(i128, i128)>> = cycles
         .iter()
-        .flat_map(|c| c.terminals.iter().map(|t| c.warmup + t))
+        .map(|c| {
+            c.terminals
+                .iter()
+                .map(|t| ((c.warmup + t) as i128, c.length as i128))
+                .collect()
+        })
         .collect();
-    // SIMPLIFICATION: This was not guaranteed in the problem text, but at least in my input
-    // the length of the cycle is always equal to the length of the warmup and the terminal.
-    // This lets us simplify things to just looking at the least common mulitple.
-    assert_eq!(lengths, steps);
-
-    lengths.into_iter().reduce(|a, b| lcm(a, b)).unwrap()
-
-    // while steps.iter().any(|s| s != &steps[0]) {
-    //     let cycle_index = argmin(&steps);
-    //     steps[cycle_index] += lengths[cycle_index];
-    // }
-    // steps[0]
+    let steps = crt_choices_at_least(&congruence_choices, max_warmup as i128, MAX_COMBINATIONS)
+        .unwrap_or_else(|n| panic!("There are {n} ways to line up the ghosts' terminal nodes"));
+    *steps
+        .first()
+        .expect("The ghosts never all reach a terminal node at the same time") as usize
 }
 
 pub(crate) struct Day {}
@@ -229,24 +213,17 @@ mod test {
     use super::*;
 
     #[test]
-    fn test_gcd() {
-        assert_eq!(gcd(12, 18), 6);
-        assert_eq!(gcd(18, 12), 6);
-        assert_eq!(gcd(12, 12), 12);
-        assert_eq!(gcd(12, 1), 1);
-        assert_eq!(gcd(3, 12), 3);
-        assert_eq!(gcd(1, 0), 1);
-        assert_eq!(gcd(0, 0), 0);
-    }
-
-    #[test]
-    fn test_lcm() {
-        assert_eq!(lcm(12, 18), 36);
-        assert_eq!(lcm(18, 12), 36);
-        assert_eq!(lcm(12, 12), 12);
-        assert_eq!(lcm(12, 1), 12);
-        assert_eq!(lcm(3, 12), 12);
-        assert_eq!(lcm(1, 0), 0);
-        assert_eq!(lcm(0, 0), 0);
+    fn test_compute_2_example() {
+        let example = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+        assert_eq!(compute_2(example), 6);
     }
 }
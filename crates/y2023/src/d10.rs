@@ -1,6 +1,5 @@
 use shared::Problem;
-
-use std::collections::HashSet;
+use shared::geom::{Vec2, polygon};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 enum Dir {
@@ -54,16 +53,6 @@ impl Tile {
             }
         }
     }
-
-    fn solo_east_west_dir(&self) -> Option<Dir> {
-        match self {
-            Tile::Pipe((Dir::N, Dir::E)) => Some(Dir::E),
-            Tile::Pipe((Dir::N, Dir::W)) => Some(Dir::W),
-            Tile::Pipe((Dir::S, Dir::E)) => Some(Dir::E),
-            Tile::Pipe((Dir::S, Dir::W)) => Some(Dir::W),
-            _ => None,
-        }
-    }
 }
 
 type PipeGrid = Vec<Vec<Tile>>;
@@ -122,21 +111,20 @@ fn traverse_pipes(
     start_i: usize,
     start_j: usize,
     mut dir_to: Dir,
-) -> (Vec<u64>, HashSet<(usize, usize)>) {
-    let mut visited = HashSet::new();
-    visited.insert((start_i, start_j));
+) -> (Vec<u64>, Vec<(usize, usize)>) {
+    let mut path = vec![(start_i, start_j)];
     let mut dists = vec![]; // leave off initial zero so that reversing it aligns the two vectors
     let mut counter = 1;
     let (mut i, mut j) = update_i_j(start_i, start_j, dir_to);
     while (i, j) != (start_i, start_j) {
-        visited.insert((i, j));
+        path.push((i, j));
         dists.push(counter);
         counter += 1;
         let dir_from = dir_to.opposite();
         dir_to = pipes[i][j].where_to(dir_from);
         (i, j) = update_i_j(i, j, dir_to);
     }
-    (dists, visited)
+    (dists, path)
 }
 
 fn compute_1(contents: &str) -> u64 {
@@ -175,9 +163,7 @@ fn compute_1(contents: &str) -> u64 {
 }
 
 fn compute_2(contents: &str) -> u64 {
-    let (mut pipes, (s_i, s_j)) = parse_input(&contents);
-    // pipes.insert(0, vec![Pipe::Ground; pipes[0].len()]);
-    // let s_i = s_i + 1;
+    let (pipes, (s_i, s_j)) = parse_input(contents);
 
     // Find the directions we can go to from the start.
     let mut dirs = vec![];
@@ -199,57 +185,14 @@ fn compute_2(contents: &str) -> u64 {
     }
     assert!(dirs.len() == 2);
 
-    let visited = traverse_pipes(&pipes, s_i, s_j, dirs[0]).1;
-
-    pipes[s_i][s_j] = Tile::new_pipe(dirs[0], dirs[1]);
-
-    let mut contained_count = 0;
-    for i in 0..pipes.len() {
-        for j in 0..pipes[0].len() {
-            if !visited.contains(&(i, j)) {
-                let mut odd_intersrection_count = false;
-                let mut how_we_got_here: Option<Dir> = None;
-                // cast a vertical ray upwards from the location
-                // requires ugly bookkeeping to detect situations like
-                //
-                // --7.
-                // ..|.
-                // --J.
-                //   X
-                //
-                // which shouldn't count as intersecting a pipe when casting the ray from X
-                for (k, pipes_k) in pipes.iter().take(i).enumerate() {
-                    if visited.contains(&(k, j)) {
-                        let curr_pipes_east_west_dir = pipes_k[j].solo_east_west_dir();
-                        match how_we_got_here {
-                            Some(incoming_dir) => {
-                                if let Some(d) = curr_pipes_east_west_dir {
-                                    if d != incoming_dir {
-                                        odd_intersrection_count = !odd_intersrection_count;
-                                    }
-                                    how_we_got_here = None;
-                                }
-                            }
-                            None => match curr_pipes_east_west_dir {
-                                Some(d) => {
-                                    how_we_got_here = Some(d);
-                                }
-                                None => {
-                                    odd_intersrection_count = !odd_intersrection_count;
-                                }
-                            },
-                        }
-                    } else {
-                        how_we_got_here = None;
-                    }
-                }
-                if odd_intersrection_count {
-                    contained_count += 1;
-                }
-            }
-        }
-    }
-    contained_count
+    // Every tile of the loop is a vertex of a polygon, so the tiles enclosed by the loop are
+    // the integer points strictly inside it.
+    let vertices: Vec<Vec2<i64>> = traverse_pipes(&pipes, s_i, s_j, dirs[0])
+        .1
+        .into_iter()
+        .map(|(i, j)| Vec2::new(j as i64, i as i64))
+        .collect();
+    polygon::interior_points(&vertices) as u64
 }
 
 pub(crate) struct Day {}
@@ -271,3 +214,44 @@ impl Problem for Day {
         "265".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compute_1_example() {
+        let example = "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...";
+        assert_eq!(compute_1(example), 8);
+    }
+
+    #[test]
+    fn test_compute_2_examples() {
+        let example = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+        assert_eq!(compute_2(example), 4);
+
+        let example = ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+        assert_eq!(compute_2(example), 8);
+    }
+}
@@ -1,6 +1,6 @@
-use shared::Problem;
+use shared::{Problem, cycle};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Rock {
     Round,
     Square,
@@ -152,43 +152,18 @@ fn tilt_east_west(platform: &mut Platform, is_west: bool) {
     }
 }
 
-fn get_round_rock_locations(platform: &Platform) -> std::collections::HashSet<(usize, usize)> {
-    let mut locations = std::collections::HashSet::new();
-    for (i, row) in platform.iter().enumerate() {
-        for (j, element) in row.iter().enumerate() {
-            if element == &Rock::Round {
-                locations.insert((i, j));
-            }
-        }
-    }
-    locations
+fn spin(platform: &Platform) -> Platform {
+    let mut platform = platform.clone();
+    tilt_north_south(&mut platform, true);
+    tilt_east_west(&mut platform, true);
+    tilt_north_south(&mut platform, false);
+    tilt_east_west(&mut platform, false);
+    platform
 }
 
 fn compute_2(contents: &str) -> usize {
-    let mut platform = parse_input(contents);
-    let mut locations = Vec::new();
-    let mut loads = Vec::new();
-    for iteration_count in 0..10000000 {
-        tilt_north_south(&mut platform, true);
-        tilt_east_west(&mut platform, true);
-        tilt_north_south(&mut platform, false);
-        tilt_east_west(&mut platform, false);
-        let curr_locations = get_round_rock_locations(&platform);
-        loads.push(compute_load(&platform));
-        if locations.contains(&curr_locations) {
-            let matching_iter_count = locations
-                .iter()
-                .enumerate()
-                .find(|(_, l)| **l == curr_locations)
-                .unwrap()
-                .0;
-            let cycle_len = iteration_count - matching_iter_count;
-            let cycle_index = (1000000000 - matching_iter_count - 1) % cycle_len;
-            return loads[matching_iter_count + cycle_index];
-        }
-        locations.push(curr_locations);
-    }
-    compute_load(&platform)
+    let platform = parse_input(contents);
+    compute_load(&cycle::nth_state(platform, spin, 1000000000))
 }
 
 pub(crate) struct Day {}
@@ -210,3 +185,29 @@ impl Problem for Day {
         "87700".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 136);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 64);
+    }
+}
@@ -1,10 +1,7 @@
 use shared::Problem;
+use shared::geom::{Vec2, polygon};
 
-use std::{
-    cmp::{max, min},
-    ops::{Add, Sub},
-    str::FromStr,
-};
+use std::str::FromStr;
 
 #[derive(PartialEq, Eq, Hash, Debug)]
 enum Dir {
@@ -31,34 +28,6 @@ impl FromStr for Dir {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy)]
-struct Point {
-    x: i64,
-    y: i64,
-}
-
-impl Add for Point {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
-    }
-}
-
-impl Sub for Point {
-    type Output = Self;
-
-    fn sub(self, other: Self) -> Self {
-        Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
-    }
-}
-
 struct Instruction {
     dir: Dir,
     count: i64,
@@ -114,295 +83,50 @@ impl FromStr for Instruction2 {
     }
 }
 
-fn parse_input(contents: &str) -> Vec<Point> {
-    let (mut x, mut y) = (0, 0);
-    let mut vertices = vec![Point { x: 0, y: 0 }];
-    for instruction_string in contents.trim().split('\n') {
-        let instruction: Instruction = instruction_string
-            .parse()
-            .expect("Malformed instruction string '{instruction_string}'");
-        match instruction.dir {
-            Dir::U => {
-                vertices.push(Point {
-                    x,
-                    y: y + instruction.count,
-                });
-                y += instruction.count;
-            }
-            Dir::D => {
-                vertices.push(Point {
-                    x,
-                    y: y - instruction.count,
-                });
-                y -= instruction.count;
-            }
-            Dir::L => {
-                vertices.push(Point {
-                    x: x - instruction.count,
-                    y,
-                });
-                x -= instruction.count;
-            }
-            Dir::R => {
-                vertices.push(Point {
-                    x: x + instruction.count,
-                    y,
-                });
-                x += instruction.count;
-            }
-        }
+/// The corners of the trench, as the cells the digger turns in.
+fn dig(steps: impl Iterator<Item = (Dir, i64)>) -> Vec<Vec2<i64>> {
+    let mut position = Vec2::new(0, 0);
+    let mut vertices = vec![position];
+    for (dir, count) in steps {
+        position += match dir {
+            Dir::U => Vec2::new(0, count),
+            Dir::D => Vec2::new(0, -count),
+            Dir::L => Vec2::new(-count, 0),
+            Dir::R => Vec2::new(count, 0),
+        };
+        vertices.push(position);
     }
 
     // Make sure it begins and ends at (0, 0)
-    assert_eq!(vertices.first().unwrap(), &Point { x: 0, y: 0 });
-    assert_eq!(vertices.last().unwrap(), &Point { x: 0, y: 0 });
+    assert_eq!(vertices.last().unwrap(), &Vec2::new(0, 0));
 
     vertices
 }
 
-fn parse_input_2(contents: &str) -> Vec<Point> {
-    let (mut x, mut y) = (0, 0);
-    let mut vertices = vec![Point { x: 0, y: 0 }];
-    for instruction_string in contents.trim().split('\n') {
-        let instruction: Instruction2 = instruction_string
-            .parse()
-            .expect("Malformed instruction string '{instruction_string}'");
-        match instruction.dir {
-            Dir::U => y += instruction.count,
-            Dir::D => y -= instruction.count,
-            Dir::L => x -= instruction.count,
-            Dir::R => x += instruction.count,
-        }
-        vertices.push(Point { x, y });
-    }
-
-    // Make sure it begins and ends at (0, 0)
-    assert_eq!(vertices.first().unwrap(), &Point { x: 0, y: 0 });
-    assert_eq!(vertices.last().unwrap(), &Point { x: 0, y: 0 });
-
-    vertices
+/// The trench goes through the middle of each cell it digs, so the lagoon is every cell on or
+/// inside it.
+fn lagoon_size(vertices: &[Vec2<i64>]) -> u64 {
+    polygon::lattice_points(vertices) as u64
 }
 
 fn compute_1(contents: &str) -> u64 {
-    let vertices = parse_input(contents);
-
-    let lower_bound = vertices.iter().map(|p| p.y).min().unwrap();
-    let upper_bound = vertices.iter().map(|p| p.y).max().unwrap();
-
-    let mut counter = 0;
-    for y in (lower_bound..upper_bound + 1).rev() {
-        counter += num_interior_points_on_horizontal(&vertices, y);
-    }
-    counter
-}
-
-fn num_interior_points_on_horizontal(vertices: &[Point], y: i64) -> u64 {
-    // println!("");
-    // All line segments that will intersect the horizontal ray at y
-    let mut relevant_line_segments: Vec<(&Point, &Point)> = vertices
-        .iter()
-        .zip(vertices.iter().skip(1))
-        .filter(|(p1, p2)| min(p1.y, p2.y) <= y && y <= max(p1.y, p2.y))
-        .collect();
-
-    // Order by x values
-    relevant_line_segments.sort_by(|ls1, ls2| (ls1.0.x + ls1.1.x).cmp(&(ls2.0.x + ls2.1.x)));
-
-    let mut counter = 0;
-    let mut parity_change_points: Vec<i64> = vec![];
-
-    for (i, (p1, p2)) in relevant_line_segments.iter().enumerate() {
-        if p1.x == p2.x {
-            // the y's vary
-            // Treat vertical line segments as bottom-open top-closed.
-            // This will handle situations like ,---' and '---, (parity should change)
-            // vs. ,---, and '---' (parity does not change):
-            //
-            // line segments :  |  ,---'  '---,  ,---,  '---'
-            // parity changes:  ^  ^          ^  ^   ^
-            if min(p1.y, p2.y) < y && y <= max(p1.y, p2.y) {
-                parity_change_points.push(p1.x);
-            }
-        } else {
-            // the x's vary
-
-            // This line must be in the interior of the relevant_line_segments array
-            // So doing i-1 and i+1 is safe.
-
-            let (prev_p1, prev_p2) = relevant_line_segments[i - 1];
-            let (next_p1, next_p2) = relevant_line_segments[i + 1];
-
-            let prev_came_from_up = prev_p1.y > y || prev_p2.y > y;
-            let next_goes_to_up = next_p1.y > y || next_p2.y > y;
-            let currently_exterior = parity_change_points.len() % 2 == 0;
-
-            // The only way we intersect is if we lie on top of the line.
-            // If we're already in the interior, then this line segment will
-            // get counted anyway. Otherwise, we'll need to adjust our counting:
-            //
-            //  ,---'  (off) |  '---,  (off) |  ,---,  (off) |  '---'  (off)
-            // iiooooo (n)   | oooooii (n)   | iioooii (n-1) | ooooooo (n+1)
-
-            if currently_exterior {
-                if (prev_came_from_up && !next_goes_to_up)
-                    || (!prev_came_from_up && next_goes_to_up)
-                {
-                    counter += p1.x.abs_diff(p2.x);
-                } else if !prev_came_from_up && !next_goes_to_up {
-                    counter += p1.x.abs_diff(p2.x) - 1_u64;
-                } else if prev_came_from_up && next_goes_to_up {
-                    counter += p1.x.abs_diff(p2.x) + 1_u64;
-                }
-            }
-        }
-    }
-    assert_eq!(parity_change_points.len() % 2, 0);
-    for (x1, x2) in parity_change_points
-        .iter()
-        .step_by(2)
-        .zip(parity_change_points.iter().skip(1).step_by(2))
-    {
-        counter += (*x2 - *x1) as u64 + 1;
-    }
-    counter
-}
-
-/// Possible ways to "corner", e.g. LeftUp means coming from Left, going Up
-#[derive(Debug, PartialEq, Eq)]
-enum DirectedTurn {
-    LeftUp,
-    LeftDown,
-    RightUp,
-    RightDown,
-    UpLeft,
-    UpRight,
-    DownLeft,
-    DownRight,
-}
-
-fn corner_type(p0: &Point, p1: &Point, p2: &Point) -> DirectedTurn {
-    if p0.x < p1.x {
-        if p2.y > p1.y {
-            DirectedTurn::LeftUp
-        } else {
-            DirectedTurn::LeftDown
-        }
-    } else if p0.x > p1.x {
-        if p2.y > p1.y {
-            DirectedTurn::RightUp
-        } else {
-            DirectedTurn::RightDown
-        }
-    } else if p0.y > p1.y {
-        if p2.x < p1.x {
-            DirectedTurn::UpLeft
-        } else {
-            DirectedTurn::UpRight
-        }
-    } else {
-        if p2.x < p1.x {
-            DirectedTurn::DownLeft
-        } else {
-            DirectedTurn::DownRight
-        }
-    }
+    let vertices = dig(contents.trim().split('\n').map(|instruction_string| {
+        let instruction: Instruction = instruction_string
+            .parse()
+            .expect("Malformed instruction string '{instruction_string}'");
+        (instruction.dir, instruction.count)
+    }));
+    lagoon_size(&vertices)
 }
 
 fn compute_2(contents: &str) -> u64 {
-    let mut vertices = parse_input_2(contents);
-    assert_eq!(vertices.first().unwrap(), &Point { x: 0, y: 0 });
-    assert_eq!(vertices.last().unwrap(), &Point { x: 0, y: 0 });
-
-    // "Correct" the placement of vertices. Assume that a vertex of (i, j)
-    // in vertices means that the snow plow filled the area from (i, j) to (i+1, j+1)
-    // on the coordinate grid. We first need to find the "handed-ness" of the polygon,
-    // i.e. is it defined clockwise vs. counterclockwise. There's probably a better
-    // way to do this, but I'm going to find the a lower left corner where I know
-    // the exterior is to the left/down, and see which way the vertices are aligned.
-
-    let mut low_left = *vertices.first().unwrap();
-    let mut low_left_index = 0;
-    for (i, p) in vertices.iter().enumerate() {
-        if p.x < low_left.x || (p.x == low_left.x && p.y < low_left.y) {
-            low_left = *p;
-            low_left_index = i;
-        }
-    }
-    let prev = if low_left_index == 0 {
-        vertices[vertices.len() - 2]
-    } else {
-        vertices[low_left_index - 1]
-    };
-    let next = vertices[(low_left_index + 1) % vertices.len()];
-    let is_clockwise = match corner_type(&prev, &low_left, &next) {
-        DirectedTurn::RightUp => true,
-        DirectedTurn::UpRight => false,
-        _ => panic!("Inconceivable!"),
-    };
-
-    // Since we'll be iterating over (prev, curr, next) triples, tack item number 2 to the end.
-    vertices.push(*vertices.iter().nth(1).unwrap());
-
-    // This could be done in place with a little bit of extra book-keeping, but
-    // I'm not going to worry about that...
-    let mut new_vertices: Vec<Point> = Vec::with_capacity(vertices.len());
-    for ((prev, curr), next) in vertices
-        .iter()
-        .zip(vertices.iter().skip(1))
-        .zip(vertices.iter().skip(2))
-    {
-        // This here is the secret sauce. We're basically figuring out how to
-        // translate the snow plow's (i, j) position, which actually means
-        // the snow plow is clearing the square defined by (i, j) -> (i+1, j+1),
-        // into an actual polygon's coordinate. The trick is that we need to adjust
-        // the (i, j) coordinate by different amounts depending on the type
-        // of corner we observed and the handed-ness of the polygon.
-        //
-        // E.g. a corner that looks like an L shape, with `prev` being right of the
-        // corner and `next` being above the corner, requires no adjustment for a
-        // clockwise-defined polygon, and an adjustment of +1, +1 for a
-        // counter-clockwise-defined polygon:
-        //
-        //      clockwise     counter-clockwise
-        //         ↑#               #↑
-        //         ↑#               #↑
-        //         ↑#               #+←←←
-        //         ↑#####           #####
-        //         +←←←←←
-        let adjustment = match (corner_type(prev, curr, next), is_clockwise) {
-            (DirectedTurn::LeftUp, true) => Point { x: 0, y: 1 },
-            (DirectedTurn::LeftUp, false) => Point { x: 1, y: 0 },
-            (DirectedTurn::LeftDown, true) => Point { x: 1, y: 1 },
-            (DirectedTurn::LeftDown, false) => Point { x: 0, y: 0 },
-            (DirectedTurn::RightUp, true) => Point { x: 0, y: 0 },
-            (DirectedTurn::RightUp, false) => Point { x: 1, y: 1 },
-            (DirectedTurn::RightDown, true) => Point { x: 1, y: 0 },
-            (DirectedTurn::RightDown, false) => Point { x: 0, y: 1 },
-            (DirectedTurn::UpLeft, true) => Point { x: 1, y: 0 },
-            (DirectedTurn::UpLeft, false) => Point { x: 0, y: 1 },
-            (DirectedTurn::UpRight, true) => Point { x: 1, y: 1 },
-            (DirectedTurn::UpRight, false) => Point { x: 0, y: 0 },
-            (DirectedTurn::DownLeft, true) => Point { x: 0, y: 0 },
-            (DirectedTurn::DownLeft, false) => Point { x: 1, y: 1 },
-            (DirectedTurn::DownRight, true) => Point { x: 0, y: 1 },
-            (DirectedTurn::DownRight, false) => Point { x: 1, y: 0 },
-        };
-        new_vertices.push(*curr + adjustment);
-    }
-    new_vertices.push(*new_vertices.first().unwrap());
-
-    // Finally, use a consequence of Stoke's theorem to compute the area.
-    // I definitely did not know this off the top of my head, I found it on Wikipedia.
-    // https://en.wikipedia.org/wiki/Shoelace_formula
-    let out = new_vertices
-        .iter()
-        .zip(new_vertices.iter().skip(1))
-        .map(|(p0, p1)| p0.x * p1.y - p0.y * p1.x)
-        .sum::<i64>()
-        .unsigned_abs()
-        / 2;
-
-    out
+    let vertices = dig(contents.trim().split('\n').map(|instruction_string| {
+        let instruction: Instruction2 = instruction_string
+            .parse()
+            .expect("Malformed instruction string '{instruction_string}'");
+        (instruction.dir, instruction.count)
+    }));
+    lagoon_size(&vertices)
 }
 
 pub(crate) struct Day {}
@@ -429,15 +153,28 @@ impl Problem for Day {
 mod test {
     use super::*;
 
+    const EXAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 62);
+    }
+
     #[test]
-    fn test_corner_type() {
-        assert_eq!(
-            corner_type(
-                &Point { x: 0, y: 0 },
-                &Point { x: 10, y: 0 },
-                &Point { x: 10, y: -10 }
-            ),
-            DirectedTurn::LeftDown
-        );
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 952408144115);
     }
 }
@@ -1,4 +1,5 @@
 use shared::Problem;
+use shared::interval::IntervalSet;
 
 use std::collections::{HashMap, VecDeque};
 
@@ -187,34 +188,12 @@ fn compute_1(contents: &str) -> u64 {
 const MIN_VAL: u64 = 1;
 const MAX_VAL: u64 = 4000;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Range {
-    lo: u64,
-    hi: u64,
-}
-
-impl Range {
-    fn intersect(&self, other: &Self) -> Option<Self> {
-        let lo = std::cmp::max(self.lo, other.lo);
-        let hi = std::cmp::min(self.hi, other.hi);
-        if lo > hi {
-            None
-        } else {
-            Some(Range { lo, hi })
-        }
-    }
-
-    fn count(&self) -> u64 {
-        self.hi - self.lo + 1
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct PartsRange {
-    x: Range,
-    m: Range,
-    a: Range,
-    s: Range,
+    x: IntervalSet<u64>,
+    m: IntervalSet<u64>,
+    a: IntervalSet<u64>,
+    s: IntervalSet<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -225,74 +204,37 @@ struct RuleApplicationResult {
 
 impl PartsRange {
     fn default() -> Self {
+        let all = IntervalSet::from(MIN_VAL..MAX_VAL + 1);
         PartsRange {
-            x: Range {
-                lo: MIN_VAL,
-                hi: MAX_VAL,
-            },
-            m: Range {
-                lo: MIN_VAL,
-                hi: MAX_VAL,
-            },
-            a: Range {
-                lo: MIN_VAL,
-                hi: MAX_VAL,
-            },
-            s: Range {
-                lo: MIN_VAL,
-                hi: MAX_VAL,
-            },
+            x: all.clone(),
+            m: all.clone(),
+            a: all.clone(),
+            s: all,
         }
     }
 
     fn count(&self) -> u64 {
-        self.x.count() * self.m.count() * self.a.count() * self.s.count()
+        self.x.len() * self.m.len() * self.a.len() * self.s.len()
     }
 
     fn filter(&self, rule: Rule) -> (Option<Self>, Option<Self>) {
-        let (matching_intersecter, nonmatching_intersecter) = match rule.test {
-            TestFunction::Lesser => (
-                Range {
-                    lo: MIN_VAL,
-                    hi: rule.value - 1,
-                },
-                Range {
-                    lo: rule.value,
-                    hi: MAX_VAL,
-                },
-            ),
-            TestFunction::Greater => (
-                Range {
-                    lo: rule.value + 1,
-                    hi: MAX_VAL,
-                },
-                Range {
-                    lo: MIN_VAL,
-                    hi: rule.value,
-                },
-            ),
+        let matching_intersecter = match rule.test {
+            TestFunction::Lesser => IntervalSet::from(MIN_VAL..rule.value),
+            TestFunction::Greater => IntervalSet::from(rule.value + 1..MAX_VAL + 1),
         };
         macro_rules! process_category {
-            ($field:ident) => {
+            ($field:ident) => {{
+                let split = |values: IntervalSet<u64>| {
+                    (!values.is_empty()).then(|| PartsRange {
+                        $field: values,
+                        ..self.clone()
+                    })
+                };
                 (
-                    if let Some(intersection) = self.$field.intersect(&matching_intersecter) {
-                        Some(PartsRange {
-                            $field: intersection,
-                            ..*self
-                        })
-                    } else {
-                        None
-                    },
-                    if let Some(intersection) = self.$field.intersect(&nonmatching_intersecter) {
-                        Some(PartsRange {
-                            $field: intersection,
-                            ..*self
-                        })
-                    } else {
-                        None
-                    },
+                    split(self.$field.intersection(&matching_intersecter)),
+                    split(self.$field.difference(&matching_intersecter)),
                 )
-            };
+            }};
         }
         match rule.cat {
             Category::X => process_category!(x),
@@ -306,17 +248,17 @@ impl PartsRange {
         let mut accepted_ranges = vec![];
         let mut workflows_to_do = vec![];
 
-        let mut current_range = *self;
+        let mut current_range = self.clone();
 
         for workflow_step in workflow {
             match workflow_step.rule {
                 None => match &workflow_step.dest {
                     Destination::Accept => {
-                        accepted_ranges.push(current_range);
+                        accepted_ranges.push(current_range.clone());
                     }
                     Destination::Reject => {}
                     Destination::Other(name) => {
-                        workflows_to_do.push((name.clone(), current_range));
+                        workflows_to_do.push((name.clone(), current_range.clone()));
                     }
                 },
                 Some(rule) => {
@@ -381,3 +323,36 @@ impl Problem for Day {
         "123972546935551".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 19114);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 167409079868000);
+    }
+}
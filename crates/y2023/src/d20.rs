@@ -1,8 +1,11 @@
 use shared::Problem;
+use shared::math::crt_choices_at_least;
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::io::{BufRead, Write};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Pulse {
     Hi,
     Lo,
@@ -10,6 +13,22 @@ enum Pulse {
 
 type ModuleId = usize;
 
+/// The sender of the pulse that starts each button press.
+const BUTTON_ID: ModuleId = usize::MAX;
+
+/// How many presses to try before giving up on a module ever sending a high pulse.
+const MAX_PRESSES: u64 = 1 << 20;
+
+/// How many presses to simulate directly, before trying to split the network up to find when
+/// `rx` gets a low pulse.
+const DIRECT_PRESSES: u64 = 10_000;
+
+/// How many combinations of presses from each subnetwork's cycle to try with the CRT.
+const MAX_COMBINATIONS: usize = 100_000;
+
+/// (source_id, dest_id, pulse)
+type PulseEvent = (ModuleId, ModuleId, Pulse);
+
 trait ModuleInterface {
     fn process_pulse(&mut self, sender_id: ModuleId, pulse: Pulse) -> Option<Pulse>;
 
@@ -342,28 +361,449 @@ fn parse_input(contents: &str) -> (ModuleId, HashMap<ModuleId, Module>) {
     (broadcast_id, id_to_module)
 }
 
+fn find_module(modules: &HashMap<ModuleId, Module>, name: &str) -> Option<ModuleId> {
+    modules
+        .values()
+        .find(|module| module.name() == name)
+        .map(|module| module.id())
+}
+
+/// Press the button once, calling `on_pulse` for every pulse in the order they arrive
+/// (starting with the button's own pulse).
+fn push_button_with(
+    broadcast_id: ModuleId,
+    modules: &mut HashMap<ModuleId, Module>,
+    mut on_pulse: impl FnMut(ModuleId, ModuleId, Pulse),
+) {
+    let mut pulses: VecDeque<PulseEvent> = VecDeque::new();
+    pulses.push_back((BUTTON_ID, broadcast_id, Pulse::Lo));
+    while let Some((source_id, destination_id, pulse)) = pulses.pop_front() {
+        on_pulse(source_id, destination_id, pulse);
+        if let Some(module) = modules.get_mut(&destination_id)
+            && let Some(new_pulse) = module.process_pulse(source_id, pulse)
+        {
+            for new_destination_id in module.destinations().iter() {
+                pulses.push_back((destination_id, *new_destination_id, new_pulse))
+            }
+        }
+    }
+}
+
 fn push_button(broadcast_id: usize, modules: &mut HashMap<ModuleId, Module>) -> (u64, u64) {
     let mut lo_count = 0;
     let mut hi_count = 0;
-    // (source_id, dest_id, pulse)
-    let mut pulses: VecDeque<(ModuleId, ModuleId, Pulse)> = VecDeque::new();
-    pulses.push_back((usize::MAX, broadcast_id, Pulse::Lo));
-    lo_count += 1;
-    while !pulses.is_empty() {
-        let (source_id, destination_id, pulse) = pulses.pop_front().unwrap();
-        modules.entry(destination_id).and_modify(|module| {
-            if let Some(new_pulse) = module.process_pulse(source_id, pulse) {
-                for new_destination_id in module.destinations().iter() {
-                    match new_pulse {
-                        Pulse::Hi => hi_count += 1,
-                        Pulse::Lo => lo_count += 1,
-                    };
-                    pulses.push_back((destination_id, *new_destination_id, new_pulse))
-                }
+    push_button_with(broadcast_id, modules, |_, _, pulse| match pulse {
+        Pulse::Hi => hi_count += 1,
+        Pulse::Lo => lo_count += 1,
+    });
+    (lo_count, hi_count)
+}
+
+/// Every pulse sent during the next `presses` presses, one list per press.
+fn record_presses(
+    broadcast_id: ModuleId,
+    modules: &mut HashMap<ModuleId, Module>,
+    presses: u64,
+) -> Vec<Vec<PulseEvent>> {
+    (0..presses)
+        .map(|_| {
+            let mut events = vec![];
+            push_button_with(broadcast_id, modules, |source_id, destination_id, pulse| {
+                events.push((source_id, destination_id, pulse))
+            });
+            events
+        })
+        .collect()
+}
+
+/// The short identifier VCD files use for the `i`th signal.
+fn vcd_code(mut i: usize) -> String {
+    // Any printable ASCII character other than space can be used
+    let mut code = String::new();
+    loop {
+        code.push((b'!' + (i % 94) as u8) as char);
+        i /= 94;
+        if i == 0 {
+            return code;
+        }
+        i -= 1;
+    }
+}
+
+/// Remember `pulse` as the last one `source_id` sent, returning whether its level changed.
+/// Modules that haven't sent anything yet count as low, and the button isn't tracked.
+fn set_level(levels: &mut HashMap<ModuleId, Pulse>, source_id: ModuleId, pulse: Pulse) -> bool {
+    source_id != BUTTON_ID && levels.insert(source_id, pulse).unwrap_or(Pulse::Lo) != pulse
+}
+
+/// Write a recording from [`record_presses`] as a Value Change Dump, which waveform viewers
+/// like GTKWave can open. There's a signal for the button, which is high while a press is
+/// being handled, and one for each module showing the last pulse it sent, starting from
+/// `levels` (see [`set_level`]). Each pulse takes one nanosecond.
+/// https://en.wikipedia.org/wiki/Value_change_dump
+fn write_vcd(
+    modules: &HashMap<ModuleId, Module>,
+    levels: &HashMap<ModuleId, Pulse>,
+    recording: &[Vec<PulseEvent>],
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    // Sinks never send anything, so they're left out
+    let mut senders: Vec<&Module> = modules
+        .values()
+        .filter(|module| !matches!(module, Module::Sink(_)))
+        .collect();
+    senders.sort_by_key(|module| module.name());
+    let codes: HashMap<ModuleId, String> = senders
+        .iter()
+        .enumerate()
+        .map(|(i, module)| (module.id(), vcd_code(i + 1)))
+        .collect();
+    let button = vcd_code(0);
+
+    writeln!(output, "$timescale 1ns $end")?;
+    writeln!(output, "$scope module network $end")?;
+    writeln!(output, "$var wire 1 {button} button $end")?;
+    for module in &senders {
+        writeln!(
+            output,
+            "$var wire 1 {} {} $end",
+            codes[&module.id()],
+            module.name()
+        )?;
+    }
+    writeln!(output, "$upscope $end")?;
+    writeln!(output, "$enddefinitions $end")?;
+    writeln!(output, "#0")?;
+    writeln!(output, "$dumpvars")?;
+    writeln!(output, "0{button}")?;
+    for module in &senders {
+        let value = match levels.get(&module.id()) {
+            Some(Pulse::Hi) => 1,
+            Some(Pulse::Lo) | None => 0,
+        };
+        writeln!(output, "{value}{}", codes[&module.id()])?;
+    }
+    writeln!(output, "$end")?;
+
+    let mut levels = levels.clone();
+    let mut time = 0;
+    for events in recording {
+        time += 1;
+        writeln!(output, "#{time}")?;
+        writeln!(output, "1{button}")?;
+        for &(source_id, _, pulse) in events {
+            time += 1;
+            if !set_level(&mut levels, source_id, pulse) {
+                continue;
             }
+            let value = match pulse {
+                Pulse::Hi => 1,
+                Pulse::Lo => 0,
+            };
+            writeln!(output, "#{time}")?;
+            writeln!(output, "{value}{}", codes[&source_id])?;
+        }
+        time += 1;
+        writeln!(output, "#{time}")?;
+        writeln!(output, "0{button}")?;
+    }
+    Ok(())
+}
+
+/// How many more presses it takes for a pulse to match `is_match`, if it happens within
+/// `max_presses`.
+fn first_press_where(
+    broadcast_id: ModuleId,
+    modules: &mut HashMap<ModuleId, Module>,
+    max_presses: u64,
+    mut is_match: impl FnMut(ModuleId, ModuleId, Pulse) -> bool,
+) -> Option<u64> {
+    for press in 1..=max_presses {
+        let mut matched = false;
+        push_button_with(broadcast_id, modules, |source_id, destination_id, pulse| {
+            matched |= is_match(source_id, destination_id, pulse)
         });
+        if matched {
+            return Some(press);
+        }
     }
-    (lo_count, hi_count)
+    None
+}
+
+/// How many more presses it takes for `module_id` to send a high pulse, if it happens within
+/// `max_presses`. The pulses sent along the way are kept in `levels` (see [`set_level`]).
+fn first_high_press(
+    broadcast_id: ModuleId,
+    modules: &mut HashMap<ModuleId, Module>,
+    levels: &mut HashMap<ModuleId, Pulse>,
+    module_id: ModuleId,
+    max_presses: u64,
+) -> Option<u64> {
+    first_press_where(broadcast_id, modules, max_presses, |source_id, _, pulse| {
+        set_level(levels, source_id, pulse);
+        source_id == module_id && pulse == Pulse::Hi
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RxError {
+    NoRx,
+    /// `rx` has to be fed by a single conjunction, which sends it a low pulse once every
+    /// subnetwork feeding the conjunction is high at the same time.
+    NotFedByConjunction,
+    /// The subnetwork ending at the module depends on the conjunction in front of `rx`.
+    Feedback(String),
+    /// The subnetworks ending at the first two modules share the third.
+    Overlapping(String, String, String),
+    /// The subnetwork ending at the module didn't repeat within [`MAX_PRESSES`].
+    NoCycle(String),
+    /// There are more than [`MAX_COMBINATIONS`] ways to line the cycles up.
+    TooManyCombinations(usize),
+    Never,
+}
+
+impl fmt::Display for RxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RxError::NoRx => write!(f, "There's no rx module"),
+            RxError::NotFedByConjunction => {
+                write!(f, "rx isn't fed by a single conjunction")
+            }
+            RxError::Feedback(name) => write!(
+                f,
+                "The subnetwork ending at {name} depends on the conjunction in front of rx"
+            ),
+            RxError::Overlapping(a, b, shared) => write!(
+                f,
+                "The subnetworks ending at {a} and {b} both contain {shared}, so they aren't \
+                 independent"
+            ),
+            RxError::NoCycle(name) => write!(
+                f,
+                "The subnetwork ending at {name} doesn't repeat within {MAX_PRESSES} presses"
+            ),
+            RxError::TooManyCombinations(n) => write!(
+                f,
+                "There are {n} ways to line up the subnetworks' cycles, which is too many to try"
+            ),
+            RxError::Never => write!(f, "rx never gets a low pulse"),
+        }
+    }
+}
+
+/// How a subnetwork behaves: its state repeats every `length` presses once the first `warmup`
+/// presses are over. For each press up to the end of the first cycle, `levels` has the last
+/// pulse the subnetwork's output sent before the press, and `might_be_high` says whether the
+/// output is high at any point during the press. Storing every state would take too much
+/// memory, so they're found again by pressing the button from the `initial` modules.
+#[derive(Debug)]
+struct Cycle {
+    warmup: u64,
+    length: u64,
+    initial: HashMap<ModuleId, Module>,
+    levels: Vec<Pulse>,
+    might_be_high: Vec<bool>,
+}
+
+impl Cycle {
+    /// Where to find the given press in `levels` and `might_be_high`.
+    fn index(&self, press: u64) -> usize {
+        let i = press - 1;
+        if i < self.warmup + self.length {
+            i as usize
+        } else {
+            ((i - self.warmup) % self.length + self.warmup) as usize
+        }
+    }
+
+    /// The subnetwork as it is before the given press.
+    fn modules_before(&self, broadcast_id: ModuleId, press: u64) -> HashMap<ModuleId, Module> {
+        let mut modules = self.initial.clone();
+        for _ in 0..self.index(press) {
+            push_button_with(broadcast_id, &mut modules, |_, _, _| {});
+        }
+        modules
+    }
+}
+
+/// Everything that affects whether each module sends a high or low pulse next.
+fn network_state(modules: &HashMap<ModuleId, Module>) -> Vec<bool> {
+    let mut ids: Vec<&ModuleId> = modules.keys().collect();
+    ids.sort();
+    let mut state = vec![];
+    for id in ids {
+        match &modules[id] {
+            Module::FlipFlop(flip_flop) => state.push(flip_flop.is_on),
+            Module::Conjunction(conjunction) => {
+                let mut inputs: Vec<(&ModuleId, &Pulse)> =
+                    conjunction.connected_inputs.iter().collect();
+                inputs.sort_by_key(|(id, _)| **id);
+                state.extend(inputs.into_iter().map(|(_, pulse)| *pulse == Pulse::Hi));
+            }
+            Module::Broadcast(_) | Module::Sink(_) => {}
+        }
+    }
+    state
+}
+
+/// Press the button until the state of `modules` (and the last pulse `output_id` sent)
+/// repeats.
+fn find_cycle(
+    broadcast_id: ModuleId,
+    initial: HashMap<ModuleId, Module>,
+    output_id: ModuleId,
+) -> Option<Cycle> {
+    let mut modules = initial.clone();
+    let mut seen: HashMap<(Vec<bool>, Pulse), u64> = HashMap::new();
+    let mut levels = vec![];
+    let mut might_be_high = vec![];
+    let mut level = Pulse::Lo;
+    for presses in 0..=MAX_PRESSES {
+        let key = (network_state(&modules), level);
+        if let Some(&warmup) = seen.get(&key) {
+            return Some(Cycle {
+                warmup,
+                length: presses - warmup,
+                initial,
+                levels,
+                might_be_high,
+            });
+        }
+        seen.insert(key, presses);
+        levels.push(level);
+        let mut is_high = level == Pulse::Hi;
+        push_button_with(broadcast_id, &mut modules, |source_id, _, pulse| {
+            if source_id == output_id {
+                level = pulse;
+                is_high |= pulse == Pulse::Hi;
+            }
+        });
+        might_be_high.push(is_high);
+    }
+    None
+}
+
+/// The modules whose pulses can reach `module_id`, not counting the broadcaster.
+fn upstream(
+    module_id: ModuleId,
+    broadcast_id: ModuleId,
+    incoming: &HashMap<ModuleId, Vec<ModuleId>>,
+) -> BTreeSet<ModuleId> {
+    let mut found = BTreeSet::from([module_id]);
+    let mut to_visit = vec![module_id];
+    while let Some(id) = to_visit.pop() {
+        for &source_id in incoming.get(&id).into_iter().flatten() {
+            if source_id != broadcast_id && found.insert(source_id) {
+                to_visit.push(source_id);
+            }
+        }
+    }
+    found
+}
+
+/// Find the first press where `rx` gets a low pulse, by splitting the network into the
+/// independent subnetworks that feed the conjunction in front of `rx`. Each one is simulated
+/// on its own until it repeats, the CRT gives the presses where all of them might be high at
+/// once, and those are checked by putting the whole network together as it would be at that
+/// press and simulating it.
+fn rx_press_from_cycles(
+    broadcast_id: ModuleId,
+    modules: &HashMap<ModuleId, Module>,
+    rx_id: ModuleId,
+) -> Result<u64, RxError> {
+    let parents: Vec<&Module> = modules
+        .values()
+        .filter(|module| module.destinations().contains(&rx_id))
+        .collect();
+    let [Module::Conjunction(conjunction)] = parents.as_slice() else {
+        return Err(RxError::NotFedByConjunction);
+    };
+    let mut incoming: HashMap<ModuleId, Vec<ModuleId>> = HashMap::new();
+    for module in modules.values() {
+        for destination_id in module.destinations() {
+            incoming
+                .entry(*destination_id)
+                .or_default()
+                .push(module.id());
+        }
+    }
+
+    let mut inputs: Vec<ModuleId> = conjunction.connected_inputs.keys().copied().collect();
+    inputs.sort();
+    let mut owners: HashMap<ModuleId, ModuleId> = HashMap::new();
+    let mut cycles = vec![];
+    for &input_id in &inputs {
+        let name = |id: ModuleId| modules[&id].name().clone();
+        let subnetwork = upstream(input_id, broadcast_id, &incoming);
+        if subnetwork.contains(&conjunction.id) {
+            return Err(RxError::Feedback(name(input_id)));
+        }
+        for &id in &subnetwork {
+            if let Some(&owner_id) = owners.get(&id) {
+                return Err(RxError::Overlapping(
+                    name(owner_id),
+                    name(input_id),
+                    name(id),
+                ));
+            }
+            owners.insert(id, input_id);
+        }
+        let sub_modules: HashMap<ModuleId, Module> = modules
+            .iter()
+            .filter(|(id, _)| **id == broadcast_id || subnetwork.contains(id))
+            .map(|(id, module)| (*id, module.clone()))
+            .collect();
+        let cycle = find_cycle(broadcast_id, sub_modules, input_id)
+            .ok_or_else(|| RxError::NoCycle(name(input_id)))?;
+        cycles.push(cycle);
+    }
+
+    let rx_gets_low = |press: u64| {
+        let mut network: HashMap<ModuleId, Module> = HashMap::new();
+        network.insert(rx_id, modules[&rx_id].clone());
+        let mut last = (*conjunction).clone();
+        for (input_id, cycle) in inputs.iter().zip(&cycles) {
+            network.extend(cycle.modules_before(broadcast_id, press));
+            last.connected_inputs
+                .insert(*input_id, cycle.levels[cycle.index(press)]);
+        }
+        network.insert(last.id, Module::Conjunction(last));
+        first_press_where(broadcast_id, &mut network, 1, |_, destination_id, pulse| {
+            destination_id == rx_id && pulse == Pulse::Lo
+        })
+        .is_some()
+    };
+    let might_work =
+        |press: u64| cycles.iter().all(|c| c.might_be_high[c.index(press)]) && rx_gets_low(press);
+
+    // Before every subnetwork has settled into its cycle, check the presses directly
+    let max_warmup = cycles.iter().map(|c| c.warmup).max().unwrap_or(0);
+    if let Some(press) = (1..=max_warmup).find(|&press| might_work(press)) {
+        return Ok(press);
+    }
+
+    // After that, pick a press from each cycle where its output might be high, and solve the
+    // congruences with the CRT. The network is in the same state every time a solution
+    // repeats, so only the first one needs checking.
+    let congruence_choices: Vec<Vec<(i128, i128)>> = cycles
+        .iter()
+        .map(|c| {
+            (c.warmup..c.warmup + c.length)
+                .filter(|&i| c.might_be_high[i as usize])
+                .map(|i| (i as i128 + 1, c.length as i128))
+                .collect()
+        })
+        .collect();
+    crt_choices_at_least(
+        &congruence_choices,
+        max_warmup as i128 + 1,
+        MAX_COMBINATIONS,
+    )
+    .map_err(RxError::TooManyCombinations)?
+    .into_iter()
+    .map(|press| press as u64)
+    .find(|&press| rx_gets_low(press))
+    .ok_or(RxError::Never)
 }
 
 fn compute_1(contents: &str) -> u64 {
@@ -378,69 +818,137 @@ fn compute_1(contents: &str) -> u64 {
     lo_count * hi_count
 }
 
-fn push_button_2(
-    broadcast_id: ModuleId,
-    rx_parent_id: ModuleId,
-    modules: &mut HashMap<ModuleId, Module>,
-) -> Option<ModuleId> {
-    let mut rx_grandparent_sending_high_pulse = None;
-    // (source_id, dest_id, pulse)
-    let mut pulses: VecDeque<(ModuleId, ModuleId, Pulse)> = VecDeque::new();
-    pulses.push_back((usize::MAX, broadcast_id, Pulse::Lo));
-    while !pulses.is_empty() {
-        let (source_id, destination_id, pulse) = pulses.pop_front().unwrap();
-        modules.entry(destination_id).and_modify(|module| {
-            if let Some(new_pulse) = module.process_pulse(source_id, pulse) {
-                for new_destination_id in module.destinations().iter() {
-                    if (*new_destination_id == rx_parent_id) & (new_pulse == Pulse::Hi) {
-                        rx_grandparent_sending_high_pulse = Some(module.id());
-                    }
-                    pulses.push_back((destination_id, *new_destination_id, new_pulse))
-                }
-            }
-        });
+fn compute_2(contents: &str) -> Result<u64, RxError> {
+    let (broadcast_id, modules) = parse_input(contents);
+    let rx_id = find_module(&modules, "rx").ok_or(RxError::NoRx)?;
+    // Small networks can just be simulated
+    let direct = first_press_where(
+        broadcast_id,
+        &mut modules.clone(),
+        DIRECT_PRESSES,
+        |_, destination_id, pulse| destination_id == rx_id && pulse == Pulse::Lo,
+    );
+    match direct {
+        Some(press) => Ok(press),
+        None => rx_press_from_cycles(broadcast_id, &modules, rx_id),
     }
-    rx_grandparent_sending_high_pulse
 }
 
-fn compute_2(contents: &str) -> u64 {
-    let (broadcast_id, mut modules) = parse_input(contents);
-    let rx_id = *modules
-        .iter()
-        .filter(|(_, module)| module.name() == &"rx".to_string())
-        .map(|(id, _)| id)
-        .next()
-        .unwrap();
-    let rx_parent_id = *modules
-        .iter()
-        .filter(|(_, module)| module.destinations().contains(&rx_id))
-        .map(|(id, _)| id)
-        .next()
-        .unwrap();
-    let mut unhandled_rx_grandparents: HashSet<ModuleId> = HashSet::new();
-    let mut rx_grandparents_iters_to_high: Vec<u64> = Vec::new();
-    for module in modules.values() {
-        if module.destinations().contains(&rx_parent_id) {
-            unhandled_rx_grandparents.insert(module.id());
+const DEBUGGER_HELP: &str = "Commands:
+  p, press [n]           press the button n times (1 by default) and count the pulses
+  vcd <file> [n]         press the button n times (1 by default), saving the pulses as a VCD
+                         waveform
+  high <module> [limit]  press the button until the module sends a high pulse
+  reset                  put every module back as it started
+  h, help                show this message
+  q, quit                stop debugging
+";
+
+/// Press the button interactively, reading one command per line from `input`.
+fn debug(contents: &str, input: &mut dyn BufRead, output: &mut dyn Write) -> std::io::Result<()> {
+    let (broadcast_id, initial) = parse_input(contents);
+    let mut modules = initial.clone();
+    // The last pulse each module sent, so that recordings start from the right levels
+    let mut levels: HashMap<ModuleId, Pulse> = HashMap::new();
+    let mut presses: u64 = 0;
+    let mut line = String::new();
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
         }
-    }
-    let mut counter: u64 = 0;
-    while !unhandled_rx_grandparents.is_empty() {
-        counter += 1;
-        if let Some(rx_grandparent_sending_high_pulse) =
-            push_button_2(broadcast_id, rx_parent_id, &mut modules)
-        {
-            if unhandled_rx_grandparents.contains(&rx_grandparent_sending_high_pulse) {
-                rx_grandparents_iters_to_high.push(counter);
-                unhandled_rx_grandparents.remove(&rx_grandparent_sending_high_pulse);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let count = |word: Option<&&str>, default: u64| match word {
+            Some(word) => word.parse::<u64>().ok(),
+            None => Some(default),
+        };
+        match words.as_slice() {
+            [] => {}
+            ["p" | "press", rest @ ..] if rest.len() <= 1 => match count(rest.first(), 1) {
+                Some(n) => {
+                    let mut lo_count = 0;
+                    let mut hi_count = 0;
+                    for _ in 0..n {
+                        push_button_with(broadcast_id, &mut modules, |source_id, _, pulse| {
+                            set_level(&mut levels, source_id, pulse);
+                            match pulse {
+                                Pulse::Hi => hi_count += 1,
+                                Pulse::Lo => lo_count += 1,
+                            }
+                        });
+                    }
+                    presses += n;
+                    writeln!(
+                        output,
+                        "{presses} presses so far, the last {n} sent {lo_count} low and \
+                         {hi_count} high pulses"
+                    )?;
+                }
+                None => writeln!(output, "Can't parse {:?} as a number", rest[0])?,
+            },
+            ["vcd", path, rest @ ..] if rest.len() <= 1 => match count(rest.first(), 1) {
+                Some(n) => {
+                    let before = modules.clone();
+                    let recording = record_presses(broadcast_id, &mut modules, n);
+                    let mut vcd = vec![];
+                    write_vcd(&before, &levels, &recording, &mut vcd)?;
+                    match std::fs::write(path, vcd) {
+                        Ok(()) => {
+                            for &(source_id, _, pulse) in recording.iter().flatten() {
+                                set_level(&mut levels, source_id, pulse);
+                            }
+                            presses += n;
+                            writeln!(
+                                output,
+                                "Saved presses {}..={presses} to {path}",
+                                presses - n + 1
+                            )?;
+                        }
+                        Err(e) => {
+                            modules = before;
+                            writeln!(output, "Couldn't save {path}: {e}")?;
+                        }
+                    }
+                }
+                None => writeln!(output, "Can't parse {:?} as a number", rest[0])?,
+            },
+            ["high", name, rest @ ..] if rest.len() <= 1 => {
+                match (
+                    find_module(&modules, name),
+                    count(rest.first(), MAX_PRESSES),
+                ) {
+                    (None, _) => writeln!(output, "There's no module called {name:?}")?,
+                    (_, None) => writeln!(output, "Can't parse {:?} as a number", rest[0])?,
+                    (Some(id), Some(limit)) => {
+                        match first_high_press(broadcast_id, &mut modules, &mut levels, id, limit) {
+                            Some(n) => {
+                                presses += n;
+                                writeln!(output, "{name} sends a high pulse on press {presses}")?;
+                            }
+                            None => {
+                                presses += limit;
+                                writeln!(
+                                    output,
+                                    "{name} didn't send a high pulse by press {presses}"
+                                )?;
+                            }
+                        }
+                    }
+                }
+            }
+            ["reset"] => {
+                modules = initial.clone();
+                levels.clear();
+                presses = 0;
+                writeln!(output, "Put every module back as it started")?;
             }
+            ["h" | "help"] => write!(output, "{DEBUGGER_HELP}")?,
+            ["q" | "quit"] => return Ok(()),
+            _ => writeln!(output, "Unknown command {:?}, try \"help\"", line.trim())?,
         }
     }
-    rx_grandparents_iters_to_high
-        .iter()
-        .copied()
-        .reduce(|acc, e| acc * e)
-        .unwrap()
 }
 
 pub(crate) struct Day {}
@@ -456,9 +964,249 @@ impl Problem for Day {
         "670984704".to_string()
     }
     fn solve2(&self, contents: &str) -> String {
-        format!("{}", compute_2(contents))
+        match compute_2(contents) {
+            Ok(press) => format!("{press}"),
+            Err(e) => format!("{e}"),
+        }
     }
     fn expected2(&self) -> String {
         "262775362119547".to_string()
     }
+    fn debug(
+        &self,
+        contents: &str,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), String> {
+        debug(contents, input, output).map_err(|e| format!("Problem talking to the debugger: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_1: &str = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+
+    const EXAMPLE_2: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+    /// A 3-bit ripple counter, so c's first high pulse is on the 4th press.
+    const COUNTER: &str = "broadcaster -> a
+%a -> b
+%b -> c
+%c -> out";
+
+    #[test]
+    fn test_compute_1() {
+        assert_eq!(compute_1(EXAMPLE_1), 32000000);
+        assert_eq!(compute_1(EXAMPLE_2), 11687500);
+    }
+
+    /// Two counters, one with period 5 and one with period 7.
+    const COUNTERS: &str = "%li -> sw
+broadcaster -> rc, xq, ty
+%qc -> aq
+&vt -> rx
+&aq -> ty, qr
+%ty -> xl, aq
+%xq -> uq, sw
+%nn -> nf
+%uq -> li
+&en -> rc, nn, xg
+%rc -> nn, en
+%xl -> qc, aq
+&qr -> vt
+&sw -> xq, uq, em
+%nf -> en
+&xg -> vt
+&em -> vt";
+
+    fn rx_press(contents: &str) -> Result<u64, RxError> {
+        let (broadcast_id, modules) = parse_input(contents);
+        let rx_id = find_module(&modules, "rx").unwrap();
+        rx_press_from_cycles(broadcast_id, &modules, rx_id)
+    }
+
+    #[test]
+    fn test_compute_2() {
+        assert_eq!(compute_2(COUNTERS), Ok(35));
+        assert_eq!(rx_press(COUNTERS), Ok(35));
+        // c sends a high pulse on presses 4, 12, 20, ..., and i on every even press
+        let phases = "broadcaster -> a, x
+%a -> b
+%b -> c
+%c -> f
+%x -> i
+&i -> f
+&f -> rx";
+        assert_eq!(rx_press(phases), Ok(4));
+
+        assert_eq!(compute_2(EXAMPLE_2), Err(RxError::NoRx));
+        // c is high from press 4 to 7, and v from press 8 (after c goes low) to 15
+        let overlap = "broadcaster -> a, x
+%a -> b
+%b -> c
+%c -> f
+%x -> y
+%y -> w
+%w -> v
+%v -> f
+&f -> rx";
+        assert_eq!(compute_2(overlap), Ok(12));
+        assert_eq!(rx_press(overlap), Ok(12));
+        // n never gets a low pulse, so it never sends anything
+        let never = "broadcaster -> a, m
+%a -> f
+&m -> n
+%n -> f
+&f -> rx";
+        assert_eq!(compute_2(never), Err(RxError::Never));
+        assert_eq!(
+            rx_press("broadcaster -> a\n%a -> rx"),
+            Err(RxError::NotFedByConjunction)
+        );
+        assert_eq!(
+            rx_press("broadcaster -> a\n%a -> b, c\n&b -> f\n&c -> f\n&f -> rx"),
+            Err(RxError::Overlapping(
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ))
+        );
+        assert_eq!(
+            rx_press("broadcaster -> a\n%a -> b\n&b -> f\n&f -> rx, a"),
+            Err(RxError::Feedback("b".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_first_high_press() {
+        let (broadcast_id, modules) = parse_input(COUNTER);
+        for (name, expected) in [
+            ("a", Some(1)),
+            ("b", Some(2)),
+            ("c", Some(4)),
+            ("out", None),
+        ] {
+            let id = find_module(&modules, name).unwrap();
+            assert_eq!(
+                first_high_press(
+                    broadcast_id,
+                    &mut modules.clone(),
+                    &mut HashMap::new(),
+                    id,
+                    100
+                ),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_vcd() {
+        let (broadcast_id, mut modules) = parse_input(COUNTER);
+        let initial = modules.clone();
+        let recording = record_presses(broadcast_id, &mut modules, 2);
+        assert_eq!(recording[0].len(), 3);
+        let mut vcd = vec![];
+        write_vcd(&initial, &HashMap::new(), &recording, &mut vcd).unwrap();
+        let vcd = String::from_utf8(vcd).unwrap();
+        assert_eq!(
+            vcd,
+            "$timescale 1ns $end
+$scope module network $end
+$var wire 1 ! button $end
+$var wire 1 \" a $end
+$var wire 1 # b $end
+$var wire 1 $ broadcaster $end
+$var wire 1 % c $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+0!
+0\"
+0#
+0$
+0%
+$end
+#1
+1!
+#4
+1\"
+#5
+0!
+#6
+1!
+#9
+0\"
+#10
+1#
+#11
+0!
+"
+        );
+        assert_eq!(vcd_code(93), "~");
+        assert_eq!(vcd_code(94), "!!");
+        assert_eq!(vcd_code(95), "\"!");
+    }
+
+    #[test]
+    fn test_debug_vcd() {
+        // Named after the process so that test runs at the same time don't clash
+        let path = std::env::temp_dir().join(format!(
+            "y2023_d20_test_debug_vcd_{}.vcd",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        let commands = format!("press\nhigh b\nvcd {path}\n");
+        let mut output = vec![];
+        debug(COUNTER, &mut commands.as_bytes(), &mut output).unwrap();
+        let vcd = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(&format!("Saved presses 3..=3 to {path}")));
+        // a is low and b is high after the second press, and the third turns a back on
+        assert!(vcd.ends_with(
+            "$dumpvars
+0!
+0\"
+1#
+0$
+0%
+$end
+#1
+1!
+#4
+1\"
+#5
+0!
+"
+        ));
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut input = "press 3\nhigh c\nhigh out 10\nreset\nhigh c\nbogus\nq\n".as_bytes();
+        let mut output = vec![];
+        debug(COUNTER, &mut input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.split("\n> ").collect();
+        assert_eq!(
+            lines[0],
+            "> 3 presses so far, the last 3 sent 7 low and 3 high pulses"
+        );
+        assert_eq!(lines[1], "c sends a high pulse on press 4");
+        assert_eq!(lines[2], "out didn't send a high pulse by press 14");
+        assert_eq!(lines[4], "c sends a high pulse on press 4");
+        assert_eq!(lines[5], "Unknown command \"bogus\", try \"help\"");
+    }
 }
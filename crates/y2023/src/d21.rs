@@ -1,11 +1,15 @@
+use shared::Grid;
 use shared::Problem;
 
 use std::collections::{HashMap, HashSet};
 
-const MAP_DIMENSION: usize = 131;
 const NUM_STEPS_PART_1: usize = 64;
 const NUM_STEPS_PART_2: usize = 26501365;
-// const NUM_STEPS_PART_2: usize = 65 + 131 * 4;
+
+/// How far the BFS for [`count_reachable_tiled`] goes at most.
+const MAX_BFS_STEPS: usize = 4096;
+/// How many samples past the three used to fit the quadratic have to agree with it.
+const NUM_CHECKS: usize = 3;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Tile {
@@ -21,46 +25,45 @@ struct Location {
 
 type Connections = HashMap<Location, Vec<Location>>;
 
-fn parse_input(contents: &str) -> (Location, Connections) {
-    let mut map = [[Tile::Garden; MAP_DIMENSION]; MAP_DIMENSION];
+fn parse_map(contents: &str) -> (Location, Grid<Tile>) {
     let mut start = Location { x: 0, y: 0 };
-    for (x, line) in contents.trim().split('\n').enumerate() {
-        for (y, character) in line.chars().enumerate() {
-            match character {
-                '.' => {} // All tiles defaulted to garden anyway...
-                '#' => {
-                    map[x][y] = Tile::Rock;
-                }
-                'S' => {
-                    start = Location { x, y };
-                }
-                _ => panic!("Unexpected character!"),
-            }
-        }
-    }
-    let mut connections: HashMap<Location, Vec<Location>> = HashMap::new();
-    for x in 0..MAP_DIMENSION {
-        for y in 0..MAP_DIMENSION {
-            if map[x][y] == Tile::Rock {
-                continue;
-            }
-            let mut conns: Vec<Location> = Vec::with_capacity(4);
+    let map = Grid::new(
+        contents
+            .trim()
+            .split('\n')
+            .enumerate()
+            .map(|(x, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(y, character)| match character {
+                        '.' => Tile::Garden,
+                        '#' => Tile::Rock,
+                        'S' => {
+                            start = Location { x, y };
+                            Tile::Garden
+                        }
+                        _ => panic!("Unexpected character!"),
+                    })
+                    .collect()
+            })
+            .collect(),
+    );
+    (start, map)
+}
 
-            if (x > 0) && (map[x - 1][y] == Tile::Garden) {
-                conns.push(Location { x: x - 1, y });
-            }
-            if (x < MAP_DIMENSION - 1) && (map[x + 1][y] == Tile::Garden) {
-                conns.push(Location { x: x + 1, y });
-            }
-            if (y > 0) && (map[x][y - 1] == Tile::Garden) {
-                conns.push(Location { x, y: y - 1 });
-            }
-            if (y < MAP_DIMENSION - 1) && (map[x][y + 1] == Tile::Garden) {
-                conns.push(Location { x, y: y + 1 });
-            }
-            conns.shrink_to_fit();
-            connections.insert(Location { x, y }, conns);
+fn parse_input(contents: &str) -> (Location, Connections) {
+    let (start, map) = parse_map(contents);
+    let mut connections: HashMap<Location, Vec<Location>> = HashMap::new();
+    for ((x, y), tile) in map.indexed_iter() {
+        if *tile == Tile::Rock {
+            continue;
         }
+        let conns: Vec<Location> = map
+            .neighbors4(x, y)
+            .filter(|&(x, y)| map[(x, y)] == Tile::Garden)
+            .map(|(x, y)| Location { x, y })
+            .collect();
+        connections.insert(Location { x, y }, conns);
     }
     (start, connections)
 }
@@ -80,289 +83,124 @@ fn count_reachable(start: Location, connections: &Connections, num_steps: usize)
     currently_reachable.len()
 }
 
-// fn show_reachable(start: Location, connections: &Connections, num_steps: usize) -> () {
-//     let mut currently_reachable: HashSet<&Location> = HashSet::new();
-//     currently_reachable.insert(&start);
-//     for _ in 0..num_steps {
-//         let mut next_reachable = HashSet::new();
-//         for loc in currently_reachable.iter() {
-//             for connected_location in connections[loc].iter() {
-//                 next_reachable.insert(connected_location);
-//             }
-//         }
-//         currently_reachable = next_reachable;
-//     }
-//     let mut map = [[Tile::Garden; MAP_DIMENSION]; MAP_DIMENSION];
-//     for loc in currently_reachable {
-//         map[loc.x][loc.y] = Tile::Rock;
-//     }
-//     for line in map.iter() {
-//         for tile in line.iter() {
-//             match tile {
-//                 Tile::Garden => {
-//                     print!(" ");
-//                 }
-//                 Tile::Rock => {
-//                     print!("X");
-//                 }
-//             }
-//         }
-//         println!("");
-//     }
-// }
-
 fn compute_1(contents: &str) -> usize {
     let (start, connections) = parse_input(contents);
     count_reachable(start, &connections, NUM_STEPS_PART_1)
 }
 
-fn saturation_info(start: Location, connections: &Connections) -> (usize, usize, usize) {
-    let mut currently_reachable: HashSet<&Location> = HashSet::new();
-    currently_reachable.insert(&start);
-    let mut last_four_counts: [usize; 4] = [1, 0, 0, 0];
-    let mut i: usize = 0;
-
-    while (last_four_counts[0] != last_four_counts[2])
-        || (last_four_counts[1] != last_four_counts[3])
-    {
-        let mut next_reachable = HashSet::new();
-        for loc in currently_reachable.iter() {
-            for connected_location in connections[loc].iter() {
-                next_reachable.insert(connected_location);
+/// For every `n` up to `max_steps`, how many plots can be reached in exactly `n` steps on the
+/// map repeated infinitely in every direction.
+fn count_reachable_tiled_up_to(start: Location, map: &Grid<Tile>, max_steps: usize) -> Vec<usize> {
+    let (height, width) = (map.num_rows(), map.num_cols());
+    // Enough copies of the map around the start's that no walk can leave them
+    let (row_tiles, col_tiles) = (max_steps / height + 1, max_steps / width + 1);
+    let (rows, cols) = ((2 * row_tiles + 1) * height, (2 * col_tiles + 1) * width);
+    let origin = (row_tiles * height + start.x) * cols + col_tiles * width + start.y;
+    let mut visited = vec![false; rows * cols];
+    visited[origin] = true;
+    let mut frontier = vec![origin];
+    // A plot first reached after d steps can be reached after d + 2, d + 4, ... steps too
+    let mut counts = vec![1];
+    for steps in 1..=max_steps {
+        let mut next_frontier = vec![];
+        for &i in &frontier {
+            for j in [i - cols, i + cols, i - 1, i + 1] {
+                if !visited[j] && map[((j / cols) % height, (j % cols) % width)] == Tile::Garden {
+                    visited[j] = true;
+                    next_frontier.push(j);
+                }
             }
         }
-        currently_reachable = next_reachable;
-        i += 1;
-        last_four_counts[i % 4] = currently_reachable.len();
-        // println!("{}, {:?}", i, last_four_counts);
+        counts.push(next_frontier.len() + if steps >= 2 { counts[steps - 2] } else { 0 });
+        frontier = next_frontier;
     }
-    // steps to saturate, # filled after even steps, # filled after odd steps
-    (i - 2, last_four_counts[0], last_four_counts[1])
+    counts
 }
 
-fn compute_2(contents: &str) -> usize {
-    let (start, connections) = parse_input(contents);
-
-    // We're gonna do a lot of cheating here.
-    // The puzzle description leaves out several important assumptions, and worse,
-    // the test input does NOT satisfy all of these important assumptions.
-    // 1. There are direct, uninterrupted horizontal / vertical paths from
-    //    the start to each of the four edges.
-    // 2. There are no boulders along any of the four edges.
-    // 3. The garden is a square with an odd length side
-    // 4. You start in the center of the garden
-    let steps_center_to_adjacent_edge = MAP_DIMENSION / 2 + 1; // even
-    let steps_center_to_adjacent_corner_edge = MAP_DIMENSION + 1; // even
-    let steps_edge_to_adjacent_edge = MAP_DIMENSION; // odd
-
-    let upper_left = Location { x: 0, y: 0 };
-    let lower_left = Location {
-        x: 0,
-        y: MAP_DIMENSION - 1,
-    };
-    let upper_right = Location {
-        x: MAP_DIMENSION - 1,
-        y: 0,
-    };
-    let lower_right = Location {
-        x: MAP_DIMENSION - 1,
-        y: MAP_DIMENSION - 1,
-    };
-    let center_upper = Location {
-        x: MAP_DIMENSION / 2,
-        y: 0,
-    };
-    let center_left = Location {
-        x: 0,
-        y: MAP_DIMENSION / 2,
-    };
-    let center_lower = Location {
-        x: MAP_DIMENSION / 2,
-        y: MAP_DIMENSION - 1,
-    };
-    let center_right = Location {
-        x: MAP_DIMENSION - 1,
-        y: MAP_DIMENSION / 2,
-    };
-
-    let dist_to_garden_plot = |x: i64, y: i64| -> usize {
-        if (x == 0) && (y == 0) {
-            0
-        } else if (x == 0) || (y == 0) {
-            steps_center_to_adjacent_edge
-                + steps_edge_to_adjacent_edge * ((x.abs() + y.abs() - 1) as usize)
-        } else {
-            steps_center_to_adjacent_corner_edge
-                + steps_edge_to_adjacent_edge * ((x.abs() + y.abs() - 2) as usize)
-        }
-    };
+/// `value + slope * n + curvature * n * (n - 1) / 2` where `n = k - start`.
+#[derive(Debug, PartialEq, Eq)]
+struct Quadratic {
+    start: usize,
+    value: i128,
+    slope: i128,
+    curvature: i128,
+}
 
-    // Could compute this by hand, but why bother.
-    let mut x = 0;
-    while dist_to_garden_plot(x, 0) <= NUM_STEPS_PART_2 {
-        x += 1;
+impl Quadratic {
+    fn at(&self, k: usize) -> i128 {
+        let n = (k - self.start) as i128;
+        self.value + self.slope * n + self.curvature * n * (n - 1) / 2
     }
-    let x_hi = x;
-
-    // COMPUTE THE COUNTS FOR GARDENS IN INTERIOR QUADRANT:
-    //
-    // xxx   xxx   xxx | xxx   xxx
-    // xxx   xxx   xxx | xxx   xxx
-    // xxx   xxx   xxx | xxx   xxx
-    //                 |
-    // xxx   xxx   xxx | xxx   xxx
-    // xxx   xxx   xxx | xxx   xxx
-    // xxx   xxx   xxx | xxx   xxx
-    //                 +––––––––––
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xSx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    //
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    //
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-
-    // The edge (not fully saturated) garden plots that are further away
-    assert!(
-        dist_to_garden_plot(x_hi - 1, 1) + saturation_info(lower_left, &connections).0
-            > NUM_STEPS_PART_2
-    );
-    // The edge (not fully saturated) garden plots that are closer
-    assert!(
-        dist_to_garden_plot(x_hi - 2, 1) + saturation_info(lower_left, &connections).0
-            > NUM_STEPS_PART_2
-    );
-
-    let num_far_edge_gardens_in_quadrant = (x_hi - 1) as usize;
-    let num_close_edge_gardens_in_quadrant = (x_hi - 2) as usize;
-    // It's the same distance to every edge in the interior of the quadrant
-    let steps_to_reach_far_edge_garden = dist_to_garden_plot(x_hi - 1, 1);
-    assert!(steps_to_reach_far_edge_garden <= NUM_STEPS_PART_2);
-    let steps_to_reach_close_edge_garden = dist_to_garden_plot(x_hi - 2, 1);
-    assert!(steps_to_reach_close_edge_garden <= NUM_STEPS_PART_2);
+}
 
-    let far_edge_counts: usize = [lower_left, lower_right, upper_left, upper_right]
-        .map(|loc| {
-            num_far_edge_gardens_in_quadrant
-                * count_reachable(
-                    loc,
-                    &connections,
-                    NUM_STEPS_PART_2 - steps_to_reach_far_edge_garden,
-                )
-        })
-        .iter()
-        .sum();
-    let close_edge_counts: usize = [lower_left, lower_right, upper_left, upper_right]
-        .map(|loc| {
-            num_close_edge_gardens_in_quadrant
-                * count_reachable(
-                    loc,
-                    &connections,
-                    NUM_STEPS_PART_2 - steps_to_reach_close_edge_garden,
-                )
+/// Fit a quadratic to the earliest three samples that it matches, along with every sample
+/// after them. It has to start in the first half of the samples, and there have to be at
+/// least [`NUM_CHECKS`] samples after the three.
+fn fit_quadratic(samples: &[i128]) -> Option<Quadratic> {
+    let last_start = (samples.len() / 2).min(samples.len().checked_sub(3 + NUM_CHECKS)?);
+    (0..=last_start)
+        .map(|start| Quadratic {
+            start,
+            value: samples[start],
+            slope: samples[start + 1] - samples[start],
+            curvature: samples[start + 2] - 2 * samples[start + 1] + samples[start],
         })
-        .iter()
-        .sum();
-    // If we have an even # of steps left after reaching (1, 1), then we'll have
-    // an even number of steps left after reaching (2n+1, 2m+1) for every integer n, m.
-    // Vice versa as well.
-    let x_hi_minus_three = x_hi - 3;
-    let (num_even_parity_non_edges_in_quadrant, num_odd_parity_non_edges_in_quadrant) =
-        if (NUM_STEPS_PART_2 - dist_to_garden_plot(1, 1)) % 2 == 0 {
-            (
-                (x_hi_minus_three / 2).pow(2),
-                (x_hi_minus_three / 2) * (x_hi_minus_three / 2 + 1),
-            )
-        } else {
-            (
-                (x_hi_minus_three / 2) * (x_hi_minus_three / 2 + 1),
-                (x_hi_minus_three / 2).pow(2),
-            )
-        };
-    let non_edge_counts: usize = [lower_left, lower_right, upper_left, upper_right]
-        .map(|loc| {
-            let sat_info = saturation_info(loc, &connections);
-            (num_even_parity_non_edges_in_quadrant as usize) * sat_info.1
-                + (num_odd_parity_non_edges_in_quadrant as usize) * sat_info.2
-        })
-        .iter()
-        .sum();
-    let total_quadrant_reachables = far_edge_counts + close_edge_counts + non_edge_counts;
-
-    // COMPUTE THE COUNTS FOR GARDENS ON DIRECT LINES:
-    //
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    //
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    //                 +––––––––––
-    // xxx   xxx   xxx | xxx   xxx
-    // xxx   xxx   xSx | xxx   xxx
-    // xxx   xxx   xxx | xxx   xxx
-    //                 +––––––––––
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    //
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-    // xxx   xxx   xxx   xxx   xxx
-
-    assert!(dist_to_garden_plot(x_hi - 1, 0) < NUM_STEPS_PART_2);
-    assert!(dist_to_garden_plot(x_hi, 0) > NUM_STEPS_PART_2);
-    assert!(
-        dist_to_garden_plot(x_hi - 1, 0) + saturation_info(center_left, &connections).0
-            > NUM_STEPS_PART_2
-    );
-    assert!(
-        dist_to_garden_plot(x_hi - 2, 0) + saturation_info(center_left, &connections).0
-            <= NUM_STEPS_PART_2
-    );
+        .find(|quadratic| (quadratic.start..samples.len()).all(|k| quadratic.at(k) == samples[k]))
+}
 
-    let straight_away_edge_counts: usize = [center_left, center_right, center_lower, center_upper]
-        .map(|loc| {
-            count_reachable(
-                loc,
-                &connections,
-                NUM_STEPS_PART_2 - dist_to_garden_plot(x_hi - 1, 0),
-            )
-        })
+/// Fit a quadratic to the counts after `remainder + k * period` steps, using the first half of
+/// `counts` and checking it against the rest.
+fn fit_counts(counts: &[usize], remainder: usize, period: usize) -> Option<Quadratic> {
+    let samples: Vec<i128> = counts[remainder..]
         .iter()
-        .sum();
-    // S E O E O E O T H
-    // 0 1 2 3 4 5 6 7 8
-    let (num_even_parity_straight_away_interiors, num_odd_parity_straight_away_interiors) =
-        if (NUM_STEPS_PART_2 - dist_to_garden_plot(1, 0)) % 2 == 0 {
-            (((x_hi - 1) / 2) as usize, ((x_hi - 2) / 2) as usize)
-        } else {
-            (((x_hi - 2) / 2) as usize, ((x_hi - 1) / 2) as usize)
-        };
-    let straight_away_interior_counts: usize =
-        [center_left, center_right, center_lower, center_upper]
-            .map(|loc| {
-                let sat_info = saturation_info(loc, &connections);
-                num_even_parity_straight_away_interiors * sat_info.1
-                    + num_odd_parity_straight_away_interiors * sat_info.2
-            })
-            .iter()
-            .sum();
-
-    let total_straight_away_reachables = straight_away_edge_counts + straight_away_interior_counts;
+        .step_by(period)
+        .map(|&count| count as i128)
+        .collect();
+    let num_fitted = ((counts.len() - 1) / 2).checked_sub(remainder)? / period + 1;
+    fit_quadratic(&samples[..num_fitted])
+        .filter(|quadratic| (quadratic.start..samples.len()).all(|k| quadratic.at(k) == samples[k]))
+}
 
-    let center_garden_reachables = if (NUM_STEPS_PART_2 % 2) == 0 {
-        saturation_info(start, &connections).1
-    } else {
-        saturation_info(start, &connections).2
-    };
+/// How many plots can be reached in exactly `num_steps` steps on the map repeated infinitely
+/// in every direction.
+///
+/// Far enough out, each repeat of the map is reached the same way as the one before it in the
+/// same direction, just later. So for some period (the size of the map, for the puzzle
+/// input) the counts after `remainder + k * period` steps eventually grow quadratically in
+/// `k`. This uses the shortest period where that's true of the counts from a BFS for every
+/// remainder, since with only one remainder a quadratic can fit by coincidence. Small step
+/// counts are simply simulated.
+fn count_reachable_tiled(
+    start: Location,
+    map: &Grid<Tile>,
+    num_steps: usize,
+) -> Result<usize, String> {
+    let mut max_steps = (16 * map.num_rows().max(map.num_cols())).min(MAX_BFS_STEPS);
+    loop {
+        if num_steps <= max_steps {
+            return Ok(count_reachable_tiled_up_to(start, map, num_steps)[num_steps]);
+        }
+        let counts = count_reachable_tiled_up_to(start, map, max_steps);
+        for period in 1..=max_steps / 2 {
+            if let Some(quadratic) = fit_counts(&counts, num_steps % period, period)
+                && (0..period).all(|remainder| fit_counts(&counts, remainder, period).is_some())
+            {
+                return Ok(quadratic.at(num_steps / period) as usize);
+            }
+        }
+        if max_steps == MAX_BFS_STEPS {
+            return Err(format!(
+                "The number of reachable plots doesn't settle into quadratic growth within \
+                 {MAX_BFS_STEPS} steps"
+            ));
+        }
+        max_steps = (2 * max_steps).min(MAX_BFS_STEPS);
+    }
+}
 
-    total_quadrant_reachables + total_straight_away_reachables + center_garden_reachables
+fn compute_2(contents: &str) -> Result<usize, String> {
+    let (start, map) = parse_map(contents);
+    count_reachable_tiled(start, &map, NUM_STEPS_PART_2)
 }
 
 pub(crate) struct Day {}
@@ -378,9 +216,80 @@ impl Problem for Day {
         "3773".to_string()
     }
     fn solve2(&self, contents: &str) -> String {
-        format!("{}", compute_2(contents))
+        match compute_2(contents) {
+            Ok(count) => format!("{count}"),
+            Err(e) => e,
+        }
     }
     fn expected2(&self) -> String {
         "625628021226274".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    #[test]
+    fn test_count_reachable() {
+        let (start, connections) = parse_input(EXAMPLE);
+        assert_eq!(count_reachable(start, &connections, 6), 16);
+    }
+
+    #[test]
+    fn test_count_reachable_tiled() {
+        let (start, map) = parse_map(EXAMPLE);
+        for (num_steps, expected) in [
+            (6, 16),
+            (10, 50),
+            (50, 1594),
+            (100, 6536),
+            (500, 167004),
+            (1000, 668697),
+            (5000, 16733044),
+        ] {
+            assert_eq!(count_reachable_tiled(start, &map, num_steps), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_count_reachable_tiled_thin_map() {
+        let (start, map) = parse_map("......\n..#S..\n.#....");
+        for num_steps in [7, 100, 1000] {
+            assert_eq!(
+                count_reachable_tiled(start, &map, num_steps),
+                Ok(count_reachable_tiled_up_to(start, &map, num_steps)[num_steps])
+            );
+        }
+        // Without rocks, everything the right distance away is reachable
+        let (start, map) = parse_map("...S...");
+        assert_eq!(
+            count_reachable_tiled(start, &map, 100_000),
+            Ok(100_001 * 100_001)
+        );
+    }
+
+    #[test]
+    fn test_fit_quadratic() {
+        // The first sample isn't part of the quadratic
+        let samples = [5, 2, 6, 14, 26, 42, 62];
+        let quadratic = fit_quadratic(&samples).unwrap();
+        assert_eq!(quadratic.start, 1);
+        assert_eq!(quadratic.at(10), 2 * 81 + 2 * 9 + 2);
+        // Not enough samples to check it
+        assert_eq!(fit_quadratic(&samples[..5]), None);
+        assert_eq!(fit_quadratic(&[1, 2, 4, 8, 16, 32, 64]), None);
+    }
+}
@@ -1,4 +1,7 @@
 use shared::Problem;
+use shared::graph::toposort::toposort;
+
+use std::collections::HashSet;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 struct Coord {
@@ -59,171 +62,84 @@ impl Brick {
     }
 }
 
-mod private {
-    use super::{Brick, Range};
-    use std::cell::RefCell;
-    use std::collections::HashSet;
-    use std::marker::PhantomData;
-    use std::rc::Rc;
-
-    type RcBrick = Rc<RefCell<Brick>>;
+/// The bricks once they've all fallen, listed so that every brick comes after the bricks
+/// below it.
+struct Settled {
+    bricks: Vec<Brick>,
+    /// The bricks (by index) that each brick is resting directly on.
+    supporters: Vec<Vec<usize>>,
+}
 
-    #[derive(Debug)]
-    struct Node {
-        brick: RcBrick,
-        // Keep track of the edges in both directions
-        bricks_below: Vec<RcBrick>,
-        bricks_above: Vec<RcBrick>,
-        support_count: usize,
+fn settle(bricks: Vec<Brick>) -> Settled {
+    // A brick can only land on the bricks below it that it overlaps, so they have to fall first.
+    let ids: Vec<usize> = (0..bricks.len()).collect();
+    let below = ids.iter().flat_map(|&a| {
+        let bricks = &bricks;
+        ids.iter()
+            .filter(move |&&b| {
+                bricks[a].overlaps(&bricks[b]) && bricks[a].z_lo() < bricks[b].z_lo()
+            })
+            .map(move |&b| (a, b))
+    });
+    let order = toposort(&ids, below).expect("Bricks shouldn't intersect");
+
+    let mut settled: Vec<Brick> = Vec::with_capacity(bricks.len());
+    let mut supporters = Vec::with_capacity(bricks.len());
+    for i in order {
+        let mut brick = bricks[i];
+        let new_starting_height = settled
+            .iter()
+            .filter(|other| other.overlaps(&brick))
+            .map(|other| other.z_hi() + 1)
+            .max()
+            .unwrap_or(1);
+        brick.height_range.lo = new_starting_height;
+        supporters.push(
+            settled
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| other.overlaps(&brick) && other.z_hi() + 1 == brick.z_lo())
+                .map(|(j, _)| j)
+                .collect(),
+        );
+        settled.push(brick);
     }
-
-    type RcNode = Rc<RefCell<Node>>;
-
-    #[derive(Debug)]
-    pub struct Bricks {
-        bricks: Vec<RcNode>,
-        _phantom: PhantomData<()>, // zero-runtime-cost private member to prevent bare instantiation
+    Settled {
+        bricks: settled,
+        supporters,
     }
+}
 
-    pub fn new_bricks_graph(mut bricks: Vec<Brick>) -> Bricks {
-        // Sort by z so bricks that are lower are come earlier
-        bricks.sort_by_key(|a| a.z_lo());
-        let bricks: Vec<RcBrick> = bricks
-            .into_iter()
-            .map(|b| Rc::new(RefCell::new(b)))
-            .collect();
-        // When processing gravity, we care about the bricks below it.
-        // But in other cases, we care about the bricks above it. Track both.
-        let mut downward_overlaps: Vec<Vec<RcBrick>> = Vec::new();
-        let mut upward_overlaps: Vec<Vec<RcBrick>> = Vec::new();
-        for brick in &bricks {
-            let mut downward_bricks: Vec<RcBrick> = Vec::new();
-            let mut upward_bricks: Vec<RcBrick> = Vec::new();
-            for brick2 in &bricks {
-                if brick.borrow().overlaps(&brick2.borrow()) {
-                    if brick2.borrow().z_lo() < brick.borrow().z_lo() {
-                        downward_bricks.push(Rc::clone(brick2));
-                    } else {
-                        upward_bricks.push(Rc::clone(brick2));
-                    }
-                }
-            }
-            downward_overlaps.push(downward_bricks);
-            upward_overlaps.push(upward_bricks);
-        }
-
-        // Process gravity
-        for (brick, downward_overlaps) in bricks.iter().zip(downward_overlaps.iter()) {
-            let mut new_starting_height = 1;
-            for overlapper in downward_overlaps {
-                new_starting_height = std::cmp::max(
-                    new_starting_height,
-                    overlapper.borrow().height_range.hi() + 1,
-                );
-            }
-            let range = brick.borrow().height_range.range;
-            brick.borrow_mut().height_range = Range {
-                lo: new_starting_height,
-                range,
-            }
-        }
-
-        // Find edges (i.e. supporting bricks)
-        // Each brick will point to the bricks that it supports
-        let nodes: Vec<RcNode> = bricks
-            .into_iter()
-            .zip(downward_overlaps.iter())
-            .zip(upward_overlaps.iter())
-            .map(|((brick, downward_overlaps), upward_overlaps)| {
-                let brick_height_bottom = brick.borrow().z_lo();
-                let bricks_below: Vec<Rc<RefCell<Brick>>> = downward_overlaps
-                    .iter()
-                    .filter(|overlapper| overlapper.borrow().z_hi() + 1 == brick_height_bottom)
-                    .map(Rc::clone)
-                    .collect();
-                let brick_height_top = brick.borrow().z_hi();
-                let bricks_above: Vec<Rc<RefCell<Brick>>> = upward_overlaps
-                    .iter()
-                    .filter(|overlapper| overlapper.borrow().z_lo() == brick_height_top + 1)
-                    .map(Rc::clone)
-                    .collect();
-                Rc::new(RefCell::new(Node {
-                    support_count: bricks_below.len(),
-                    brick,
-                    bricks_below,
-                    bricks_above,
-                }))
-            })
+impl Settled {
+    fn count_disintegrable(&self) -> usize {
+        let load_bearing_bricks: HashSet<usize> = self
+            .supporters
+            .iter()
+            .filter(|supporters| supporters.len() == 1)
+            .map(|supporters| supporters[0])
             .collect();
-
-        Bricks {
-            bricks: nodes,
-            _phantom: PhantomData,
-        }
-    }
-
-    impl Bricks {
-        pub fn count_disintegrable(&self) -> usize {
-            let mut load_bearing_bricks: HashSet<Brick> = HashSet::new();
-            for node in &self.bricks {
-                if node.borrow().bricks_below.len() == 1 {
-                    load_bearing_bricks.insert(*node.borrow().bricks_below[0].borrow());
+        self.bricks.len() - load_bearing_bricks.len()
+    }
+
+    fn count_total_falls(&self) -> usize {
+        let mut falls = 0;
+        for i in 0..self.bricks.len() {
+            // Everything a brick rests on comes before it, so one pass upwards is enough to
+            // see the whole chain reaction.
+            let mut fallen = vec![false; self.bricks.len()];
+            fallen[i] = true;
+            for j in i + 1..self.bricks.len() {
+                let supporters = &self.supporters[j];
+                if !supporters.is_empty() && supporters.iter().all(|&k| fallen[k]) {
+                    fallen[j] = true;
+                    falls += 1;
                 }
             }
-            self.bricks.len() - load_bearing_bricks.len()
-        }
-
-        fn reset_support_counts(&mut self) {
-            for node in &mut self.bricks {
-                let mut node = node.borrow_mut();
-                node.support_count = node.bricks_below.len();
-            }
-        }
-
-        fn node_at_brick(&self, brick: &RcBrick) -> Option<&RcNode> {
-            self.bricks
-                .iter()
-                .find(|&node| node.borrow().brick == *brick)
-        }
-
-        pub fn count_total_falls(&mut self) -> usize {
-            let mut falls: usize = 0;
-            for i in 0..self.bricks.len() {
-                // Traverse the graph breadth first. That's important because the graph
-                // nodes are stored from bottom to top, and we want to process them
-                // in that order.
-                let mut nodes_to_process: Vec<&RcNode> = vec![&self.bricks[i]];
-                while let Some(node) = nodes_to_process.pop() {
-                    for supported_brick in &node.borrow().bricks_above {
-                        let mut supported_node =
-                            self.node_at_brick(supported_brick).unwrap().borrow_mut();
-                        supported_node.support_count =
-                            supported_node.support_count.checked_sub(1).unwrap();
-                        if supported_node.support_count == 0 {
-                            std::mem::drop(supported_node);
-                            nodes_to_process.push(self.node_at_brick(supported_brick).unwrap());
-                        }
-                    }
-                }
-                let x = self
-                    .bricks
-                    .iter()
-                    .filter(|node| {
-                        (node.borrow().support_count == 0)
-                            && (node.borrow().brick.borrow().z_lo() > 1)
-                    })
-                    .count();
-                falls += x;
-
-                self.reset_support_counts();
-            }
-            falls
         }
+        falls
     }
 }
 
-use private::{Bricks, new_bricks_graph};
-
 fn parse_brick_str(brick_str: &str) -> Brick {
     let coords: Vec<Vec<u64>> = brick_str
         .split('~')
@@ -255,8 +171,8 @@ fn parse_brick_str(brick_str: &str) -> Brick {
     }
 }
 
-fn parse_input(contents: &str) -> Bricks {
-    new_bricks_graph(contents.trim().split('\n').map(parse_brick_str).collect())
+fn parse_input(contents: &str) -> Settled {
+    settle(contents.trim().split('\n').map(parse_brick_str).collect())
 }
 
 fn compute_1(contents: &str) -> usize {
@@ -265,7 +181,7 @@ fn compute_1(contents: &str) -> usize {
 }
 
 fn compute_2(contents: &str) -> usize {
-    let mut bricks = parse_input(contents);
+    let bricks = parse_input(contents);
     bricks.count_total_falls()
 }
 
@@ -288,3 +204,26 @@ impl Problem for Day {
         "41610".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 5);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 7);
+    }
+}
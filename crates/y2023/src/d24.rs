@@ -1,143 +1,33 @@
 use shared::Problem;
-
-use std::ops::{Add, Sub};
+use shared::geom::{Vec2, Vec3};
+use shared::num::BigInt;
 
 const TEST_AREA: [f64; 2] = [200000000000000.0, 400000000000000.0];
 
-// #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct Coord {
-    x: i128,
-    y: i128,
-    z: i128,
-}
-
-impl Coord {
-    fn cross_2d(&self, other: &Self) -> i128 {
-        // self.x * other.y - self.y * other.x
-        self.x
-            .checked_mul(other.y)
-            .unwrap()
-            .checked_sub(self.y.checked_mul(other.x).unwrap())
-            .unwrap()
-    }
-
-    fn cross(&self, other: &Self) -> Coord {
-        Coord {
-            x: self
-                .y
-                .checked_mul(other.z)
-                .unwrap()
-                .checked_sub(self.z.checked_mul(other.y).unwrap())
-                .unwrap(),
-            y: self
-                .z
-                .checked_mul(other.x)
-                .unwrap()
-                .checked_sub(self.x.checked_mul(other.z).unwrap())
-                .unwrap(),
-            z: self
-                .x
-                .checked_mul(other.y)
-                .unwrap()
-                .checked_sub(self.y.checked_mul(other.x).unwrap())
-                .unwrap(),
-        }
-    }
-
-    fn dot(&self, other: &Self) -> i128 {
-        let x = self.x.checked_mul(other.x).unwrap();
-        let y = self.y.checked_mul(other.y).unwrap();
-        let z = self.z.checked_mul(other.z).unwrap();
-        x.checked_add(y).unwrap().checked_add(z).unwrap()
-    }
-
-    fn scalar_mul(&self, other: i128) -> Coord {
-        Coord {
-            x: self.x * other,
-            y: self.y * other,
-            z: self.z * other,
-        }
-    }
+type Coord = Vec3<i128>;
 
-    fn scalar_div(&self, other: i128) -> Coord {
-        Coord {
-            x: self.x / other,
-            y: self.y / other,
-            z: self.z / other,
-        }
-    }
-}
-
-impl Sub for &Coord {
-    type Output = Coord;
-
-    fn sub(self, other: Self) -> Self::Output {
-        Coord {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-        }
-    }
-}
-
-impl Add for &Coord {
-    type Output = Coord;
-
-    fn add(self, other: Self) -> Self::Output {
-        Coord {
-            x: self.x + other.x,
-            y: self.y + other.y,
-            z: self.z + other.z,
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq)]
-struct ParseCoordError;
-
-impl std::str::FromStr for Coord {
-    type Err = ParseCoordError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y_z) = s.split_once(", ").ok_or(ParseCoordError)?;
-        let (y, z) = y_z.split_once(", ").ok_or(ParseCoordError)?;
-
-        let x_fromstr = x.trim().parse::<i128>().map_err(|_| ParseCoordError)?;
-        let y_fromstr = y.trim().parse::<i128>().map_err(|_| ParseCoordError)?;
-        let z_fromstr = z.trim().parse::<i128>().map_err(|_| ParseCoordError)?;
-
-        Ok(Coord {
-            x: x_fromstr,
-            y: y_fromstr,
-            z: z_fromstr,
-        })
-    }
-}
-
-// #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 struct Hail {
     pos: Coord,
     vel: Coord,
 }
 
-impl Sub for &Hail {
-    type Output = Hail;
+fn cross_2d(a: &Coord, b: &Coord) -> i128 {
+    Vec2::new(a.x, a.y).cross(&Vec2::new(b.x, b.y))
+}
 
-    fn sub(self, other: Self) -> Self::Output {
-        Hail {
-            pos: &self.pos - &other.pos,
-            vel: &self.vel - &other.vel,
-        }
-    }
+/// `a / b`, panicking if the division isn't exact.
+fn exact_div(a: &BigInt, b: &BigInt) -> BigInt {
+    let (q, r) = a.div_rem(b).expect("Division by zero");
+    assert!(r.is_zero(), "{a} is not divisible by {b}");
+    q
 }
 
 fn intersection_point_2d(h1: &Hail, h2: &Hail) -> Option<(f64, f64)> {
     // I'm using the method described here:
     // https://stackoverflow.com/questions/563198
-    let r_x_s = h1.vel.cross_2d(&h2.vel);
-    let q_minus_p_x_r = (&h2.pos - &h1.pos).cross_2d(&h1.vel);
+    let r_x_s = cross_2d(&h1.vel, &h2.vel);
+    let q_minus_p_x_r = cross_2d(&(h2.pos - h1.pos), &h1.vel);
     if r_x_s == 0 {
         if q_minus_p_x_r == 0 {
             if (h1.vel.x > 0) == (h2.vel.x > 0) {
@@ -149,7 +39,7 @@ fn intersection_point_2d(h1: &Hail, h2: &Hail) -> Option<(f64, f64)> {
             None
         }
     } else {
-        let q_minus_p_x_s = (&h2.pos - &h1.pos).cross_2d(&h2.vel);
+        let q_minus_p_x_s = cross_2d(&(h2.pos - h1.pos), &h2.vel);
         let t = (q_minus_p_x_s as f64) / (r_x_s as f64);
         let u = (q_minus_p_x_r as f64) / (r_x_s as f64);
         if (t < 0.0) || (u < 0.0) {
@@ -231,24 +121,34 @@ fn compute_1(contents: &str) -> u64 {
 //
 // n3 = (-(p2 x p3) • v2) / ((p2 x v3) • v2)
 // n2 = (-(p2 x p3) • v3) / ((v2 x p3) • v3)
-fn compute_2(contents: &str) -> i128 {
-    let hail_stones = parse_input(contents);
+fn compute_2(contents: &str) -> BigInt {
+    let hail_stones: Vec<(Vec3<BigInt>, Vec3<BigInt>)> = parse_input(contents)
+        .into_iter()
+        .map(|hail| (hail.pos.map(BigInt::from), hail.vel.map(BigInt::from)))
+        .collect();
     assert!(hail_stones.len() >= 3);
-    let hail2 = &hail_stones[1] - &hail_stones[0];
-    let hail3 = &hail_stones[2] - &hail_stones[0];
-
-    // We're doing integer division here, so we're assuming that velocities/positions
-    // of intersections are all integers as well. The description text kind
-    // of implies that, though...
-    let n3 = -(hail2.pos.cross(&hail3.pos).dot(&hail2.vel))
-        / (hail2.pos.cross(&hail3.vel).dot(&hail2.vel));
-    let n2 = -(hail2.pos.cross(&hail3.pos).dot(&hail3.vel))
-        / (hail2.vel.cross(&hail3.pos).dot(&hail3.vel));
-
-    let collision_point_2 = &hail_stones[1].pos + &(hail_stones[1].vel.scalar_mul(n2));
-    let collision_point_3 = &hail_stones[2].pos + &(hail_stones[2].vel.scalar_mul(n3));
-    let rock_velocity = (&collision_point_3 - &collision_point_2).scalar_div(n3 - n2);
-    let rock_position = &collision_point_2 - &(rock_velocity.scalar_mul(n2));
+    let (p1, v1) = &hail_stones[0];
+    let (p2, v2) = &hail_stones[1];
+    let (p3, v3) = &hail_stones[2];
+    let (rel_p2, rel_v2) = (p2.clone() - p1.clone(), v2.clone() - v1.clone());
+    let (rel_p3, rel_v3) = (p3.clone() - p1.clone(), v3.clone() - v1.clone());
+
+    // The collision times and the rock's velocity must be integers for the rock's starting
+    // position to be one, so check that every division is exact rather than assuming it.
+    let p2_x_p3 = rel_p2.cross(&rel_p3);
+    let n3 = exact_div(&-p2_x_p3.dot(&rel_v2), &rel_p2.cross(&rel_v3).dot(&rel_v2));
+    let n2 = exact_div(&-p2_x_p3.dot(&rel_v3), &rel_v2.cross(&rel_p3).dot(&rel_v3));
+
+    let collision_point_2 = p2.clone() + v2.clone() * n2.clone();
+    let collision_point_3 = p3.clone() + v3.clone() * n3.clone();
+    let time_between = &n3 - &n2;
+    let displacement = collision_point_3 - collision_point_2.clone();
+    let rock_velocity = Vec3::new(
+        exact_div(&displacement.x, &time_between),
+        exact_div(&displacement.y, &time_between),
+        exact_div(&displacement.z, &time_between),
+    );
+    let rock_position = collision_point_2 - rock_velocity * n2;
 
     rock_position.x + rock_position.y + rock_position.z
 }
@@ -272,3 +172,19 @@ impl Problem for Day {
         "580043851566574".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE).to_string(), "47");
+    }
+}
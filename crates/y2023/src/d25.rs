@@ -1,141 +1,56 @@
-use shared::Problem;
-
-use std::collections::HashMap;
+use std::io::{BufRead, Write};
 
-type Node = usize;
-type AdjMat = Vec<Vec<u64>>;
+use shared::Problem;
+use shared::graph::{NodeIds, min_cut::MinCut, min_cut::min_cut};
 
-fn parse_input(contents: &str) -> AdjMat {
-    let mut curr_id: Node = 0;
-    let mut name_to_id: HashMap<String, Node> = HashMap::new();
-    // First, find all nodes
-    for line in contents.trim().split('\n') {
-        let (node1_name, neighbor_names) = line.split_once(": ").unwrap();
-        name_to_id.entry(node1_name.to_string()).or_insert_with(|| {
-            // println!("{curr_id}: {node1_name}");
-            curr_id += 1;
-            curr_id - 1
-        });
-        for neighbor_name in neighbor_names.split(' ') {
-            name_to_id
-                .entry(neighbor_name.to_string())
-                .or_insert_with(|| {
-                    // println!("{curr_id}: {neighbor_name}");
-                    curr_id += 1;
-                    curr_id - 1
-                });
-        }
-    }
-    // Then, build the graph
-    let mut adj_mat = vec![vec![0; name_to_id.len()]; name_to_id.len()];
+fn parse_input(contents: &str) -> (NodeIds<String>, Vec<(usize, usize, u64)>) {
+    let mut ids = NodeIds::new();
+    let mut wires = vec![];
     for line in contents.trim().split('\n') {
         let (node1_name, neighbor_names) = line.split_once(": ").unwrap();
-        let node1 = *name_to_id.get(node1_name).unwrap();
+        let node1 = ids.id(node1_name.to_string());
         for neighbor_name in neighbor_names.split(' ') {
-            let node2 = *name_to_id.get(neighbor_name).unwrap();
-            adj_mat[node1][node2] = 1;
-            adj_mat[node2][node1] = 1;
+            wires.push((node1, ids.id(neighbor_name.to_string()), 1));
         }
     }
-    adj_mat
+    (ids, wires)
 }
 
-fn min_cut(mut adj_mat: AdjMat) -> usize {
-    let mut final_adj_mat = adj_mat.clone();
-    let mut removed = vec![false; adj_mat.len()];
-    let mut global_min_cut = u64::MAX;
-    let mut num_nodes_merged = vec![1; adj_mat.len()];
-    let mut final_num_nodes_merged = vec![1; adj_mat.len()];
-
-    // We need these during Min-Cut-Phase
-    let mut visited: Vec<bool> = vec![false; adj_mat.len()];
-    let mut dist_to_visited_set: Vec<u64> = vec![0; adj_mat.len()];
-
-    for _ in 1..adj_mat.len() {
-        // Min-Cut-Phase
-        let mut s = usize::MAX;
-        let mut t = (0..removed.len()).find(|i| !removed[*i]).unwrap();
-        let mut min_cut = 0;
-        for i in 0..visited.len() {
-            visited[i] = false;
-            dist_to_visited_set[i] = adj_mat[i][t];
-        }
-        for _ in 1..adj_mat.len() {
-            visited[t] = true;
-            let new_t = (0..adj_mat.len())
-                .max_by_key(|i| {
-                    if (removed[*i]) || (visited[*i]) {
-                        0
-                    } else {
-                        dist_to_visited_set[*i]
-                    }
-                })
-                .unwrap();
-            if (removed[new_t]) || (visited[new_t]) {
-                break;
-            }
-            s = t;
-            t = new_t;
-            min_cut = dist_to_visited_set[t];
-            dist_to_visited_set[t] = 0;
-            for (dist_i, edge_t_i) in dist_to_visited_set.iter_mut().zip(adj_mat[t].iter()) {
-                *dist_i += edge_t_i;
-            }
-        }
-
-        removed[t] = true;
-        for i in 0..adj_mat.len() {
-            adj_mat[s][i] += adj_mat[t][i];
-            adj_mat[i][s] = adj_mat[s][i];
-        }
-        for i in 0..adj_mat.len() {
-            adj_mat[t][i] = 0;
-            adj_mat[i][t] = 0;
-        }
-        if min_cut < global_min_cut {
-            global_min_cut = min_cut;
-            for i in 0..adj_mat.len() {
-                for j in 0..adj_mat[i].len() {
-                    final_adj_mat[i][j] = adj_mat[i][j];
-                }
-                final_num_nodes_merged[i] = num_nodes_merged[i];
-            }
-        }
-        num_nodes_merged[s] += num_nodes_merged[t];
-        num_nodes_merged[t] = 0;
-    }
-
-    let mut partition: Vec<usize> = Vec::new();
-    let mut to_visit: Vec<usize> = Vec::new();
-    to_visit.push(
-        (0..final_adj_mat.len())
-            .find(|i| final_adj_mat[*i].iter().sum::<u64>() > 0) // deleted nodes have no edges
-            .unwrap(),
-    );
-    while !to_visit.is_empty() {
-        let node = &to_visit.pop().unwrap();
-        partition.push(*node);
-        for (neighbor, neighbor_edges) in final_adj_mat.iter().enumerate() {
-            if (neighbor_edges[*node] > 0)
-                && (!to_visit.contains(&neighbor))
-                && (!partition.contains(&neighbor))
-            {
-                to_visit.push(neighbor);
-            }
-        }
-    }
-    let x = partition
-        .into_iter()
-        .map(|i| final_num_nodes_merged[i])
-        .sum::<usize>();
-    let y = final_adj_mat.len() - x;
+fn disconnect(contents: &str) -> (NodeIds<String>, MinCut<u64>) {
+    let (ids, wires) = parse_input(contents);
+    let cut = min_cut(ids.len(), wires).unwrap();
+    assert_eq!(cut.weight, 3, "Expected to snip exactly three wires");
+    (ids, cut)
+}
 
-    x * y
+/// The names of the components on either end of each wire that needs snipping, in
+/// alphabetical order both within each wire and between wires.
+fn wires_to_snip(contents: &str) -> Vec<(String, String)> {
+    let (ids, cut) = disconnect(contents);
+    let mut wires: Vec<(String, String)> = cut
+        .cut_edges
+        .iter()
+        .map(|&(a, b, _)| {
+            let (a, b) = (ids.node(a), ids.node(b));
+            (a.min(b).clone(), a.max(b).clone())
+        })
+        .collect();
+    wires.sort();
+    wires
 }
 
 fn compute_1(contents: &str) -> usize {
-    let adj_mat = parse_input(contents);
-    min_cut(adj_mat)
+    let (_, cut) = disconnect(contents);
+    cut.sides.0.len() * cut.sides.1.len()
+}
+
+/// Show which wires to snip. There's nothing to explore, so no commands are read.
+fn debug(contents: &str, output: &mut dyn Write) -> std::io::Result<()> {
+    let wires: Vec<String> = wires_to_snip(contents)
+        .iter()
+        .map(|(a, b)| format!("{a}/{b}"))
+        .collect();
+    writeln!(output, "Snip {}", wires.join(", "))
 }
 
 pub(crate) struct Day {}
@@ -156,4 +71,50 @@ impl Problem for Day {
     fn expected2(&self) -> String {
         "no part 2".to_string()
     }
+    fn debug(
+        &self,
+        contents: &str,
+        _input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), String> {
+        debug(contents, output).map_err(|e| format!("Problem writing the wires: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 54);
+    }
+
+    #[test]
+    fn test_wires_to_snip() {
+        let wires = wires_to_snip(EXAMPLE);
+        let expected = [("bvb", "cmg"), ("hfx", "pzl"), ("jqt", "nvd")];
+        assert_eq!(wires, expected.map(|(a, b)| (a.to_string(), b.to_string())));
+
+        let mut output = vec![];
+        debug(EXAMPLE, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Snip bvb/cmg, hfx/pzl, jqt/nvd\n"
+        );
+    }
 }
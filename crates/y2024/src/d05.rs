@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
 use shared::Problem;
+use shared::graph::toposort::{is_ordered, toposort};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct Rule {
@@ -46,19 +47,13 @@ impl FromStr for Manual {
 type Manuals = Vec<Manual>;
 type Rules = Vec<Rule>;
 
+fn rule_pairs(rules: &Rules) -> impl Iterator<Item = (u8, u8)> + '_ {
+    rules.iter().map(|rule| (rule.x, rule.y))
+}
+
 impl Manual {
     fn is_ordered(&self, rules: &Rules) -> bool {
-        for i in 1..self.pages.len() {
-            for j in 0..i {
-                if rules.contains(&Rule {
-                    x: self.pages[i],
-                    y: self.pages[j],
-                }) {
-                    return false;
-                }
-            }
-        }
-        true
+        is_ordered(&self.pages, rule_pairs(rules))
     }
 }
 
@@ -85,24 +80,22 @@ fn compute_1(contents: &str) -> u64 {
         .sum()
 }
 
-fn fix_manual(manual: Manual, rules: &Rules) -> Manual {
-    let mut pages = manual.pages;
-    pages.sort_by(|p1, p2| {
-        if rules.contains(&Rule { x: *p1, y: *p2 }) {
-            std::cmp::Ordering::Less
-        } else {
-            std::cmp::Ordering::Greater
-        }
-    });
-    Manual { pages }
+fn fix_manual(manual: Manual, rules: &Rules) -> Result<Manual, String> {
+    // Only the rules between this manual's pages matter, and they might not be consistent
+    let pages = toposort(&manual.pages, rule_pairs(rules))
+        .map_err(|err| format!("Can't fix manual {:?}: {err}", manual.pages))?;
+    Ok(Manual { pages })
 }
 
-fn compute_2(contents: &str) -> u64 {
+fn compute_2(contents: &str) -> Result<u64, String> {
     let (rules, manuals) = parse_input(contents);
     manuals
         .iter()
         .filter(|manual| !manual.is_ordered(&rules))
-        .map(|manual| fix_manual(manual.clone(), &rules).pages[manual.pages.len() / 2] as u64)
+        .map(|manual| {
+            let fixed = fix_manual(manual.clone(), &rules)?;
+            Ok(fixed.pages[fixed.pages.len() / 2] as u64)
+        })
         .sum()
 }
 
@@ -116,7 +109,10 @@ impl Problem for Day {
         format!("{}", compute_1(contents))
     }
     fn solve2(&self, contents: &str) -> String {
-        format!("{}", compute_2(contents))
+        match compute_2(contents) {
+            Ok(total) => format!("{total}"),
+            Err(e) => e,
+        }
     }
     fn expected1(&self) -> String {
         "4569".to_string()
@@ -125,3 +121,59 @@ impl Problem for Day {
         "6456".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 143);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), Ok(123));
+    }
+
+    #[test]
+    fn test_compute_2_errors() {
+        assert_eq!(
+            compute_2("1|2\n2|1\n\n2,1"),
+            Err("Can't fix manual [2, 1]: Rules form a cycle: 1 -> 2 -> 1".to_string())
+        );
+        assert_eq!(
+            compute_2("1|2\n\n2,1,2"),
+            Err("Can't fix manual [2, 1, 2]: 2 appears more than once".to_string())
+        );
+    }
+}
@@ -1,89 +1,51 @@
-use shared::Problem;
-
-#[derive(Clone, PartialEq, Eq, Copy)]
-enum Dir {
-    Left,
-    Up,
-    Right,
-    Down,
-}
+use shared::{Dir4, Grid, Problem};
 
 #[derive(Clone, PartialEq, Eq)]
 enum Tile {
     Obstacle,
     Unvisited,
-    Visited(Dir),
+    Visited(Dir4),
 }
 
 #[derive(Clone, PartialEq, Eq)]
 struct Guard {
     pos: (usize, usize),
-    dir: Dir,
+    dir: Dir4,
 }
 
-type Map = Vec<Vec<Tile>>;
+type Map = Grid<Tile>;
 
 fn parse_input(contents: &str) -> (Map, Guard) {
-    let mut map: Map = Vec::new();
-    let mut guard = Guard {
-        pos: (usize::MAX, usize::MAX),
-        dir: Dir::Up,
+    let map = Grid::parse(contents, |c| match c {
+        '.' => Tile::Unvisited,
+        '^' => Tile::Visited(Dir4::Up),
+        '#' => Tile::Obstacle,
+        _ => panic!("Unexpected character {c}"),
+    });
+    let guard = Guard {
+        pos: map
+            .position(|tile| matches!(tile, Tile::Visited(_)))
+            .expect("No guard found"),
+        dir: Dir4::Up,
     };
-    for (i, line) in contents.trim().split('\n').enumerate() {
-        map.push(
-            line.chars()
-                .enumerate()
-                .map(|(j, c)| match c {
-                    '.' => Tile::Unvisited,
-                    '^' => {
-                        guard.pos = (i, j);
-                        Tile::Visited(Dir::Up)
-                    }
-                    '#' => Tile::Obstacle,
-                    _ => panic!("Unexpected character {c}"),
-                })
-                .collect(),
-        )
-    }
-    assert!(guard.pos.0 < usize::MAX);
     (map, guard)
 }
 
 fn process_path(map: &mut Map, mut guard: Guard) -> usize {
-    loop {
-        let (new_i, new_j) = match guard.dir {
-            Dir::Left => (guard.pos.0, guard.pos.1.wrapping_sub(1)),
-            Dir::Up => (guard.pos.0.wrapping_sub(1), guard.pos.1),
-            Dir::Right => (guard.pos.0, guard.pos.1 + 1),
-            Dir::Down => (guard.pos.0 + 1, guard.pos.1),
-        };
-        if new_i >= map.len() || new_j >= map[0].len() {
-            break;
+    while let Some(new_pos) = map.step(guard.pos.0, guard.pos.1, guard.dir) {
+        if map[new_pos] == Tile::Visited(guard.dir) {
+            return 0;
         }
-        if let Tile::Visited(dir) = map[new_i][new_j] {
-            if dir == guard.dir {
-                return 0;
-            }
-        }
-        if map[new_i][new_j] == Tile::Obstacle {
-            guard.dir = match guard.dir {
-                Dir::Left => Dir::Up,
-                Dir::Up => Dir::Right,
-                Dir::Right => Dir::Down,
-                Dir::Down => Dir::Left,
-            };
+        if map[new_pos] == Tile::Obstacle {
+            guard.dir = guard.dir.turn_right();
         } else {
-            map[new_i][new_j] = Tile::Visited(guard.dir);
-            guard.pos = (new_i, new_j);
+            map[new_pos] = Tile::Visited(guard.dir);
+            guard.pos = new_pos;
         }
     }
     map.iter()
-        .map(|row| {
-            row.iter()
-                .filter(|tile| matches!(tile, Tile::Visited(_)))
-                .count()
-        })
-        .sum()
+        .filter(|tile| matches!(tile, Tile::Visited(_)))
+        .count()
 }
 
 fn compute_1(contents: &str) -> usize {
@@ -93,21 +55,18 @@ fn compute_1(contents: &str) -> usize {
 
 fn compute_2(contents: &str) -> u64 {
     let (map, guard) = parse_input(contents);
-    let (initial_i, initial_j) = guard.pos;
     let mut counter = 0;
     let mut initial_path_map = map.clone();
     process_path(&mut initial_path_map, guard.clone());
-    for i in 0..map.len() {
-        for j in 0..map[i].len() {
-            if i == initial_i && j == initial_j {
-                continue;
-            }
-            if let Tile::Visited(_) = initial_path_map[i][j] {
-                let mut new_map = map.clone();
-                new_map[i][j] = Tile::Obstacle;
-                if process_path(&mut new_map, guard.clone()) == 0 {
-                    counter += 1;
-                }
+    for pos in map.positions() {
+        if pos == guard.pos {
+            continue;
+        }
+        if let Tile::Visited(_) = initial_path_map[pos] {
+            let mut new_map = map.clone();
+            new_map[pos] = Tile::Obstacle;
+            if process_path(&mut new_map, guard.clone()) == 0 {
+                counter += 1;
             }
         }
     }
@@ -133,3 +92,25 @@ impl Problem for Day {
         "1575".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn test_example() {
+        assert_eq!(compute_1(EXAMPLE), 41);
+        assert_eq!(compute_2(EXAMPLE), 6);
+    }
+}
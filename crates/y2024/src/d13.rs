@@ -1,9 +1,6 @@
-use std::{
-    ops::{Add, Sub},
-    str::FromStr,
-};
+use std::{ops::Add, str::FromStr};
 
-use shared::Problem;
+use shared::{Problem, linalg::Matrix, num::Rational};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 struct Point {
@@ -38,26 +35,6 @@ impl Add for &Point {
     }
 }
 
-impl Sub for &Point {
-    type Output = Point;
-
-    fn sub(self, other: Self) -> Self::Output {
-        Point {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
-    }
-}
-
-impl Point {
-    fn scalar_mul(&self, other: i64) -> Point {
-        Point {
-            x: self.x * other,
-            y: self.y * other,
-        }
-    }
-}
-
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 struct Machine {
     a: Point,
@@ -91,41 +68,23 @@ fn possible_score(machine: &Machine) -> Option<i64> {
     //
     // However, even though this looks like an optimization problem,
     // there is actually exactly one solution (or <=1 solution when restricted to
-    // non-negative integers) when A and B are not zero and not collinear:
-    //
-    // Let Ax be the x part of A, and similar for Ay, Bx, By, Px, Py.
-    //
-    // mAx = Px - nBx
-    // mAy = Py - nBy
-    //
-    // m = (Px - nBx) / Ax
-    // m = (Py - nBy) / Ay
+    // non-negative integers) when A and B are not collinear, so we can solve
+    // the linear system exactly and check whether the solution is usable.
     //
-    // (Px - nBx) / Ax = (Py - nBy) / Ay
-    // (Px - nBx)Ay = (Py - nBy)Ax  # multiply by AyAx
-    // PxAy - nBxAy = PyAx - nByAx
-    // PxAy - PyAx = n(BxAy - ByAx)
-    // n = (PxAy - PyAx) / (BxAy - ByAx)
-    //
-    // Now that we know n, we can solve for m:
-    //
-    // m = (Py - nBy) / Ay
-    //
-    // The above math relies on Ax != 0, Ay != 0, and BxAy != ByAx
-    // There's no reason this has to be true, but it does hold,
+    // There's no reason A and B can't be collinear, but they never are,
     // at least for my input.
-    //
-    // Note that these solutions may not be integers, so we can simply
-    // check the final solution mA + nB = P, and if it is not satisfied
-    // then it is unsolvable (given the constraints).
-    let n = (machine.a.x * machine.prize.y - machine.prize.x * machine.a.y)
-        / (machine.a.x * machine.b.y - machine.b.x * machine.a.y);
-    let m = (machine.prize.x - n * machine.b.x) / machine.a.x;
-    if (n >= 0) // might have found solution with negative n or m, ignore those
-        && (m >= 0)
-        && (&machine.a.scalar_mul(m) + &machine.b.scalar_mul(n) == machine.prize)
-    {
-        Some(3 * m + n)
+    let matrix = Matrix::from_ints(vec![
+        vec![machine.a.x as i128, machine.b.x as i128],
+        vec![machine.a.y as i128, machine.b.y as i128],
+    ]);
+    let prize = [machine.prize.x, machine.prize.y].map(|p| Rational::whole_number(p as i128));
+    let solution = matrix.solve(&prize)?;
+    assert!(solution.is_unique(), "Collinear buttons aren't supported");
+    let m = solution.particular[0].integral()?;
+    let n = solution.particular[1].integral()?;
+    // might have found solution with negative n or m, ignore those
+    if (n >= 0) && (m >= 0) {
+        Some((3 * m + n) as i64)
     } else {
         None
     }
@@ -171,3 +130,34 @@ impl Problem for Day {
         "105620095782547".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 480);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 875318608908);
+    }
+}
@@ -1,37 +1,6 @@
-use std::cmp::Reverse;
-use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashMap, HashSet},
-};
+use std::collections::{HashMap, HashSet};
 
-use shared::Problem;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Dir {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Dir {
-    fn rotate_left(&self) -> Self {
-        match self {
-            Dir::Up => Dir::Left,
-            Dir::Right => Dir::Up,
-            Dir::Down => Dir::Right,
-            Dir::Left => Dir::Down,
-        }
-    }
-    fn rotate_right(&self) -> Self {
-        match self {
-            Dir::Up => Dir::Right,
-            Dir::Right => Dir::Down,
-            Dir::Down => Dir::Left,
-            Dir::Left => Dir::Up,
-        }
-    }
-}
+use shared::{Dir4 as Dir, Problem, search};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Id(usize, usize, Dir);
@@ -39,18 +8,6 @@ struct Id(usize, usize, Dir);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Edge(Id, u64);
 
-impl PartialOrd for Edge {
-    fn partial_cmp(&self, other: &Edge) -> Option<Ordering> {
-        Some(self.1.cmp(&other.1))
-    }
-}
-
-impl Ord for Edge {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.1.cmp(&other.1)
-    }
-}
-
 fn parse_input(contents: &str) -> (Id, Id, HashMap<Id, Vec<Edge>>) {
     #[derive(PartialEq, Eq)]
     enum Tile {
@@ -112,11 +69,11 @@ fn parse_input(contents: &str) -> (Id, Id, HashMap<Id, Vec<Edge>>) {
                                     .or_insert(vec![])
                                     .push(Edge(Id(new_i, new_j, dir), 1));
                                 graph
-                                    .entry(Id(i, j, dir.rotate_left()))
+                                    .entry(Id(i, j, dir.turn_left()))
                                     .or_insert(vec![])
                                     .push(Edge(Id(i, j, dir), 1000));
                                 graph
-                                    .entry(Id(i, j, dir.rotate_right()))
+                                    .entry(Id(i, j, dir.turn_right()))
                                     .or_insert(vec![])
                                     .push(Edge(Id(i, j, dir), 1000));
                             }
@@ -129,87 +86,40 @@ fn parse_input(contents: &str) -> (Id, Id, HashMap<Id, Vec<Edge>>) {
     (start, end, graph)
 }
 
-fn compute_1(contents: &str) -> u64 {
-    let (start, end, graph) = parse_input(contents);
-    let mut visited: HashSet<Id> = HashSet::new();
-    let mut to_visit = BinaryHeap::new();
-    to_visit.push(Reverse(Edge(start, 0)));
-    while let Some(Reverse(curr_edge)) = to_visit.pop() {
-        let id = curr_edge.0;
-        let cost = curr_edge.1;
-        if id.0 == end.0 && id.1 == end.1 {
-            return cost;
-        }
-        visited.insert(id);
-        if graph.contains_key(&id) {
-            for next_edge in graph[&id].iter() {
-                let next_id = next_edge.0;
-                let next_cost = next_edge.1;
-                if !visited.contains(&next_id) {
-                    to_visit.push(Reverse(Edge(next_id, cost + next_cost)));
-                }
-            }
-        }
-    }
-
-    unreachable!()
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct TrackedEdge(Edge, HashSet<(usize, usize)>);
-
-impl PartialOrd for TrackedEdge {
-    fn partial_cmp(&self, other: &TrackedEdge) -> Option<Ordering> {
-        Some(self.0.1.cmp(&other.0.1))
-    }
+fn neighbors<'a>(
+    graph: &'a HashMap<Id, Vec<Edge>>,
+    id: &Id,
+) -> impl Iterator<Item = (Id, u64)> + use<'a> {
+    graph
+        .get(id)
+        .into_iter()
+        .flatten()
+        .map(|&Edge(next_id, cost)| (next_id, cost))
 }
 
-impl Ord for TrackedEdge {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.0.1.cmp(&other.0.1)
-    }
+fn compute_1(contents: &str) -> u64 {
+    let (start, end, graph) = parse_input(contents);
+    search::dijkstra(
+        start,
+        |id| neighbors(&graph, id),
+        |id| id.0 == end.0 && id.1 == end.1,
+    )
+    .cost()
+    .unwrap()
 }
 
 fn compute_2(contents: &str) -> usize {
     let (start, end, graph) = parse_input(contents);
-    let mut visited: HashSet<Id> = HashSet::new();
-    let mut to_visit = BinaryHeap::new();
-    to_visit.push(Reverse(TrackedEdge(
-        Edge(start, 0),
-        HashSet::from([(start.0, start.1)]),
-    )));
-    let mut lowest_score = u64::MAX;
-    let mut tiles_along_path: HashSet<(usize, usize)> = HashSet::new();
-    while let Some(Reverse(curr_edge)) = to_visit.pop() {
-        let id = curr_edge.0.0;
-        let cost = curr_edge.0.1;
-        let locations_visited = curr_edge.1;
-        if cost > lowest_score {
-            break;
-        }
-        if id.0 == end.0 && id.1 == end.1 {
-            tiles_along_path.extend(locations_visited);
-            lowest_score = cost;
-            continue;
-        }
-        visited.insert(id);
-        if graph.contains_key(&id) {
-            for next_edge in graph[&id].iter() {
-                let next_id = next_edge.0;
-                let next_cost = next_edge.1;
-                let mut next_locations_visited = locations_visited.clone();
-                next_locations_visited.insert((next_id.0, next_id.1));
-                if !visited.contains(&next_id) {
-                    to_visit.push(Reverse(TrackedEdge(
-                        Edge(next_id, cost + next_cost),
-                        next_locations_visited,
-                    )));
-                }
-            }
-        }
-    }
-
-    tiles_along_path.len()
+    search::dijkstra_all_optimal(
+        start,
+        |id| neighbors(&graph, id),
+        |id| id.0 == end.0 && id.1 == end.1,
+    )
+    .optimal_states()
+    .into_iter()
+    .map(|id| (id.0, id.1))
+    .collect::<HashSet<(usize, usize)>>()
+    .len()
 }
 
 pub(crate) struct Day {}
@@ -231,3 +141,50 @@ impl Problem for Day {
         "535".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_1: &str = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+    const EXAMPLE_2: &str = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#...........#.#
+#################";
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(compute_1(EXAMPLE_1), 7036);
+        assert_eq!(compute_2(EXAMPLE_1), 45);
+        assert_eq!(compute_1(EXAMPLE_2), 11048);
+        assert_eq!(compute_2(EXAMPLE_2), 64);
+    }
+}
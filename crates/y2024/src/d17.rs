@@ -1,5 +1,137 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{BufRead, Write};
+
 use shared::Problem;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Opcode {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+const OPCODES: [Opcode; 8] = [
+    Opcode::Adv,
+    Opcode::Bxl,
+    Opcode::Bst,
+    Opcode::Jnz,
+    Opcode::Bxc,
+    Opcode::Out,
+    Opcode::Bdv,
+    Opcode::Cdv,
+];
+
+impl Opcode {
+    fn mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Adv => "adv",
+            Opcode::Bxl => "bxl",
+            Opcode::Bst => "bst",
+            Opcode::Jnz => "jnz",
+            Opcode::Bxc => "bxc",
+            Opcode::Out => "out",
+            Opcode::Bdv => "bdv",
+            Opcode::Cdv => "cdv",
+        }
+    }
+
+    /// Whether the operand is a combo operand (where 4, 5 and 6 mean registers A, B and C)
+    /// rather than a literal.
+    fn takes_combo(&self) -> bool {
+        matches!(
+            self,
+            Opcode::Adv | Opcode::Bst | Opcode::Out | Opcode::Bdv | Opcode::Cdv
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction {
+    opcode: Opcode,
+    operand: u8,
+}
+
+impl Instruction {
+    fn decode(opcode: u8, operand: u8) -> Instruction {
+        Instruction {
+            opcode: OPCODES[opcode as usize],
+            operand,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = match (self.opcode.takes_combo(), self.operand) {
+            (true, 4) => "a".to_string(),
+            (true, 5) => "b".to_string(),
+            (true, 6) => "c".to_string(),
+            (_, n) => n.to_string(),
+        };
+        write!(f, "{} {operand}", self.opcode.mnemonic())
+    }
+}
+
+/// One line per instruction, e.g. `0: bst a`, where the number is the instruction pointer.
+fn disassemble(program: &[u8]) -> String {
+    program
+        .chunks(2)
+        .enumerate()
+        .map(|(i, chunk)| match chunk {
+            [opcode, operand] => format!("{}: {}\n", 2 * i, Instruction::decode(*opcode, *operand)),
+            // A trailing opcode without an operand is never run
+            _ => format!("{}: ; {}\n", 2 * i, chunk[0]),
+        })
+        .collect()
+}
+
+/// The reverse of [`disassemble`]: one instruction per line, with combo operands written as
+/// `a`, `b` or `c` or as a number. Anything after a `;` is ignored, as is an `n:` prefix.
+fn assemble(source: &str) -> Result<Vec<u8>, String> {
+    let mut program = vec![];
+    for (line_number, line) in source.lines().enumerate() {
+        let line = line.split(';').next().unwrap();
+        let line = line.split_once(':').map_or(line, |(_, rest)| rest).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message: String| format!("Line {}: {message}", line_number + 1);
+        let (mnemonic, operand) = line.split_once(' ').unwrap_or((line, ""));
+        let opcode = OPCODES
+            .iter()
+            .position(|opcode| opcode.mnemonic() == mnemonic)
+            .ok_or_else(|| error(format!("Unknown instruction {mnemonic:?}")))?;
+        let operand = match (OPCODES[opcode], operand.trim()) {
+            (opcode, "a") if opcode.takes_combo() => 4,
+            (opcode, "b") if opcode.takes_combo() => 5,
+            (opcode, "c") if opcode.takes_combo() => 6,
+            // The operand of bxc is ignored, so it can be left out
+            (Opcode::Bxc, "") => 0,
+            // Combo operand 7 is reserved
+            (opcode, operand) => match operand.parse::<u8>() {
+                Ok(n) if n < 7 || (n == 7 && !opcode.takes_combo()) => n,
+                _ => return Err(error(format!("Invalid operand {operand:?}"))),
+            },
+        };
+        program.extend([opcode as u8, operand]);
+    }
+    Ok(program)
+}
+
+fn format_program(program: &[u8]) -> String {
+    program
+        .iter()
+        .map(|i| i.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
 #[derive(Debug, Clone)]
 struct Interpreter {
     program: Vec<u8>,
@@ -10,69 +142,289 @@ struct Interpreter {
 }
 
 impl Interpreter {
-    fn combo(&self, n: u8) -> u64 {
+    fn combo(&self, n: u8) -> Result<u64, String> {
         match n {
-            0..=3 => n as u64,
-            4 => self.reg_a,
-            5 => self.reg_b,
-            6 => self.reg_c,
-            _ => panic!("Unexpected combo operand {n}"),
+            0..=3 => Ok(n as u64),
+            4 => Ok(self.reg_a),
+            5 => Ok(self.reg_b),
+            6 => Ok(self.reg_c),
+            _ => Err(format!(
+                "Invalid combo operand {n} at ip {}",
+                self.instruction_pointer
+            )),
         }
     }
 
-    fn run(&mut self) -> Vec<u8> {
-        let mut out: Vec<u8> = Vec::new();
-        while self.instruction_pointer < self.program.len() - 1 {
-            let opcode = self.program.get(self.instruction_pointer).unwrap();
-            let operand = self.program.get(self.instruction_pointer + 1).unwrap();
-            match opcode {
-                // 0: adv
-                0 => {
-                    self.reg_a >>= self.combo(*operand);
-                    self.instruction_pointer += 2;
+    /// Register A divided by two to the power of the combo operand, rounding down.
+    fn divide(&self, n: u8) -> Result<u64, String> {
+        Ok(u32::try_from(self.combo(n)?)
+            .ok()
+            .and_then(|shift| self.reg_a.checked_shr(shift))
+            .unwrap_or(0))
+    }
+
+    fn halted(&self) -> bool {
+        self.instruction_pointer + 1 >= self.program.len()
+    }
+
+    /// The instruction that will be run next, unless the program has halted.
+    fn current_instruction(&self) -> Option<Instruction> {
+        if self.halted() {
+            return None;
+        }
+        Some(Instruction::decode(
+            self.program[self.instruction_pointer],
+            self.program[self.instruction_pointer + 1],
+        ))
+    }
+
+    /// Run one instruction, returning its output if it's an `out`. Nothing changes if the
+    /// instruction can't be run.
+    fn step(&mut self) -> Result<Option<u8>, String> {
+        let Some(Instruction { opcode, operand }) = self.current_instruction() else {
+            return Err("The program has halted".to_string());
+        };
+        let mut out = None;
+        match opcode {
+            Opcode::Adv => self.reg_a = self.divide(operand)?,
+            Opcode::Bxl => self.reg_b ^= operand as u64,
+            Opcode::Bst => self.reg_b = self.combo(operand)? & 0b111,
+            Opcode::Jnz => {
+                if self.reg_a != 0 {
+                    self.instruction_pointer = operand as usize;
+                    return Ok(None);
                 }
-                // 1: bxl
-                1 => {
-                    self.reg_b ^= *operand as u64;
-                    self.instruction_pointer += 2;
+            }
+            Opcode::Bxc => self.reg_b ^= self.reg_c,
+            Opcode::Out => out = Some((self.combo(operand)? & 0b111) as u8),
+            Opcode::Bdv => self.reg_b = self.divide(operand)?,
+            Opcode::Cdv => self.reg_c = self.divide(operand)?,
+        }
+        self.instruction_pointer += 2;
+        Ok(out)
+    }
+
+    fn run(&mut self) -> Result<Vec<u8>, String> {
+        let mut out: Vec<u8> = Vec::new();
+        while !self.halted() {
+            out.extend(self.step()?);
+        }
+        Ok(out)
+    }
+
+    fn registers(&self) -> String {
+        format!(
+            "ip={} a={} b={} c={}",
+            self.instruction_pointer, self.reg_a, self.reg_b, self.reg_c
+        )
+    }
+}
+
+const DEBUGGER_HELP: &str = "Commands:
+  s, step [n]      run n instructions (default 1), showing each one
+  c, continue      run until a breakpoint or the program halts, for at most the step limit
+  limit <n>        set the step limit for continue (1000000 by default)
+  b, break <ip>    toggle a breakpoint on an instruction pointer
+  t, trace         toggle showing every instruction while continuing
+  r, regs          show the registers
+  l, list          show the disassembled program
+  o, out           show the output so far
+  set <reg> <n>    set register a, b, c or ip
+  patch <ip> <instruction>
+                   replace the instruction at ip, e.g. \"patch 2 bxl 5\"
+  reset            start again from the initial state
+  h, help          show this message
+  q, quit          stop debugging
+";
+
+/// How many instructions `continue` runs at most, unless the limit is changed.
+const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
+/// Step through a program interactively, reading one command per line from `input`.
+struct Debugger {
+    initial: Interpreter,
+    interpreter: Interpreter,
+    breakpoints: BTreeSet<usize>,
+    trace: bool,
+    /// How many instructions `continue` runs before giving up, in case the program never halts.
+    max_steps: u64,
+    out: Vec<u8>,
+}
+
+impl Debugger {
+    fn new(interpreter: Interpreter) -> Debugger {
+        Debugger {
+            initial: interpreter.clone(),
+            interpreter,
+            breakpoints: BTreeSet::new(),
+            trace: false,
+            max_steps: DEFAULT_MAX_STEPS,
+            out: vec![],
+        }
+    }
+
+    /// Run one instruction, describing it if `show` is set. Returns whether it ran, after
+    /// describing the problem if it didn't.
+    fn step(&mut self, output: &mut dyn Write, show: bool) -> std::io::Result<bool> {
+        let Some(instruction) = self.interpreter.current_instruction() else {
+            writeln!(output, "Halted")?;
+            return Ok(false);
+        };
+        let ip = self.interpreter.instruction_pointer;
+        let value = match self.interpreter.step() {
+            Ok(value) => value,
+            Err(e) => {
+                writeln!(output, "{e}")?;
+                return Ok(false);
+            }
+        };
+        self.out.extend(value);
+        if show {
+            let printed = value.map_or(String::new(), |v| format!(" -> {v}"));
+            writeln!(
+                output,
+                "{ip:>3}: {:<8}{}{printed}",
+                instruction.to_string(),
+                self.interpreter.registers()
+            )?;
+        }
+        Ok(true)
+    }
+
+    /// Carry out one command, returning whether to keep going.
+    fn command(&mut self, line: &str, output: &mut dyn Write) -> std::io::Result<bool> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |i: usize| words.get(i).and_then(|w| w.parse::<u64>().ok());
+        match words.as_slice() {
+            [] => {}
+            ["s" | "step", ..] => {
+                for _ in 0..number(1).unwrap_or(1) {
+                    if self.interpreter.halted() || !self.step(output, true)? {
+                        break;
+                    }
                 }
-                // 2: bst
-                2 => {
-                    self.reg_b = self.combo(*operand) & 0b111;
-                    self.instruction_pointer += 2;
+                if self.interpreter.halted() {
+                    writeln!(output, "Halted")?;
                 }
-                // 3: jnz
-                3 => {
-                    if self.reg_a == 0 {
-                        self.instruction_pointer += 2;
-                    } else {
-                        self.instruction_pointer = *operand as usize;
+            }
+            ["c" | "continue"] => {
+                // Always take at least one step, so that continuing from a breakpoint works
+                let mut num_steps = 0;
+                while !self.interpreter.halted() {
+                    if num_steps == self.max_steps {
+                        writeln!(output, "Stopped after {num_steps} steps")?;
+                        break;
+                    }
+                    if !self.step(output, self.trace)? {
+                        break;
                     }
+                    num_steps += 1;
+                    if self
+                        .breakpoints
+                        .contains(&self.interpreter.instruction_pointer)
+                    {
+                        writeln!(
+                            output,
+                            "Breakpoint at {}",
+                            self.interpreter.instruction_pointer
+                        )?;
+                        break;
+                    }
+                }
+                if self.interpreter.halted() {
+                    writeln!(output, "Halted with output {}", format_program(&self.out))?;
                 }
-                // 4: bxc
-                4 => {
-                    self.reg_b ^= self.reg_c;
-                    self.instruction_pointer += 2;
+            }
+            ["b" | "break", _] if number(1).is_some() => {
+                let ip = number(1).unwrap() as usize;
+                if self.breakpoints.remove(&ip) {
+                    writeln!(output, "Removed breakpoint at {ip}")?;
+                } else {
+                    self.breakpoints.insert(ip);
+                    writeln!(output, "Added breakpoint at {ip}")?;
                 }
-                // 5: out
-                5 => {
-                    self.instruction_pointer += 2;
-                    out.push((self.combo(*operand) & 0b111) as u8);
+            }
+            ["limit", _] if number(1).is_some() => {
+                self.max_steps = number(1).unwrap();
+                writeln!(output, "Continue stops after {} steps", self.max_steps)?;
+            }
+            ["t" | "trace"] => {
+                self.trace = !self.trace;
+                writeln!(output, "Tracing {}", if self.trace { "on" } else { "off" })?;
+            }
+            ["r" | "regs"] => writeln!(output, "{}", self.interpreter.registers())?,
+            ["l" | "list"] => {
+                for line in disassemble(&self.interpreter.program).lines() {
+                    let ip: usize = line.split(':').next().unwrap().parse().unwrap();
+                    let marker = if ip == self.interpreter.instruction_pointer {
+                        ">"
+                    } else {
+                        " "
+                    };
+                    let breakpoint = if self.breakpoints.contains(&ip) {
+                        "*"
+                    } else {
+                        " "
+                    };
+                    writeln!(output, "{marker}{breakpoint}{line}")?;
                 }
-                // 6: bdv
-                6 => {
-                    self.reg_b = self.reg_a >> self.combo(*operand);
-                    self.instruction_pointer += 2;
+            }
+            ["o" | "out"] => writeln!(output, "{}", format_program(&self.out))?,
+            ["set", register, _] if number(2).is_some() => {
+                let value = number(2).unwrap();
+                match *register {
+                    "a" => self.interpreter.reg_a = value,
+                    "b" => self.interpreter.reg_b = value,
+                    "c" => self.interpreter.reg_c = value,
+                    "ip" if value.is_multiple_of(2) => {
+                        self.interpreter.instruction_pointer = value as usize
+                    }
+                    "ip" => writeln!(output, "The instruction pointer has to be even")?,
+                    _ => writeln!(output, "Unknown register {register:?}")?,
                 }
-                // 7: cdv
-                7 => {
-                    self.reg_c = self.reg_a >> self.combo(*operand);
-                    self.instruction_pointer += 2;
+                writeln!(output, "{}", self.interpreter.registers())?;
+            }
+            ["patch", _, instruction @ ..] if number(1).is_some() => {
+                let ip = number(1).unwrap() as usize;
+                match assemble(&instruction.join(" ")) {
+                    Ok(code)
+                        if code.len() == 2
+                            && ip.is_multiple_of(2)
+                            && ip + 1 < self.interpreter.program.len() =>
+                    {
+                        self.interpreter.program[ip..ip + 2].copy_from_slice(&code);
+                        writeln!(output, "{ip}: {}", Instruction::decode(code[0], code[1]))?;
+                    }
+                    Ok(_) => writeln!(
+                        output,
+                        "Can only patch one instruction at an even ip in the program"
+                    )?,
+                    Err(e) => writeln!(output, "{e}")?,
                 }
-                _ => panic!("Unexpected state opcode {opcode} and operand {operand}"),
+            }
+            ["reset"] => {
+                self.interpreter = self.initial.clone();
+                self.out.clear();
+                writeln!(output, "{}", self.interpreter.registers())?;
+            }
+            ["h" | "help"] => write!(output, "{DEBUGGER_HELP}")?,
+            ["q" | "quit"] => return Ok(false),
+            _ => writeln!(output, "Unknown command {line:?}, try \"help\"")?,
+        }
+        Ok(true)
+    }
+
+    fn run(&mut self, input: &mut dyn BufRead, output: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(output, "{}", self.interpreter.registers())?;
+        let mut line = String::new();
+        loop {
+            write!(output, "> ")?;
+            output.flush()?;
+            line.clear();
+            if input.read_line(&mut line)? == 0 || !self.command(line.trim(), output)? {
+                return Ok(());
             }
         }
-        out
     }
 }
 
@@ -102,89 +454,142 @@ fn parse_input(contents: &str) -> Interpreter {
     }
 }
 
-fn compute_1(contents: &str) -> Vec<u8> {
+fn compute_1(contents: &str) -> Result<Vec<u8>, String> {
     let mut interpreter = parse_input(contents);
     interpreter.run()
 }
 
-// To be honest, I cannot fully explain this one.
-// After hand-inspecting the program, it became clear that it operated on 3 bits of A
-// at a time: e.g. 0b_xxx_yyy_zzz will output three values, each one (mostly) driven by
-// zzz, yyy, and xxx respectively.
-//
-// If the sets of 3 bits were totally independent, then this problem would be
-// very simple. E.g. if for 0b_xxx_yyy_zzz, the first output value _only_ depended
-// on the zzz bits, then it would be straight forward.
-//
-// However, it also became clear that consecutive groups of 3 bits _can_
-// influence the output of each other, mostly because of the division instructions
-// which are basically A >> n for some n=0..8 (n possibly being another register).
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum QuineError {
+    /// The program has to be one loop, ending with the only `jnz 0`.
+    NotALoop,
+    /// Each time round the loop, A has to be shifted right by 3 bits (by a single `adv 3`).
+    /// Otherwise the outputs don't line up with the digits of A in base 8.
+    NotShiftingBy3(String),
+    /// Each time round the loop there has to be exactly one output.
+    OutputsPerLoop(usize),
+    /// B and C have to be set from A each time round the loop before they're read, so that the
+    /// outputs only depend on A. This is the register and the instruction that reads it.
+    CarriesRegister(char, String),
+    NoQuine,
+}
+
+impl fmt::Display for QuineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuineError::NotALoop => write!(f, "The program isn't a single loop ending in jnz 0"),
+            QuineError::NotShiftingBy3(reason) => {
+                write!(
+                    f,
+                    "The program doesn't shift A by 3 bits per loop: {reason}"
+                )
+            }
+            QuineError::OutputsPerLoop(n) => {
+                write!(f, "The program outputs {n} values per loop instead of 1")
+            }
+            QuineError::CarriesRegister(register, instruction) => write!(
+                f,
+                "The program reads {register} in `{instruction}` before setting it in the loop"
+            ),
+            QuineError::NoQuine => write!(f, "No value of A makes the program output itself"),
+        }
+    }
+}
+
+/// Check that the program is a loop that consumes one base 8 digit of A and outputs one value
+/// each time round, which only depends on A.
+fn check_loop_structure(program: &[u8]) -> Result<(), QuineError> {
+    let instructions: Vec<Instruction> = program
+        .chunks_exact(2)
+        .map(|chunk| Instruction::decode(chunk[0], chunk[1]))
+        .collect();
+    let jump = Instruction {
+        opcode: Opcode::Jnz,
+        operand: 0,
+    };
+    let num_jumps = instructions
+        .iter()
+        .filter(|instruction| instruction.opcode == Opcode::Jnz)
+        .count();
+    if !program.len().is_multiple_of(2) || instructions.last() != Some(&jump) || num_jumps != 1 {
+        return Err(QuineError::NotALoop);
+    }
+    let shifts: Vec<&Instruction> = instructions
+        .iter()
+        .filter(|instruction| instruction.opcode == Opcode::Adv)
+        .collect();
+    match shifts.as_slice() {
+        [] => return Err(QuineError::NotShiftingBy3("A is never shifted".to_string())),
+        [shift] if shift.operand != 3 => {
+            return Err(QuineError::NotShiftingBy3(format!("found `{shift}`")));
+        }
+        [_] => {}
+        _ => {
+            return Err(QuineError::NotShiftingBy3(format!(
+                "A is shifted {} times",
+                shifts.len()
+            )));
+        }
+    }
+    let num_outputs = instructions
+        .iter()
+        .filter(|instruction| instruction.opcode == Opcode::Out)
+        .count();
+    if num_outputs != 1 {
+        return Err(QuineError::OutputsPerLoop(num_outputs));
+    }
+    let (mut set_b, mut set_c) = (false, false);
+    for instruction in &instructions {
+        let Instruction { opcode, operand } = *instruction;
+        let reads_b =
+            matches!(opcode, Opcode::Bxl | Opcode::Bxc) || (opcode.takes_combo() && operand == 5);
+        let reads_c = opcode == Opcode::Bxc || (opcode.takes_combo() && operand == 6);
+        for (reads, set, register) in [(reads_b, set_b, 'B'), (reads_c, set_c, 'C')] {
+            if reads && !set {
+                return Err(QuineError::CarriesRegister(
+                    register,
+                    instruction.to_string(),
+                ));
+            }
+        }
+        set_b |= matches!(opcode, Opcode::Bst | Opcode::Bdv);
+        set_c |= opcode == Opcode::Cdv;
+    }
+    Ok(())
+}
+
+// The program goes round the loop once per base 8 digit of A, shifting a digit off each time,
+// so the last output only depends on the most significant digit, the last two outputs only
+// depend on the top two digits, and so on. (The outputs can depend on several digits at once,
+// but only ones that are more significant, since A is only ever shifted right.)
 //
-// To get around this, we keep track of all possible values of bits would result
-// in the desired output. E.g. which 0b_zzz bits result in desired output value z.
-// This is the part that I cannot explain why it works, the approach came to me when
-// I was playing around with different inputs manually.
-fn compute_2(contents: &str) -> u64 {
+// So we build up A a digit at a time from the top, keeping every prefix whose outputs match
+// the end of the program, and checking each one with the actual interpreter.
+fn compute_2(contents: &str) -> Result<u64, QuineError> {
     let init_interpreter = parse_input(contents);
     let desired_outputs = init_interpreter.program.clone();
-    let mut nums: Vec<u64> = vec![0];
-    loop {
-        let mut curr_nums: Vec<u64> = vec![];
-        for mut num in nums.into_iter() {
-            num <<= 3;
-            for i in 0..8 {
+    check_loop_structure(&desired_outputs)?;
+    let mut prefixes: Vec<u64> = vec![0];
+    for num_digits in 1..=desired_outputs.len() {
+        let desired_suffix = &desired_outputs[desired_outputs.len() - num_digits..];
+        let mut next_prefixes = vec![];
+        for prefix in prefixes {
+            for digit in 0..8 {
+                let reg_a = (prefix << 3) + digit;
                 let mut interpreter = init_interpreter.clone();
-                interpreter.reg_a = num + i;
-                let outs = interpreter.run();
-                if outs.len() > desired_outputs.len() {
-                    panic!("Something has gone terribly wrong!");
-                }
-                if outs == desired_outputs {
-                    // nums is always sorted, so we can return on the first one
-                    return num + i;
-                }
-                if (!outs.is_empty())
-                    && desired_outputs[(desired_outputs.len() - outs.len())..] == outs
-                {
-                    curr_nums.push(num + i);
+                interpreter.reg_a = reg_a;
+                if interpreter.run().is_ok_and(|out| out == desired_suffix) {
+                    next_prefixes.push(reg_a);
                 }
             }
         }
-        nums = curr_nums;
-    }
-}
-
-// "Decompiled" program used for reasoning about what is happening.
-#[allow(dead_code)]
-fn program(mut a: u64) -> Vec<u64> {
-    let mut out = vec![];
-    loop {
-        // bst
-        let mut b = a & 0b_111;
-
-        // bxl
-        b ^= 0b_010;
-
-        // // cdv (with `& 0b_111` added)
-        // let c = (a >> b) & 0b_111;
-        //
-        // // bxc
-        // b ^= c;
-
-        // cdv + bxc
-        b ^= (a >> b) & 0b_111;
-        // bxl
-        b ^= 0b_011;
-        // out
-        out.push(b & 0b_111);
-        // adv
-        a >>= 3;
-        // jnz
-        if a == 0 {
-            break;
+        if next_prefixes.is_empty() {
+            return Err(QuineError::NoQuine);
         }
+        prefixes = next_prefixes;
     }
-    out
+    // The prefixes are built in increasing order, so the first is the smallest
+    Ok(prefixes[0])
 }
 
 pub(crate) struct Day {}
@@ -194,17 +599,16 @@ impl Problem for Day {
         file!().to_string()
     }
     fn solve1(&self, contents: &str) -> String {
-        format!(
-            "{}",
-            compute_1(contents)
-                .into_iter()
-                .map(|i| format!("{i}"))
-                .collect::<Vec<String>>()
-                .join(",")
-        )
+        match compute_1(contents) {
+            Ok(out) => format_program(&out),
+            Err(e) => e,
+        }
     }
     fn solve2(&self, contents: &str) -> String {
-        format!("{}", compute_2(contents))
+        match compute_2(contents) {
+            Ok(reg_a) => format!("{reg_a}"),
+            Err(e) => e.to_string(),
+        }
     }
     fn expected1(&self) -> String {
         "2,7,4,7,2,1,7,5,1".to_string()
@@ -212,4 +616,189 @@ impl Problem for Day {
     fn expected2(&self) -> String {
         "37221274271220".to_string()
     }
+    fn debug(
+        &self,
+        contents: &str,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), String> {
+        Debugger::new(parse_input(contents))
+            .run(input, output)
+            .map_err(|e| format!("Problem talking to the debugger: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(
+            compute_1(EXAMPLE).map(|out| format_program(&out)),
+            Ok("4,6,3,5,6,3,5,2,1,0".to_string())
+        );
+    }
+
+    fn with_program(program: &str) -> String {
+        format!("Register A: 2024\nRegister B: 0\nRegister C: 0\n\nProgram: {program}")
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(&with_program("0,3,5,4,3,0")), Ok(117440));
+        // A typical input, with the digits of A mixed together through B and C
+        let program = "2,4,1,2,7,5,4,7,1,3,5,5,0,3,3,0";
+        let reg_a = compute_2(&with_program(program)).unwrap();
+        let mut interpreter = parse_input(&with_program(program));
+        interpreter.reg_a = reg_a;
+        assert_eq!(format_program(&interpreter.run().unwrap()), program);
+    }
+
+    #[test]
+    fn test_compute_2_errors() {
+        // Only ever outputs 3
+        assert_eq!(
+            compute_2(&with_program("2,3,5,5,0,3,3,0")),
+            Err(QuineError::NoQuine)
+        );
+        assert_eq!(
+            compute_2(&with_program("0,1,5,4,3,0")),
+            Err(QuineError::NotShiftingBy3("found `adv 1`".to_string()))
+        );
+        assert_eq!(
+            compute_2(&with_program("0,3,5,4,0,3,3,0")),
+            Err(QuineError::NotShiftingBy3(
+                "A is shifted 2 times".to_string()
+            ))
+        );
+        assert_eq!(
+            compute_2(&with_program("0,3,5,4,5,4,3,0")),
+            Err(QuineError::OutputsPerLoop(2))
+        );
+        assert_eq!(
+            compute_2(&with_program("0,3,5,4,3,2")),
+            Err(QuineError::NotALoop)
+        );
+        // B is carried over from the previous loop, so outputs depend on more than A
+        assert_eq!(
+            compute_2(&with_program("1,1,5,5,0,3,3,0")),
+            Err(QuineError::CarriesRegister('B', "bxl 1".to_string()))
+        );
+        // C is only set after it's read
+        assert_eq!(
+            compute_2(&with_program("2,4,4,0,7,4,5,5,0,3,3,0")),
+            Err(QuineError::CarriesRegister('C', "bxc 0".to_string()))
+        );
+        assert_eq!(
+            QuineError::CarriesRegister('B', "bxl 1".to_string()).to_string(),
+            "The program reads B in `bxl 1` before setting it in the loop"
+        );
+        assert_eq!(
+            QuineError::NoQuine.to_string(),
+            "No value of A makes the program output itself"
+        );
+    }
+
+    #[test]
+    fn test_assembler() {
+        let program = vec![2, 4, 1, 2, 7, 5, 4, 7, 1, 3, 5, 5, 0, 3, 3, 0];
+        let listing = disassemble(&program);
+        assert_eq!(
+            listing.lines().take(4).collect::<Vec<&str>>(),
+            vec!["0: bst a", "2: bxl 2", "4: cdv b", "6: bxc 7"]
+        );
+        assert_eq!(assemble(&listing), Ok(program));
+        assert_eq!(
+            assemble("adv 3 ; a /= 8\n\nbxc\nout a\njnz 0").map(|p| format_program(&p)),
+            Ok("0,3,4,0,5,4,3,0".to_string())
+        );
+        assert_eq!(
+            assemble("adv 3\nmul 2"),
+            Err("Line 2: Unknown instruction \"mul\"".to_string())
+        );
+        // Only combo operands can be registers
+        assert!(assemble("bxl a").is_err());
+        assert!(assemble("bst 8").is_err());
+        assert!(assemble("out 7").is_err());
+        assert_eq!(assemble("bxl 7"), Ok(vec![1, 7]));
+    }
+
+    #[test]
+    fn test_debugger() {
+        let mut input = "b 4\nc\nr\nstep 2\nb 4\nc\nreset\npatch 2 out 2\nc\nq\nr\n".as_bytes();
+        let mut output = vec![];
+        Debugger::new(parse_input(EXAMPLE))
+            .run(&mut input, &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.split("> ").collect();
+        assert_eq!(lines[0], "ip=0 a=729 b=0 c=0\n");
+        assert_eq!(lines[1], "Added breakpoint at 4\n");
+        assert_eq!(lines[2], "Breakpoint at 4\n");
+        assert_eq!(lines[3], "ip=4 a=364 b=0 c=0\n");
+        assert_eq!(
+            lines[4],
+            "  4: jnz 0   ip=0 a=364 b=0 c=0\n  0: adv 1   ip=2 a=182 b=0 c=0\n"
+        );
+        assert_eq!(lines[5], "Removed breakpoint at 4\n");
+        assert_eq!(lines[6], "Halted with output 4,6,3,5,6,3,5,2,1,0\n");
+        assert_eq!(lines[7], "ip=0 a=729 b=0 c=0\n");
+        assert_eq!(lines[8], "2: out 2\n");
+        assert_eq!(lines[9], "Halted with output 2,2,2,2,2,2,2,2,2,2\n");
+        // Nothing after quitting is run
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[10], "");
+    }
+
+    #[test]
+    fn test_debugger_bad_input() {
+        // Shifting by the width of a register or more clears it
+        let mut input = "patch 2 out 7\nset a 5\nset b 64\npatch 0 cdv b\nstep\n".as_bytes();
+        let mut output = vec![];
+        Debugger::new(parse_input(EXAMPLE))
+            .run(&mut input, &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.split("> ").collect();
+        assert_eq!(lines[1], "Line 1: Invalid operand \"7\"\n");
+        assert_eq!(lines[4], "0: cdv b\n");
+        assert_eq!(lines[5], "  0: cdv b   ip=2 a=5 b=64 c=0\n");
+    }
+
+    #[test]
+    fn test_debugger_stuck() {
+        // The program never halts, and its second instruction has an invalid combo operand
+        let interpreter = Interpreter {
+            program: vec![3, 0, 5, 7],
+            instruction_pointer: 0,
+            reg_a: 1,
+            reg_b: 0,
+            reg_c: 0,
+        };
+        let mut input = "set ip 1\nlimit 10\nc\nset a 0\ns 2\nr\n".as_bytes();
+        let mut output = vec![];
+        Debugger::new(interpreter)
+            .run(&mut input, &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.split("> ").collect();
+        assert_eq!(
+            lines[1],
+            "The instruction pointer has to be even\nip=0 a=1 b=0 c=0\n"
+        );
+        assert_eq!(lines[2], "Continue stops after 10 steps\n");
+        assert_eq!(lines[3], "Stopped after 10 steps\n");
+        assert_eq!(
+            lines[5],
+            "  0: jnz 0   ip=2 a=0 b=0 c=0\nInvalid combo operand 7 at ip 2\n"
+        );
+        assert_eq!(lines[6], "ip=2 a=0 b=0 c=0\n");
+    }
 }
@@ -1,6 +1,6 @@
 use std::collections::{HashSet, VecDeque};
 
-use shared::Problem;
+use shared::{Problem, dsu::UnionFind};
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 struct Point {
@@ -97,61 +97,36 @@ fn compute_1(contents: &str) -> u64 {
 
 fn compute_2(contents: &str) -> Option<Point> {
     let falling_ram = parse_input(contents);
+    let index = |point: Point| point.i * (BOUNDS + 1) + point.j;
 
     // Fill out the connected components as they exist at the end.
-    type ConnectedComponentId = u16; // u16 is enough b/c 70^2 < 2^16
-    let mut connected_component = [[ConnectedComponentId::MAX; BOUNDS + 1]; BOUNDS + 1];
-    let mut curr_component_id = 0;
+    let mut blocked = [[false; BOUNDS + 1]; BOUNDS + 1];
     for ram_point in falling_ram.iter() {
-        connected_component[ram_point.i][ram_point.j] = curr_component_id;
-        curr_component_id += 1;
+        blocked[ram_point.i][ram_point.j] = true;
     }
+    let mut components = UnionFind::new((BOUNDS + 1).pow(2));
+    let mut connect_to_neighbors = |point: Point, blocked: &[[bool; BOUNDS + 1]; BOUNDS + 1]| {
+        for neighbor in point.neighbors().into_iter().filter_map(checked_adjustment) {
+            if !blocked[neighbor.i][neighbor.j] {
+                components.union(index(point), index(neighbor));
+            }
+        }
+        components.connected(index(START), index(END))
+    };
     for i in 0..=BOUNDS {
         for j in 0..=BOUNDS {
-            let query_point = Point { i, j };
-            if connected_component[query_point.i][query_point.j] == ConnectedComponentId::MAX {
-                // Haven't found connected component for this point yet.
-                // Fill it out using DFS.
-                let mut visited: HashSet<Point> =
-                    HashSet::from_iter(falling_ram.clone().into_iter());
-                let mut to_visit = vec![query_point];
-                while let Some(point) = to_visit.pop() {
-                    if visited.contains(&point) {
-                        continue;
-                    }
-                    visited.insert(point);
-
-                    connected_component[point.i][point.j] = curr_component_id;
-                    for neighbor in point.neighbors().into_iter().filter_map(checked_adjustment) {
-                        if !visited.contains(&neighbor) {
-                            to_visit.push(neighbor);
-                        }
-                    }
-                }
-                curr_component_id += 1;
+            if !blocked[i][j] {
+                connect_to_neighbors(Point { i, j }, &blocked);
             }
         }
     }
 
     // Iterate backwards over the RAM, re-connecting components that should
     // now be connected when we remove that particular RAM block.
-    for ram_index in (0..falling_ram.len()).rev() {
-        let point = falling_ram[ram_index];
-        let point_connected_component_id = connected_component[point.i][point.j];
-        for neighbor in point.neighbors().into_iter().filter_map(checked_adjustment) {
-            if !falling_ram[..ram_index].contains(&neighbor) {
-                let neighbor_connected_component_id = connected_component[neighbor.i][neighbor.j];
-                for connected_component_i in connected_component.iter_mut() {
-                    for connected_component_ij in connected_component_i.iter_mut() {
-                        if connected_component_ij == &neighbor_connected_component_id {
-                            *connected_component_ij = point_connected_component_id;
-                        }
-                    }
-                }
-            }
-        }
-        if connected_component[START.i][START.j] == connected_component[END.i][END.j] {
-            return Some(falling_ram[ram_index]);
+    for point in falling_ram.into_iter().rev() {
+        blocked[point.i][point.j] = false;
+        if connect_to_neighbors(point, &blocked) {
+            return Some(point);
         }
     }
     None
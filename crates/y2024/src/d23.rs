@@ -1,83 +1,32 @@
 use std::collections::{HashMap, HashSet};
 
 use shared::Problem;
+use shared::graph::cliques;
 
-type Name = [char; 2];
-
-fn parse_input(contents: &str) -> HashMap<Name, HashSet<Name>> {
-    let mut connections: HashMap<Name, HashSet<Name>> = HashMap::new();
+fn parse_input(contents: &str) -> HashMap<String, HashSet<String>> {
+    let mut connections: HashMap<String, HashSet<String>> = HashMap::new();
     contents.trim().split('\n').for_each(|line| {
         let (first, second) = line.split_once('-').unwrap();
-        let first = first.chars().collect::<Vec<char>>().try_into().unwrap();
-        let second = second.chars().collect::<Vec<char>>().try_into().unwrap();
-        connections.entry(first).or_default().insert(second);
-        connections.entry(second).or_default().insert(first);
+        connections
+            .entry(first.to_string())
+            .or_default()
+            .insert(second.to_string());
     });
     connections
 }
 
 fn compute_1(contents: &str) -> usize {
     let connections = parse_input(contents);
-    let computer_names: Vec<Name> = connections.keys().copied().collect();
-    let mut total = 0;
-    for comp0 in computer_names.iter() {
-        let comp0_neighbors = &connections[comp0];
-        for comp1 in comp0_neighbors {
-            // Uniqueness check: Only count each triangle once
-            if comp1 < comp0 {
-                continue;
-            }
-            let comp1_neighbors = &connections[comp1];
-            for comp2 in comp1_neighbors {
-                // Uniqueness check: Only count each triangle once
-                if comp2 < comp1 {
-                    continue;
-                }
-                if (comp0[0] == 't' || comp1[0] == 't' || comp2[0] == 't')
-                    && comp1_neighbors.contains(comp2)
-                    && comp0_neighbors.contains(comp2)
-                {
-                    total += 1;
-                }
-            }
-        }
-    }
-    total
+    cliques::k_cliques(&connections, 3)
+        .into_iter()
+        .filter(|triangle| triangle.iter().any(|name| name.starts_with('t')))
+        .count()
 }
 
 fn compute_2(contents: &str) -> String {
     let connections = parse_input(contents);
-    let mut largest_observed_kn = 0;
-    let mut largest_observed_k: Vec<Name> = vec![];
-    let computer_names: Vec<Name> = connections.keys().copied().collect();
-    for comp0 in computer_names.iter() {
-        let mut neighbors_of_current_clique = connections[comp0].clone();
-        let mut clique: Vec<&Name> = vec![comp0];
-        // This is the greedy algorithm for finding maximal (not maximUM!) cliques.
-        // It is not at all guaranteed to work! But it does for the example and my input.
-        for comp in computer_names.iter() {
-            if clique.contains(&comp) {
-                continue;
-            }
-            if neighbors_of_current_clique.contains(comp) {
-                clique.push(comp);
-                neighbors_of_current_clique = neighbors_of_current_clique
-                    .intersection(&connections[comp])
-                    .copied()
-                    .collect();
-            }
-        }
-        if clique.len() > largest_observed_kn {
-            largest_observed_kn = clique.len();
-            largest_observed_k = clique.into_iter().copied().collect();
-        }
-    }
-    largest_observed_k.sort();
-    largest_observed_k
-        .iter()
-        .map(|name| format!("{}{}", name[0], name[1]))
-        .collect::<Vec<String>>()
-        .join(",")
+    // The clique comes back sorted, which is what the password needs.
+    cliques::maximum_clique(&connections).join(",")
 }
 
 pub(crate) struct Day {}
@@ -99,3 +48,51 @@ impl Problem for Day {
         "ej,hm,ks,ms,ns,rb,rq,sc,so,un,vb,vd,wd".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 7);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), "co,de,ka,ta");
+    }
+}
@@ -1,10 +1,42 @@
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, Write};
 
 use shared::Problem;
 
+mod circuit;
+mod netlist;
+
 type Id = [char; 3];
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The most bits the numbers being added can have, so that their sum fits in a `u64` (and
+/// every bit has a two digit wire name).
+const MAX_INPUT_BITS: usize = 63;
+
+/// Make sure there are inputs to add, and that they aren't too big to simulate.
+fn check_num_bits(num_bits: usize) -> Result<(), String> {
+    if num_bits == 0 {
+        return Err("The circuit doesn't have any inputs".to_string());
+    }
+    if num_bits > MAX_INPUT_BITS {
+        return Err(format!(
+            "The inputs have {num_bits} bits, but at most {MAX_INPUT_BITS} are supported"
+        ));
+    }
+    Ok(())
+}
+
+/// The name of a wire for one bit of x, y or z. Only bits up to 99 have names.
+fn wire(prefix: char, bit: usize) -> Id {
+    assert!(bit < 100, "Bit {bit} doesn't have a wire name");
+    let digit = |n: usize| char::from_digit((n % 10) as u32, 10).unwrap();
+    [prefix, digit(bit / 10), digit(bit)]
+}
+
+fn wire_name(id: &Id) -> String {
+    id.iter().collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Operation {
     And,
     Or,
@@ -66,98 +98,219 @@ fn parse_input(contents: &str) -> (HashMap<Id, Option<bool>>, VecDeque<Gate>, Ve
     (wires, gates, output_wires)
 }
 
+/// Run the gates until every output is known. Returns `None` if some gates can never run,
+/// because they're in a loop or have an input that nothing drives.
 fn run_gates(
     wires: &mut HashMap<Id, Option<bool>>,
     gates: &mut VecDeque<Gate>,
     output_wires: &[Id],
-) -> u64 {
+) -> Option<u64> {
+    // The number of gates that have been put back since one last ran
+    let mut num_waiting = 0;
     while !gates.is_empty() {
+        if num_waiting > gates.len() {
+            return None;
+        }
         let gate = gates.pop_front().unwrap();
         let mut should_put_back = true;
-        if let Some(wire1) = wires.get(&gate.in1).unwrap() {
-            if let Some(wire2) = wires.get(&gate.in2).unwrap() {
-                should_put_back = false;
-                match gate.op {
-                    Operation::And => wires.insert(gate.out, Some(wire1 & wire2)),
-                    Operation::Or => wires.insert(gate.out, Some(wire1 | wire2)),
-                    Operation::Xor => wires.insert(gate.out, Some(wire1 ^ wire2)),
-                };
-            }
+        if let Some(Some(wire1)) = wires.get(&gate.in1)
+            && let Some(Some(wire2)) = wires.get(&gate.in2)
+        {
+            should_put_back = false;
+            match gate.op {
+                Operation::And => wires.insert(gate.out, Some(wire1 & wire2)),
+                Operation::Or => wires.insert(gate.out, Some(wire1 | wire2)),
+                Operation::Xor => wires.insert(gate.out, Some(wire1 ^ wire2)),
+            };
         }
         if should_put_back {
             gates.push_back(gate);
+            num_waiting += 1;
+        } else {
+            num_waiting = 0;
         }
     }
     let mut n = 0;
     for id in output_wires.iter().rev() {
         n <<= 1;
-        n += wires.get(id).unwrap().unwrap() as u64;
+        n += wires.get(id).copied().flatten()? as u64;
     }
-    n
+    Some(n)
 }
 
-fn compute_1(contents: &str) -> u64 {
-    let (mut wires, mut gates, output_wires) = parse_input(contents);
-    run_gates(&mut wires, &mut gates, &output_wires)
+/// Run the circuit with `x` and `y` as the inputs, returning `z`.
+fn simulate(gates: &[Gate], num_bits: usize, x: u64, y: u64) -> Option<u64> {
+    let mut wires: HashMap<Id, Option<bool>> = HashMap::new();
+    for bit in 0..num_bits {
+        wires.insert(wire('x', bit), Some(x >> bit & 1 == 1));
+        wires.insert(wire('y', bit), Some(y >> bit & 1 == 1));
+    }
+    let mut output_wires: Vec<Id> = gates
+        .iter()
+        .map(|gate| gate.out)
+        .filter(|out| out[0] == 'z')
+        .collect();
+    output_wires.sort();
+    run_gates(
+        &mut wires,
+        &mut gates.iter().cloned().collect(),
+        &output_wires,
+    )
 }
 
-fn compute_2(contents: &str) -> String {
-    let (_, gates, output_wires) = parse_input(contents);
+/// Add `x` and `y` with the circuit, describing how it went.
+fn describe_sum(gates: &[Gate], num_bits: usize, x: u64, y: u64) -> Result<String, String> {
+    check_num_bits(num_bits)?;
+    if [x, y].iter().any(|n| n >> num_bits != 0) {
+        return Err(format!("The inputs only have {num_bits} bits"));
+    }
+    let sum = x
+        .checked_add(y)
+        .ok_or_else(|| format!("{x} + {y} doesn't fit in 64 bits"))?;
+    match simulate(gates, num_bits, x, y) {
+        Some(z) if z == sum => Ok(format!("{x} + {y} = {z}")),
+        Some(z) => Ok(format!(
+            "{x} + {y} = {z}, but should be {sum} (bit {} is wrong)",
+            (z ^ sum).trailing_zeros()
+        )),
+        None => Err("The gates never settle".to_string()),
+    }
+}
 
-    // Check for consistency against a ripple carry adder.
-    // https://en.wikipedia.org/wiki/Adder_(electronics)#Ripple-carry_adder
-    // Ripple Carry Adders are constructed from one Half Adder
-    // and many Full Adders (and the final set of logic at the end)
+/// The number of bits in each of the numbers being added.
+fn num_input_bits(gates: &[Gate]) -> usize {
+    let inputs: HashSet<&Id> = gates
+        .iter()
+        .flat_map(|gate| [&gate.in1, &gate.in2])
+        .filter(|id| id[0] == 'x')
+        .collect();
+    inputs.len()
+}
 
-    let mut bad_gates: HashSet<&Gate> = HashSet::new();
-    for gate in &gates {
-        let is_output_bit = gate.out[0] == 'z';
-        let is_half_adder = gate.in1 == ['x', '0', '0'] || gate.in2 == ['x', '0', '0'];
-        let takes_inputs = gate.in1[0] == 'x' || gate.in1[0] == 'y';
+fn compute_1(contents: &str) -> u64 {
+    let (mut wires, mut gates, output_wires) = parse_input(contents);
+    run_gates(&mut wires, &mut gates, &output_wires).expect("Some gates never ran")
+}
 
-        // Every output value except the last should result from an XOR
-        if is_output_bit
-            && (&gate.out != output_wires.last().unwrap())
-            && (gate.op != Operation::Xor)
-        {
-            bad_gates.insert(gate);
-        }
+fn compute_2(contents: &str) -> Result<String, String> {
+    let (_, gates, _) = parse_input(contents);
+    let gates: Vec<Gate> = gates.into_iter().collect();
+    let repair = circuit::repair(&gates, num_input_bits(&gates))?;
+    let mut out: Vec<String> = repair
+        .swaps
+        .iter()
+        .flat_map(|swap| [wire_name(&swap.wires.0), wire_name(&swap.wires.1)])
+        .collect();
+    out.sort();
+    Ok(out.join(","))
+}
 
-        // The first (half) adder should be constructed out of AND and XOR
-        if is_half_adder && (gate.op == Operation::Or) {
-            bad_gates.insert(gate);
-        }
+const DEBUGGER_HELP: &str = "Commands:
+  add <x> <y>      run the circuit on x and y, and check the sum
+  swap <a> <b>     swap the outputs of the gates driving wires a and b
+  repair           find (and make) the swaps that turn the circuit into an adder
+  role <wire>      show what the gate driving a wire does in the adder
+  reset            undo every swap
+  dot <file>       save the circuit as a Graphviz graph
+  verilog <file>   save the circuit as a Verilog module
+  load <file>      replace the circuit with the gates from a Verilog netlist
+  h, help          show this message
+  q, quit          stop debugging
+";
 
-        if gate.op == Operation::Xor {
-            // Any XOR must be either connected to two inputs or an output
-            if !(takes_inputs || is_output_bit) {
-                bad_gates.insert(gate);
+/// Try out the circuit interactively, reading one command per line from `input`.
+fn debug(contents: &str, input: &mut dyn BufRead, output: &mut dyn Write) -> std::io::Result<()> {
+    let (_, initial, _) = parse_input(contents);
+    let mut initial: Vec<Gate> = initial.into_iter().collect();
+    let mut num_bits = num_input_bits(&initial);
+    let mut gates = initial.clone();
+    let parse_wire =
+        |name: &str| -> Option<Id> { name.chars().collect::<Vec<char>>().try_into().ok() };
+    let mut line = String::new();
+    loop {
+        write!(output, "> ")?;
+        output.flush()?;
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {}
+            ["add", x, y] => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => match describe_sum(&gates, num_bits, x, y) {
+                    Ok(description) | Err(description) => writeln!(output, "{description}")?,
+                },
+                _ => writeln!(output, "Can't parse {x:?} and {y:?} as numbers")?,
+            },
+            ["swap", a, b] => {
+                let result = match (parse_wire(a), parse_wire(b)) {
+                    (Some(a), Some(b)) => circuit::swap_outputs(&mut gates, &a, &b),
+                    _ => Err("Wire names have three characters".to_string()),
+                };
+                match result {
+                    Ok(()) => writeln!(output, "Swapped {a} and {b}")?,
+                    Err(e) => writeln!(output, "{e}")?,
+                }
             }
-
-            // Any XOR only feeds into AND and XOR
-            for gate2 in &gates {
-                if (gate.out == gate2.in1 || gate.out == gate2.in2) && gate2.op == Operation::Or {
-                    bad_gates.insert(gate);
+            ["repair"] => match circuit::repair(&gates, num_bits) {
+                Ok(repair) => {
+                    write!(output, "{}", repair.report())?;
+                    for swap in &repair.swaps {
+                        circuit::swap_outputs(&mut gates, &swap.wires.0, &swap.wires.1).unwrap();
+                    }
+                }
+                Err(e) => writeln!(output, "Couldn't repair the circuit: {e}")?,
+            },
+            ["role", name] => {
+                let role = parse_wire(name).and_then(|id| {
+                    circuit::repair(&gates, num_bits)
+                        .ok()
+                        .and_then(|repair| repair.roles.get(&id).copied())
+                });
+                match role {
+                    Some(role) => writeln!(output, "{name}: {role}")?,
+                    None => writeln!(output, "{name} has no role in the repaired adder")?,
                 }
             }
-        }
-
-        // Any AND must only feed into OR (for the full adders)
-        if gate.op == Operation::And && !is_half_adder {
-            for gate2 in &gates {
-                if (gate.out == gate2.in1 || gate.out == gate2.in2) && gate2.op != Operation::Or {
-                    bad_gates.insert(gate);
+            ["reset"] => {
+                gates = initial.clone();
+                writeln!(output, "Undid every swap")?;
+            }
+            ["dot" | "verilog", path] => {
+                let contents = if words[0] == "dot" {
+                    netlist::to_dot(&gates)
+                } else {
+                    netlist::to_verilog(&gates, "circuit")
+                };
+                match std::fs::write(path, contents) {
+                    Ok(()) => writeln!(output, "Saved {path}")?,
+                    Err(e) => writeln!(output, "Couldn't save {path}: {e}")?,
                 }
             }
+            ["load", path] => {
+                let loaded = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Couldn't read {path}: {e}"))
+                    .and_then(|source| netlist::from_verilog(&source))
+                    .and_then(|loaded| {
+                        check_num_bits(num_input_bits(&loaded))?;
+                        Ok(loaded)
+                    });
+                match loaded {
+                    Ok(loaded) => {
+                        initial = loaded;
+                        gates = initial.clone();
+                        num_bits = num_input_bits(&gates);
+                        writeln!(output, "Loaded {} gates", gates.len())?;
+                    }
+                    Err(e) => writeln!(output, "{e}")?,
+                }
+            }
+            ["h" | "help"] => write!(output, "{DEBUGGER_HELP}")?,
+            ["q" | "quit"] => return Ok(()),
+            _ => writeln!(output, "Unknown command {:?}, try \"help\"", line.trim())?,
         }
     }
-
-    let mut out: Vec<String> = bad_gates
-        .iter()
-        .map(|g| format!("{}{}{}", g.out[0], g.out[1], g.out[2]))
-        .collect();
-    out.sort();
-    out.join(",")
 }
 
 pub(crate) struct Day {}
@@ -170,7 +323,7 @@ impl Problem for Day {
         format!("{}", compute_1(contents))
     }
     fn solve2(&self, contents: &str) -> String {
-        format!("{}", compute_2(contents))
+        compute_2(contents).unwrap_or_else(|e| e)
     }
     fn expected1(&self) -> String {
         "53190357879014".to_string()
@@ -178,4 +331,110 @@ impl Problem for Day {
     fn expected2(&self) -> String {
         "bks,hnd,nrn,tdv,tjp,z09,z16,z23".to_string()
     }
+    fn debug(
+        &self,
+        contents: &str,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<(), String> {
+        debug(contents, input, output).map_err(|e| format!("Problem talking to the debugger: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02";
+
+    /// A 4 bit adder, with z01 and z02 swapped with carries.
+    const BROKEN_ADDER: &str = "x00: 0
+x01: 0
+x02: 0
+x03: 0
+y00: 0
+y01: 0
+y02: 0
+y03: 0
+
+x00 XOR y00 -> z00
+x00 AND y00 -> cab
+x01 XOR y01 -> sab
+x01 AND y01 -> gab
+sab XOR cab -> pab
+sab AND cab -> z01
+gab OR pab -> cac
+x02 XOR y02 -> sac
+x02 AND y02 -> gac
+sac XOR cac -> cad
+sac AND cac -> pac
+gac OR pac -> z02
+x03 XOR y03 -> sad
+x03 AND y03 -> gad
+sad XOR cad -> z03
+sad AND cad -> pad
+gad OR pad -> z04";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 4);
+    }
+
+    #[test]
+    fn test_compute_2() {
+        assert_eq!(compute_2(BROKEN_ADDER), Ok("cad,pab,z01,z02".to_string()));
+    }
+
+    #[test]
+    fn test_debug_files() {
+        // Named after the process so that test runs at the same time don't clash
+        let path = std::env::temp_dir().join(format!(
+            "y2024_d24_test_debug_files_{}.v",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        let commands = format!("add 1 1\nrepair\nverilog {path}\nreset\nload {path}\nadd 1 1\n");
+        let mut output = vec![];
+        debug(BROKEN_ADDER, &mut commands.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.split("\n> ").collect();
+        assert_eq!(lines[0], "> 1 + 1 = 8, but should be 2 (bit 1 is wrong)");
+        assert_eq!(lines[2], format!("Saved {path}"));
+        assert_eq!(lines[4], "Loaded 17 gates");
+        // The repaired circuit was saved
+        assert_eq!(lines[5], "1 + 1 = 2");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut input =
+            "add 1 1\nrepair\nadd 15 1\nrole z02\nbogus\nadd 16 0\nadd 18446744073709551615 1\nadd 15 15\nq\n"
+                .as_bytes();
+        let mut output = vec![];
+        debug(BROKEN_ADDER, &mut input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.split("\n> ").collect();
+        assert_eq!(lines[0], "> 1 + 1 = 8, but should be 2 (bit 1 is wrong)");
+        assert_eq!(
+            lines[1],
+            "pab <-> z01 fixes bit 1 (without it, bit 1 of the sum is wrong)\n\
+             cad <-> z02 fixes bit 2 (without it, bit 2 of the sum is wrong)"
+        );
+        assert_eq!(lines[2], "15 + 1 = 16");
+        assert_eq!(lines[3], "z02: sum of bit 2 (partial sum XOR carry in)");
+        assert_eq!(lines[4], "Unknown command \"bogus\", try \"help\"");
+        assert_eq!(lines[5], "The inputs only have 4 bits");
+        assert_eq!(lines[6], "The inputs only have 4 bits");
+        assert_eq!(lines[7], "15 + 15 = 30");
+    }
 }
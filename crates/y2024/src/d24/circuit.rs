@@ -0,0 +1,411 @@
+//! Checking a circuit against a ripple-carry adder, and repairing it when pairs of gates have
+//! had their outputs swapped.
+//! https://en.wikipedia.org/wiki/Adder_(electronics)#Ripple-carry_adder
+//!
+//! Bit 0 is a half adder, and every other bit `i` is a full adder made of five gates:
+//!
+//! ```text
+//! x_i XOR y_i         -> s_i      (partial sum)
+//! x_i AND y_i         -> g_i      (carry generated by the inputs)
+//! s_i XOR c_(i-1)     -> z_i      (sum)
+//! s_i AND c_(i-1)     -> p_i      (carry propagated from the bit below)
+//! g_i OR p_i          -> c_i      (carry out)
+//! ```
+//!
+//! The carry out of the top bit is the extra output bit. Only outputs get swapped, so the gates
+//! reading `x` and `y` are always where they should be, and the rest of the adder can be
+//! rebuilt from them one bit at a time. Whenever a gate that should exist doesn't, the wires
+//! it does find point at which outputs must have been swapped.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{Gate, Id, Operation, check_num_bits, simulate, wire, wire_name};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Role {
+    PartialSum(usize),
+    GeneratedCarry(usize),
+    Sum(usize),
+    PropagatedCarry(usize),
+    CarryOut(usize),
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::PartialSum(0) => write!(f, "sum of bit 0 (x00 XOR y00)"),
+            Role::GeneratedCarry(0) => write!(f, "carry out of bit 0 (x00 AND y00)"),
+            Role::PartialSum(i) => write!(f, "partial sum of bit {i} (x XOR y)"),
+            Role::GeneratedCarry(i) => write!(f, "carry generated at bit {i} (x AND y)"),
+            Role::Sum(i) => write!(f, "sum of bit {i} (partial sum XOR carry in)"),
+            Role::PropagatedCarry(i) => {
+                write!(
+                    f,
+                    "carry propagated through bit {i} (partial sum AND carry in)"
+                )
+            }
+            Role::CarryOut(i) => write!(f, "carry out of bit {i}"),
+        }
+    }
+}
+
+/// A pair of wires whose gates had their outputs swapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Swap {
+    pub wires: (Id, Id),
+    /// The bit of the adder that was being rebuilt when the swap was found.
+    pub bit: usize,
+    /// The lowest bit of the sum that's wrong when every swap except this one is made.
+    pub first_wrong_bit: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Repair {
+    pub swaps: Vec<Swap>,
+    /// The role of the gate driving each wire, once the swaps have been made.
+    pub roles: HashMap<Id, Role>,
+}
+
+impl Repair {
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        for swap in &self.swaps {
+            report += &format!(
+                "{} <-> {} fixes bit {} (without it, bit {} of the sum is wrong)\n",
+                wire_name(&swap.wires.0),
+                wire_name(&swap.wires.1),
+                swap.bit,
+                swap.first_wrong_bit,
+            );
+        }
+        if self.swaps.is_empty() {
+            report += "The circuit is already an adder\n";
+        }
+        report
+    }
+}
+
+/// Swap the outputs of the gates driving wires `a` and `b`.
+pub(super) fn swap_outputs(gates: &mut [Gate], a: &Id, b: &Id) -> Result<(), String> {
+    let driver = |wire: &Id| {
+        gates
+            .iter()
+            .position(|gate| &gate.out == wire)
+            .ok_or_else(|| format!("No gate drives wire {}", wire_name(wire)))
+    };
+    let (i, j) = (driver(a)?, driver(b)?);
+    gates[i].out = *b;
+    gates[j].out = *a;
+    Ok(())
+}
+
+fn find<'a>(gates: &'a [Gate], op: Operation, a: &Id, b: &Id) -> Option<&'a Gate> {
+    gates.iter().find(|gate| {
+        gate.op == op && ((&gate.in1, &gate.in2) == (a, b) || (&gate.in1, &gate.in2) == (b, a))
+    })
+}
+
+/// The other input of a gate doing `op` with `a` as one of its inputs.
+fn other_input(gates: &[Gate], op: Operation, a: &Id) -> Option<Id> {
+    gates.iter().find_map(|gate| {
+        if gate.op != op {
+            None
+        } else if &gate.in1 == a {
+            Some(gate.in2)
+        } else if &gate.in2 == a {
+            Some(gate.in1)
+        } else {
+            None
+        }
+    })
+}
+
+/// Why the circuit isn't an adder (yet).
+enum Problem {
+    /// Swapping these wires should get further.
+    Swap(Id, Id, usize),
+    Unfixable(String),
+}
+
+/// Find the gate combining `a` and `b` with `op`. If there isn't one, then (assuming only one of
+/// them is on the wrong wire, and checking `b` first) propose a swap that would make one.
+fn find_pair(
+    gates: &[Gate],
+    op: Operation,
+    (a, b): (&Id, &Id),
+    bit: usize,
+) -> Result<Gate, Problem> {
+    if let Some(gate) = find(gates, op, a, b) {
+        return Ok(gate.clone());
+    }
+    // If one of the wires is there, the other input of its gate is where the other one
+    // should have gone.
+    if let Some(other) = other_input(gates, op, b) {
+        return Err(Problem::Swap(*a, other, bit));
+    }
+    if let Some(other) = other_input(gates, op, a) {
+        return Err(Problem::Swap(*b, other, bit));
+    }
+    Err(Problem::Unfixable(format!(
+        "Bit {bit} has no {op:?} gate reading {} or {}",
+        wire_name(a),
+        wire_name(b)
+    )))
+}
+
+/// Rebuild the adder, returning each gate's role (by output wire).
+fn analyse(gates: &[Gate], num_bits: usize) -> Result<HashMap<Id, Role>, Problem> {
+    let mut roles = HashMap::new();
+    let mut carry: Option<Id> = None;
+    for bit in 0..num_bits {
+        let (x, y, z) = (wire('x', bit), wire('y', bit), wire('z', bit));
+        let missing = |op: &str| {
+            Problem::Unfixable(format!(
+                "There's no {op} gate reading {} and {}",
+                wire_name(&x),
+                wire_name(&y)
+            ))
+        };
+        let partial_sum = find(gates, Operation::Xor, &x, &y)
+            .ok_or_else(|| missing("XOR"))?
+            .out;
+        let generated = find(gates, Operation::And, &x, &y)
+            .ok_or_else(|| missing("AND"))?
+            .out;
+        roles.insert(partial_sum, Role::PartialSum(bit));
+        roles.insert(generated, Role::GeneratedCarry(bit));
+
+        let Some(carry_in) = carry else {
+            if partial_sum != z {
+                return Err(Problem::Swap(partial_sum, z, bit));
+            }
+            carry = Some(generated);
+            continue;
+        };
+        let sum = find_pair(gates, Operation::Xor, (&partial_sum, &carry_in), bit)?;
+        if sum.out != z {
+            return Err(Problem::Swap(sum.out, z, bit));
+        }
+        roles.insert(sum.out, Role::Sum(bit));
+        let propagated = find_pair(gates, Operation::And, (&partial_sum, &carry_in), bit)?;
+        roles.insert(propagated.out, Role::PropagatedCarry(bit));
+        let carry_out = find_pair(gates, Operation::Or, (&generated, &propagated.out), bit)?;
+        roles.insert(carry_out.out, Role::CarryOut(bit));
+        carry = Some(carry_out.out);
+    }
+    if let Some(carry) = carry {
+        let top = wire('z', num_bits);
+        if carry != top {
+            return Err(Problem::Swap(carry, top, num_bits - 1));
+        }
+    }
+    if let Some(gate) = gates.iter().find(|gate| !roles.contains_key(&gate.out)) {
+        return Err(Problem::Unfixable(format!(
+            "The gate driving {} isn't part of the adder",
+            wire_name(&gate.out)
+        )));
+    }
+    Ok(roles)
+}
+
+/// A small pseudorandom number generator, so that the operands are the same on every run.
+/// https://en.wikipedia.org/wiki/Xorshift (the splitmix64 variant)
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// The lowest bit of the sum that the circuit gets wrong, trying random operands as well as
+/// ones that carry through every bit. `None` if it always adds correctly. `num_bits` has to
+/// be between 1 and 64.
+pub(super) fn first_wrong_bit(gates: &[Gate], num_bits: usize) -> Option<usize> {
+    let mask = u64::MAX >> (64 - num_bits);
+    let mut operands = vec![(0, 0), (mask, mask), (mask, 1)];
+    for bit in 0..num_bits {
+        operands.extend([(1 << bit, 0), (0, 1 << bit), (1 << bit, 1 << bit)]);
+        operands.push(((1 << bit) - 1, 1));
+    }
+    let mut rng = SplitMix64(num_bits as u64);
+    for _ in 0..100 {
+        operands.push((rng.next() & mask, rng.next() & mask));
+    }
+    operands
+        .into_iter()
+        .filter_map(
+            |(x, y)| match (simulate(gates, num_bits, x, y), x.checked_add(y)) {
+                (Some(z), Some(sum)) if z == sum => None,
+                (Some(z), Some(sum)) => Some((z ^ sum).trailing_zeros() as usize),
+                // The carry out of the top bit doesn't fit in z
+                (Some(_), None) => Some(num_bits),
+                // The gates never settle, so nothing can be trusted
+                (None, _) => Some(0),
+            },
+        )
+        .min()
+}
+
+/// Find the swaps that turn the circuit into an adder of two `num_bits` bit numbers, and
+/// check them by simulating the repaired circuit.
+pub(super) fn repair(gates: &[Gate], num_bits: usize) -> Result<Repair, String> {
+    check_num_bits(num_bits)?;
+    let mut repaired = gates.to_vec();
+    let mut found: Vec<(Id, Id, usize)> = vec![];
+    let roles = loop {
+        match analyse(&repaired, num_bits) {
+            Ok(roles) => break roles,
+            Err(Problem::Unfixable(reason)) => return Err(reason),
+            Err(Problem::Swap(a, b, bit)) => {
+                if found
+                    .iter()
+                    .any(|&(c, d, _)| (c, d) == (a, b) || (c, d) == (b, a))
+                {
+                    return Err(format!(
+                        "Swapping {} and {} didn't fix bit {bit}",
+                        wire_name(&a),
+                        wire_name(&b)
+                    ));
+                }
+                swap_outputs(&mut repaired, &a, &b)?;
+                found.push((a, b, bit));
+            }
+        }
+    };
+
+    if let Some(bit) = first_wrong_bit(&repaired, num_bits) {
+        return Err(format!(
+            "The circuit looks like an adder after the swaps, but gets bit {bit} wrong"
+        ));
+    }
+    let mut swaps = vec![];
+    for &(a, b, bit) in &found {
+        let mut without = repaired.clone();
+        swap_outputs(&mut without, &a, &b)?;
+        let first_wrong_bit = first_wrong_bit(&without, num_bits).ok_or_else(|| {
+            format!(
+                "Swapping {} and {} wasn't needed",
+                wire_name(&a),
+                wire_name(&b)
+            )
+        })?;
+        swaps.push(Swap {
+            wires: (a, b),
+            bit,
+            first_wrong_bit,
+        });
+    }
+    Ok(Repair { swaps, roles })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse_input;
+    use super::*;
+
+    /// A ripple-carry adder with wire names that don't give anything away.
+    fn adder(num_bits: usize) -> Vec<Gate> {
+        let mut next_name = 0;
+        let mut name = || {
+            next_name += 1;
+            let letter = |n: usize| (b'a' + (n % 26) as u8) as char;
+            [
+                letter(next_name / 676),
+                letter(next_name / 26),
+                letter(next_name),
+            ]
+        };
+        let gate = |in1, in2, op, out| Gate { in1, in2, op, out };
+        let mut gates = vec![];
+        let mut carry = None;
+        for bit in 0..num_bits {
+            let (x, y) = (wire('x', bit), wire('y', bit));
+            let z = wire('z', bit);
+            let carry_out = if bit + 1 == num_bits {
+                wire('z', num_bits)
+            } else {
+                name()
+            };
+            match carry {
+                None => {
+                    gates.push(gate(x, y, Operation::Xor, z));
+                    gates.push(gate(x, y, Operation::And, carry_out));
+                }
+                Some(carry_in) => {
+                    let (partial_sum, generated, propagated) = (name(), name(), name());
+                    gates.push(gate(x, y, Operation::Xor, partial_sum));
+                    gates.push(gate(y, x, Operation::And, generated));
+                    gates.push(gate(carry_in, partial_sum, Operation::Xor, z));
+                    gates.push(gate(partial_sum, carry_in, Operation::And, propagated));
+                    gates.push(gate(generated, propagated, Operation::Or, carry_out));
+                }
+            }
+            carry = Some(carry_out);
+        }
+        gates
+    }
+
+    fn named(name: &str) -> Id {
+        name.chars().collect::<Vec<char>>().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_repair() {
+        let gates = adder(12);
+        let repair = repair(&gates, 12).unwrap();
+        assert!(repair.swaps.is_empty());
+        assert_eq!(repair.roles.len(), gates.len());
+        assert_eq!(repair.roles[&named("z05")], Role::Sum(5));
+        assert_eq!(repair.roles[&named("z12")], Role::CarryOut(11));
+        // The widest adder whose sum fits in a u64
+        assert_eq!(first_wrong_bit(&adder(63), 63), None);
+
+        // Swap a sum with a later carry, two wires inside a full adder, and a partial sum with
+        // the carry it generates
+        let mut broken = gates.clone();
+        let by_role = |role| *repair.roles.iter().find(|(_, r)| **r == role).unwrap().0;
+        let swaps = [
+            (by_role(Role::Sum(3)), by_role(Role::CarryOut(5))),
+            (by_role(Role::Sum(7)), by_role(Role::PropagatedCarry(7))),
+            (
+                by_role(Role::PartialSum(9)),
+                by_role(Role::GeneratedCarry(9)),
+            ),
+        ];
+        for (a, b) in &swaps {
+            swap_outputs(&mut broken, a, b).unwrap();
+        }
+        assert_eq!(first_wrong_bit(&broken, 12), Some(3));
+        let fixed = super::repair(&broken, 12).unwrap();
+        let bits: Vec<usize> = fixed.swaps.iter().map(|swap| swap.bit).collect();
+        assert_eq!(bits, vec![3, 7, 9]);
+        for (swap, (a, b)) in fixed.swaps.iter().zip(swaps) {
+            assert!(swap.wires == (a, b) || swap.wires == (b, a));
+        }
+        let report = fixed.report();
+        assert_eq!(report.lines().count(), 3);
+        assert!(report.lines().next().unwrap().contains("z03"));
+        assert!(report.contains(" fixes bit 7 (without it, bit 7 of the sum is wrong)"));
+    }
+
+    #[test]
+    fn test_not_an_adder() {
+        let contents = "x00: 1\ny00: 0\n\nx00 AND y00 -> z00\nx00 OR y00 -> z01";
+        let (_, gates, _) = parse_input(contents);
+        let gates: Vec<Gate> = gates.into_iter().collect();
+        assert_eq!(
+            repair(&gates, 1).unwrap_err(),
+            "There's no XOR gate reading x00 and y00"
+        );
+        assert_eq!(
+            repair(&gates, 64).unwrap_err(),
+            "The inputs have 64 bits, but at most 63 are supported"
+        );
+    }
+}
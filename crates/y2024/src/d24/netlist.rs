@@ -0,0 +1,319 @@
+//! Converting the gates to and from formats that other tools understand: Graphviz DOT for
+//! drawing, and structural Verilog for EDA tools (and for writing test circuits by hand).
+//! https://graphviz.org/doc/info/lang.html
+
+use std::collections::{BTreeSet, HashSet};
+
+use super::{Gate, Id, Operation, wire_name};
+
+impl Operation {
+    fn verilog_primitive(&self) -> &'static str {
+        match self {
+            Operation::And => "and",
+            Operation::Or => "or",
+            Operation::Xor => "xor",
+        }
+    }
+}
+
+/// The Verilog and SystemVerilog keywords that could clash with a three character wire name.
+const KEYWORDS: [&str; 18] = [
+    "and", "bit", "buf", "end", "for", "iff", "int", "let", "new", "nor", "not", "ref", "reg",
+    "tri", "use", "var", "wor", "xor",
+];
+
+/// The wire's name as a Verilog identifier. Keywords (and anything else that isn't a plain
+/// identifier) are escaped with a `\`, which needs whitespace after it to end the name.
+fn verilog_name(wire: &Id) -> String {
+    let name = wire_name(wire);
+    if KEYWORDS.contains(&name.as_str())
+        || wire[0].is_ascii_digit()
+        || !wire.iter().all(|c| c.is_ascii_alphanumeric() || *c == '_')
+    {
+        format!("\\{name} ")
+    } else {
+        name
+    }
+}
+
+/// The wires that are read but never driven, and the `z` wires, both sorted.
+fn ports(gates: &[Gate]) -> (Vec<Id>, Vec<Id>) {
+    let driven: HashSet<Id> = gates.iter().map(|gate| gate.out).collect();
+    let inputs: BTreeSet<Id> = gates
+        .iter()
+        .flat_map(|gate| [gate.in1, gate.in2])
+        .filter(|wire| !driven.contains(wire))
+        .collect();
+    let outputs: BTreeSet<Id> = driven.into_iter().filter(|wire| wire[0] == 'z').collect();
+    (inputs.into_iter().collect(), outputs.into_iter().collect())
+}
+
+/// A Graphviz graph with a node for each wire, labelled with the gate that drives it.
+pub(super) fn to_dot(gates: &[Gate]) -> String {
+    let (inputs, outputs) = ports(gates);
+    let mut dot = "digraph circuit {\n    rankdir=LR;\n".to_string();
+    for input in &inputs {
+        dot += &format!("    {} [shape=plaintext];\n", wire_name(input));
+    }
+    for gate in gates {
+        let shape = match gate.op {
+            Operation::And => "box",
+            Operation::Or => "ellipse",
+            Operation::Xor => "diamond",
+        };
+        let peripheries = if outputs.contains(&gate.out) { 2 } else { 1 };
+        dot += &format!(
+            "    {} [label=\"{}\\n{:?}\", shape={shape}, peripheries={peripheries}];\n",
+            wire_name(&gate.out),
+            wire_name(&gate.out),
+            gate.op,
+        );
+    }
+    for gate in gates {
+        for input in [gate.in1, gate.in2] {
+            dot += &format!("    {} -> {};\n", wire_name(&input), wire_name(&gate.out));
+        }
+    }
+    dot += "}\n";
+    dot
+}
+
+/// A Verilog module using the `and`, `or` and `xor` gate primitives.
+pub(super) fn to_verilog(gates: &[Gate], module: &str) -> String {
+    let (inputs, outputs) = ports(gates);
+    let outputs_set: HashSet<&Id> = outputs.iter().collect();
+    let mut ports: Vec<String> = inputs
+        .iter()
+        .map(|wire| format!("input {}", verilog_name(wire)))
+        .collect();
+    ports.extend(
+        outputs
+            .iter()
+            .map(|wire| format!("output {}", verilog_name(wire))),
+    );
+
+    let mut verilog = format!("module {module} (\n    {}\n);\n", ports.join(",\n    "));
+    for gate in gates {
+        if !outputs_set.contains(&gate.out) {
+            verilog += &format!("    wire {};\n", verilog_name(&gate.out));
+        }
+    }
+    for (i, gate) in gates.iter().enumerate() {
+        verilog += &format!(
+            "    {} g{i} ({}, {}, {});\n",
+            gate.op.verilog_primitive(),
+            verilog_name(&gate.out),
+            verilog_name(&gate.in1),
+            verilog_name(&gate.in2),
+        );
+    }
+    verilog += "endmodule\n";
+    verilog
+}
+
+/// Parse a wire name, which can be an escaped identifier.
+fn parse_wire(name: &str) -> Result<Id, String> {
+    let trimmed = name.trim();
+    trimmed
+        .strip_prefix('\\')
+        .unwrap_or(trimmed)
+        .chars()
+        .collect::<Vec<char>>()
+        .try_into()
+        .map_err(|_| format!("Wire names must be three characters long, not {name:?}"))
+}
+
+/// Parse one statement (without its `;`), returning the gate if it is one.
+fn parse_statement(statement: &str) -> Result<Option<Gate>, String> {
+    let keyword = statement.split_whitespace().next().unwrap_or("");
+    match keyword {
+        // Declarations don't matter, since each wire's role comes from the gates using it
+        "" | "module" | "input" | "output" | "wire" => Ok(None),
+        // assign out = in1 & in2
+        "assign" => {
+            let (out, expression) = statement["assign".len()..]
+                .split_once('=')
+                .ok_or("Expected an `=` in the assignment")?;
+            let (op, (in1, in2)) = [
+                ('&', Operation::And),
+                ('|', Operation::Or),
+                ('^', Operation::Xor),
+            ]
+            .into_iter()
+            .find_map(|(symbol, op)| Some((op, expression.split_once(symbol)?)))
+            .ok_or("Expected `&`, `|` or `^` in the assignment")?;
+            Ok(Some(Gate {
+                in1: parse_wire(in1)?,
+                in2: parse_wire(in2)?,
+                out: parse_wire(out)?,
+                op,
+            }))
+        }
+        // and [name] (out, in1, in2)
+        "and" | "or" | "xor" => {
+            let op = match keyword {
+                "and" => Operation::And,
+                "or" => Operation::Or,
+                _ => Operation::Xor,
+            };
+            let (_, terminals) = statement
+                .split_once('(')
+                .ok_or("Expected `(` after the gate")?;
+            let terminals = terminals
+                .trim_end()
+                .strip_suffix(')')
+                .ok_or("Expected `)` at the end of the gate")?;
+            match terminals.split(',').collect::<Vec<&str>>().as_slice() {
+                [out, in1, in2] => Ok(Some(Gate {
+                    in1: parse_wire(in1)?,
+                    in2: parse_wire(in2)?,
+                    out: parse_wire(out)?,
+                    op,
+                })),
+                _ => Err("Gates need exactly one output and two inputs".to_string()),
+            }
+        }
+        _ => Err(format!("Unknown statement {keyword:?}")),
+    }
+}
+
+/// Read the gates from a Verilog-style netlist, made of `and`, `or` and `xor` primitives or
+/// `assign` statements using `&`, `|` and `^`. Other declarations are skipped, and the gates
+/// can be in any order. Wire names can be escaped identifiers, as long as they don't contain
+/// `,`, `;` or any of the operators.
+pub(super) fn from_verilog(source: &str) -> Result<Vec<Gate>, String> {
+    let mut gates: Vec<Gate> = vec![];
+    let mut driven = HashSet::new();
+    let mut statement = String::new();
+    let mut statement_line = 1;
+    for (line_number, line) in source.lines().enumerate() {
+        let line = line.split("//").next().unwrap();
+        let line = line.trim().strip_suffix("endmodule").unwrap_or(line.trim());
+        if statement.trim().is_empty() {
+            statement_line = line_number + 1;
+        }
+        for (i, part) in line.split(';').enumerate() {
+            if i > 0 {
+                // A `;` finished the statement
+                let gate = parse_statement(statement.trim())
+                    .map_err(|e| format!("Line {statement_line}: {e}"))?;
+                if let Some(gate) = gate {
+                    if !driven.insert(gate.out) {
+                        return Err(format!(
+                            "Line {statement_line}: {} is driven by more than one gate",
+                            wire_name(&gate.out)
+                        ));
+                    }
+                    gates.push(gate);
+                }
+                statement.clear();
+                statement_line = line_number + 1;
+            }
+            statement += part;
+            statement += " ";
+        }
+    }
+    if !statement.trim().is_empty() {
+        return Err(format!("Line {statement_line}: Missing `;`"));
+    }
+    Ok(gates)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse_input;
+    use super::*;
+
+    const CIRCUIT: &str = "x00: 1
+x01: 0
+y00: 1
+y01: 1
+
+x00 XOR y00 -> z00
+x00 AND y00 -> cab
+x01 XOR y01 -> sab
+sab XOR cab -> z01
+x01 AND y01 -> gab
+sab AND cab -> pab
+gab OR pab -> z02";
+
+    fn gates() -> Vec<Gate> {
+        parse_input(CIRCUIT).1.into_iter().collect()
+    }
+
+    #[test]
+    fn test_verilog() {
+        let verilog = to_verilog(&gates(), "adder");
+        assert!(verilog.starts_with(
+            "module adder (\n    input x00,\n    input x01,\n    input y00,\n    input y01,\n    \
+             output z00,\n    output z01,\n    output z02\n);\n    wire cab;\n"
+        ));
+        assert!(verilog.contains("\n    xor g3 (z01, sab, cab);\n"));
+        assert!(verilog.ends_with("    or g6 (z02, gab, pab);\nendmodule\n"));
+        assert_eq!(from_verilog(&verilog), Ok(gates()));
+
+        // Hand written, with assignments, comments and statements split across lines
+        let source = "module half_adder(input x00, input y00, output z00, output z01);
+            assign z00 = x00 ^ y00; // the sum
+            and carry (
+                z01, x00, y00
+            ); endmodule";
+        let parsed = from_verilog(source).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].op, Operation::Xor);
+        assert_eq!(wire_name(&parsed[1].out), "z01");
+    }
+
+    #[test]
+    fn test_verilog_keywords() {
+        // A wire named after a gate primitive
+        let gates: Vec<Gate> = parse_input(&CIRCUIT.replace("cab", "and"))
+            .1
+            .into_iter()
+            .collect();
+        let verilog = to_verilog(&gates, "adder");
+        assert!(verilog.contains("\n    wire \\and ;\n"));
+        assert!(verilog.contains("\n    xor g3 (z01, sab, \\and );\n"));
+        assert_eq!(from_verilog(&verilog), Ok(gates));
+        assert_eq!(
+            from_verilog("assign \\end  = x00 & \\for ;").map(|gates| wire_name(&gates[0].out)),
+            Ok("end".to_string())
+        );
+    }
+
+    #[test]
+    fn test_verilog_errors() {
+        assert_eq!(
+            from_verilog("wire abc;\nassign abc = x00 + y00;"),
+            Err("Line 2: Expected `&`, `|` or `^` in the assignment".to_string())
+        );
+        assert_eq!(
+            from_verilog("and (z00, x00, y00);\nor (z00,\n x00, y00);"),
+            Err("Line 2: z00 is driven by more than one gate".to_string())
+        );
+        assert_eq!(
+            from_verilog("nand (z00, x00, y00);"),
+            Err("Line 1: Unknown statement \"nand\"".to_string())
+        );
+        assert_eq!(
+            from_verilog("xor (z00, x00, input0);"),
+            Err("Line 1: Wire names must be three characters long, not \" input0\"".to_string())
+        );
+        assert_eq!(
+            from_verilog("xor (z00, x00, y00)"),
+            Err("Line 1: Missing `;`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = to_dot(&gates());
+        assert!(
+            dot.starts_with("digraph circuit {\n    rankdir=LR;\n    x00 [shape=plaintext];\n")
+        );
+        assert!(dot.contains("    cab [label=\"cab\\nAnd\", shape=box, peripheries=1];\n"));
+        assert!(dot.contains("    z02 [label=\"z02\\nOr\", shape=ellipse, peripheries=2];\n"));
+        assert!(dot.contains("    sab -> z01;\n    cab -> z01;\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
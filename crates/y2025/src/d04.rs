@@ -1,116 +1,42 @@
-use shared::Problem;
+use shared::{Grid, Problem};
 
-mod grid {
-    pub struct Grid {
-        elements: Vec<bool>,
-        num_rows: usize,
-        num_cols: usize,
-    }
-
-    impl Grid {
-        pub fn new(grid: Vec<Vec<bool>>) -> Grid {
-            let num_rows = grid.len();
-            let num_cols = grid.first().map_or(0, |v| v.len());
-            assert!(grid.iter().all(|v| v.len() == num_cols));
-            let elements = grid.into_iter().flatten().collect();
-            Grid {
-                elements,
-                num_rows,
-                num_cols,
-            }
-        }
-
-        fn get(&self, i: usize, j: usize) -> Option<bool> {
-            if i >= self.num_cols || j >= self.num_rows {
-                None
-            } else {
-                Some(self.elements[i * self.num_cols + j])
-            }
-        }
-
-        fn remove(&mut self, i: usize, j: usize) -> bool {
-            if i >= self.num_cols || j >= self.num_rows {
-                false
-            } else {
-                self.elements[i * self.num_cols + j] = false;
-                true
-            }
-        }
+fn parse_input(contents: &str) -> Grid<bool> {
+    Grid::parse(contents, |c| c == '@')
+}
 
-        pub fn is_accessible(&self, i: usize, j: usize) -> bool {
-            let mut count = 0;
-            for (a, b) in [
-                (i.wrapping_sub(1), j.wrapping_sub(1)),
-                (i.wrapping_sub(1), j),
-                (i.wrapping_sub(1), j + 1),
-                (i, j.wrapping_sub(1)),
-                (i, j + 1),
-                (i + 1, j.wrapping_sub(1)),
-                (i + 1, j),
-                (i + 1, j + 1),
-            ] {
-                if let Some(true) = self.get(a, b) {
-                    count += 1
-                }
-            }
-            count < 4
-        }
+fn is_accessible(grid: &Grid<bool>, i: usize, j: usize) -> bool {
+    grid.neighbors8(i, j).filter(|&(a, b)| grid[(a, b)]).count() < 4
+}
 
-        pub fn count_accessible(&self) -> u64 {
-            let mut count = 0;
-            for i in 0..self.num_cols {
-                for j in 0..self.num_rows {
-                    if let Some(true) = self.get(i, j) {
-                        if self.is_accessible(i, j) {
-                            count += 1;
-                        }
-                    }
-                }
-            }
-            count
-        }
+fn count_accessible(grid: &Grid<bool>) -> u64 {
+    grid.positions()
+        .filter(|&(i, j)| grid[(i, j)] && is_accessible(grid, i, j))
+        .count() as u64
+}
 
-        pub fn count_accessible_2(&mut self) -> u64 {
-            let mut count = 0;
-            let mut prev_count = 1;
-            while count != prev_count {
-                prev_count = count;
-                for i in 0..self.num_cols {
-                    for j in 0..self.num_rows {
-                        if let Some(true) = self.get(i, j) {
-                            if self.is_accessible(i, j) {
-                                count += 1;
-                                self.remove(i, j);
-                            }
-                        }
-                    }
-                }
+fn count_accessible_2(grid: &mut Grid<bool>) -> u64 {
+    let mut count = 0;
+    let mut prev_count = 1;
+    while count != prev_count {
+        prev_count = count;
+        for (i, j) in grid.positions() {
+            if grid[(i, j)] && is_accessible(grid, i, j) {
+                count += 1;
+                grid[(i, j)] = false;
             }
-            count
         }
     }
-}
-
-use grid::Grid;
-
-fn parse_input(contents: &str) -> Grid {
-    Grid::new(
-        contents
-            .trim()
-            .lines()
-            .map(|line| line.chars().map(|c| c == '@').collect())
-            .collect(),
-    )
+    count
 }
 
 fn compute_1(contents: &str) -> u64 {
     let grid = parse_input(contents);
-    grid.count_accessible()
+    count_accessible(&grid)
 }
 
 fn compute_2(contents: &str) -> u64 {
     let mut grid = parse_input(contents);
-    grid.count_accessible_2()
+    count_accessible_2(&mut grid)
 }
 
 pub(crate) struct Day {}
@@ -132,3 +58,30 @@ impl Problem for Day {
         "9050".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.";
+
+    #[test]
+    fn test_example() {
+        assert_eq!(compute_1(EXAMPLE), 13);
+        assert_eq!(compute_2(EXAMPLE), 43);
+    }
+
+    #[test]
+    fn test_non_square() {
+        assert_eq!(compute_1("@@@@@\n....."), 5);
+    }
+}
@@ -1,4 +1,4 @@
-use shared::Problem;
+use shared::{Problem, interval::IntervalSet};
 
 fn parse_input(contents: &str) -> (Vec<(u64, u64)>, Vec<u64>) {
     let mut split = contents.trim().split("\n\n");
@@ -22,44 +22,23 @@ fn parse_input(contents: &str) -> (Vec<(u64, u64)>, Vec<u64>) {
     (ranges, products)
 }
 
+fn fresh_ingredients(ranges: &[(u64, u64)]) -> IntervalSet<u64> {
+    ranges.iter().map(|(lo, hi)| *lo..*hi + 1).collect()
+}
+
 fn compute_1(contents: &str) -> u64 {
     let (ranges, products) = parse_input(contents);
-    let mut count = 0;
-    for product in products {
-        for (lo, hi) in ranges.iter() {
-            if lo <= &product && &product <= hi {
-                count += 1;
-                break;
-            }
-        }
-    }
-    count
+    let fresh = fresh_ingredients(&ranges);
+    products
+        .into_iter()
+        .filter(|product| fresh.contains(*product))
+        .count() as u64
 }
 
 fn compute_2(contents: &str) -> u64 {
-    let (mut ranges, _) = parse_input(contents);
-
-    // Sort lexicographically. This lets us iterate over the ranges just once.
-    ranges.sort();
-
-    let mut count = 0;
-    let mut curr_lo = ranges[0].0;
-    let mut curr_hi = ranges[0].1;
-    for range in ranges[1..].into_iter() {
-        // If the new range's low is higher than the current high, we're starting a new range.
-        // Finish up the current one by adding to the running count, and reset curr_lo.
-        if range.0 > curr_hi {
-            count += curr_hi - curr_lo + 1;
-            curr_lo = range.0;
-        }
-
-        // The high high value of the current range should always be the max possible value.
-        curr_hi = std::cmp::max(curr_hi, range.1);
-    }
-    // Need to count the final range
-    count += curr_hi - curr_lo + 1;
-
-    count
+    let (ranges, _) = parse_input(contents);
+    // Overlapping ranges are merged together, so this doesn't double count.
+    fresh_ingredients(&ranges).len()
 }
 
 pub(crate) struct Day {}
@@ -81,3 +60,30 @@ impl Problem for Day {
         "342018167474526".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "3-5
+10-14
+16-20
+12-18
+
+1
+5
+8
+11
+17
+32";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 3);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 14);
+    }
+}
@@ -1,54 +1,12 @@
-use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashSet},
-    str::FromStr,
-};
-
-use shared::Problem;
-
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
-struct Node {
-    x: u64,
-    y: u64,
-    z: u64,
-}
-
-impl Node {
-    fn new(x: u64, y: u64, z: u64) -> Self {
-        Self { x, y, z }
-    }
+use std::{cmp::Reverse, collections::BinaryHeap};
 
-    fn dist_squared(&self, other: &Self) -> u64 {
-        (self.x.abs_diff(other.x)).pow(2)
-            + (self.y.abs_diff(other.y)).pow(2)
-            + (self.z.abs_diff(other.z)).pow(2)
-    }
-}
-
-impl FromStr for Node {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split(',');
-
-        let mut parse_next = || -> Result<u64, String> {
-            Ok(parts
-                .next()
-                .ok_or("Not enough parts")?
-                .parse::<u64>()
-                .map_err(|_| "Unable to parse")?)
-        };
+use shared::{
+    Problem,
+    dsu::{self, UnionFind},
+    geom::Vec3,
+};
 
-        let x = parse_next()?;
-        let y = parse_next()?;
-        let z = parse_next()?;
-        if let Some(_) = parts.next() {
-            Err("Too many parts".to_string())
-        } else {
-            Ok(Self::new(x, y, z))
-        }
-    }
-}
+type Node = Vec3<u64>;
 
 fn parse_input(contents: &str) -> Vec<Node> {
     contents
@@ -58,128 +16,46 @@ fn parse_input(contents: &str) -> Vec<Node> {
         .collect()
 }
 
-fn compute_1(contents: &str) -> u64 {
-    let nodes = parse_input(contents);
-    let mut edges = vec![vec![false; nodes.len()]; nodes.len()];
-    let mut distances: BinaryHeap<Reverse<(u64, usize, usize)>> =
-        BinaryHeap::with_capacity(nodes.len().pow(2));
+/// Every pair of junction boxes as `(distance squared, i, j)`.
+fn edges(nodes: &[Node]) -> Vec<(u64, usize, usize)> {
+    let mut edges = Vec::with_capacity(nodes.len().pow(2) / 2);
     for (i, n0) in nodes.iter().enumerate() {
         for (j, n1) in nodes.iter().enumerate().skip(i + 1) {
-            distances.push(Reverse((n0.dist_squared(n1), i, j)));
+            edges.push((n0.dist_squared(n1), i, j));
         }
     }
+    edges
+}
+
+fn compute_1(contents: &str) -> u64 {
+    let nodes = parse_input(contents);
+    let mut distances: BinaryHeap<Reverse<(u64, usize, usize)>> =
+        BinaryHeap::from_iter(edges(&nodes).into_iter().map(Reverse));
+    let mut circuits = UnionFind::new(nodes.len());
     for _ in 0..1000 {
         let Reverse((_, n0, n1)) = distances.pop().unwrap();
-        edges[n0][n1] = true;
-        edges[n1][n0] = true;
-    }
-    let mut connected_component_sizes: Vec<u64> = Vec::new();
-    let mut visited_overall: HashSet<usize> = HashSet::new();
-    let mut to_visit_overall: Vec<usize> = Vec::from_iter(0..nodes.len());
-    while !to_visit_overall.is_empty() {
-        let curr = to_visit_overall.pop().unwrap();
-        if !visited_overall.contains(&curr) {
-            let mut curr_component_size = 0;
-            let mut to_visit_component: Vec<usize> = vec![curr];
-            let mut visited_component: HashSet<usize> = HashSet::new();
-            while !to_visit_component.is_empty() {
-                let curr = to_visit_component.pop().unwrap();
-                if !visited_component.contains(&curr) {
-                    curr_component_size += 1;
-                    visited_component.insert(curr);
-                    visited_overall.insert(curr);
-                    to_visit_component.extend(
-                        edges[curr]
-                            .iter()
-                            .enumerate()
-                            .filter(|(_, edge)| **edge)
-                            .map(|(i, _)| i),
-                    );
-                }
-            }
-            connected_component_sizes.push(curr_component_size);
-        }
+        circuits.union(n0, n1);
     }
+    let mut connected_component_sizes = circuits.component_sizes();
     connected_component_sizes.sort();
 
     connected_component_sizes
         .into_iter()
         .rev()
         .take(3)
+        .map(|size| size as u64)
         .reduce(|a, b| a * b)
         .unwrap()
 }
 
-#[derive(Clone, Debug)]
-struct SpanningTreeNode {
-    parent: Option<usize>,
-    subtree_depth: usize,
-}
-
-impl SpanningTreeNode {
-    fn new() -> Self {
-        SpanningTreeNode {
-            parent: None,
-            subtree_depth: 1,
-        }
-    }
-}
-
-fn spanning_tree_root_index(mut curr_node_index: usize, trees: &[SpanningTreeNode]) -> usize {
-    while let SpanningTreeNode {
-        parent: Some(new_index),
-        subtree_depth: _,
-    } = trees[curr_node_index]
-    {
-        curr_node_index = new_index;
-    }
-    curr_node_index
-}
-
 fn compute_2(contents: &str) -> u64 {
     let nodes = parse_input(contents);
-
-    let mut distances: BinaryHeap<Reverse<(u64, usize, usize)>> =
-        BinaryHeap::with_capacity(nodes.len().pow(2));
-    for (i, n0) in nodes.iter().enumerate() {
-        for (j, n1) in nodes.iter().enumerate().skip(i + 1) {
-            distances.push(Reverse((n0.dist_squared(n1), i, j)));
-        }
-    }
-
-    let mut spanning_trees = vec![SpanningTreeNode::new(); nodes.len()];
-
-    let (final_node_i, final_node_j) = loop {
-        let Reverse((_, i, j)) = distances.pop().expect("Impossible!");
-
-        let root_i = spanning_tree_root_index(i, &spanning_trees);
-        let root_j = spanning_tree_root_index(j, &spanning_trees);
-        if root_i != root_j {
-            let (root_smaller, root_bigger) =
-                if spanning_trees[root_i].subtree_depth < spanning_trees[root_j].subtree_depth {
-                    (root_i, root_j)
-                } else {
-                    (root_j, root_i)
-                };
-
-            spanning_trees[root_smaller].parent = Some(root_bigger);
-            spanning_trees[root_bigger].subtree_depth = std::cmp::max(
-                spanning_trees[root_bigger].subtree_depth,
-                spanning_trees[root_smaller].subtree_depth + 1,
-            );
-
-            if spanning_trees
-                .iter()
-                .filter(|tree_node| tree_node.parent.is_none())
-                .skip(1)
-                .all(|_| false)
-            {
-                break (nodes[i], nodes[j]);
-            }
-        };
-    };
-
-    final_node_i.x * final_node_j.x
+    // The connection that finally joins everything into one circuit is the last edge
+    // of the minimum spanning tree.
+    let (_, i, j) = *dsu::kruskal(nodes.len(), edges(&nodes))
+        .last()
+        .expect("Impossible!");
+    nodes[i].x * nodes[j].x
 }
 
 pub(crate) struct Day {}
@@ -204,6 +80,8 @@ impl Problem for Day {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
 
     #[test]
@@ -1,4 +1,8 @@
-use shared::Problem;
+use shared::{
+    Problem,
+    ilp::{IntegerProgram, Relation},
+    num::Rational,
+};
 
 type Lights = Vec<bool>;
 type Buttons = Vec<Vec<usize>>;
@@ -94,423 +98,38 @@ fn compute_1(contents: &str) -> u64 {
     total
 }
 
-mod frac {
-    use std::ops::{DivAssign, Mul, Sub, SubAssign};
-
-    // Note: since we're going to be guaranteeing a specific format
-    // (reduced form, negative numerator if needed), then deriving
-    // PartialEq and Eq are valid!
-    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-    pub struct Fraction {
-        num: i64,
-        den: i64,
-    }
-
-    impl Fraction {
-        pub fn new(num: i64, den: i64) -> Option<Fraction> {
-            if den == 0 {
-                None
-            } else {
-                Some(Fraction::new_unchecked(num, den))
-            }
-        }
-
-        // Does not check for den == 0, beware!
-        #[inline(always)]
-        pub fn new_unchecked(mut num: i64, mut den: i64) -> Fraction {
-            let is_neg = (num < 0) ^ (den < 0);
-            num = num.abs();
-            den = den.abs();
-
-            let gcd = compute_gcd(num, den);
-            num /= gcd;
-            den /= gcd;
-            Fraction {
-                num: if is_neg { -(num) } else { num },
-                den: den,
-            }
-        }
-
-        pub fn whole_number(num: i64) -> Fraction {
-            Fraction { num, den: 1 }
-        }
-
-        pub fn is_zero(&self) -> bool {
-            self.num == 0
-        }
-
-        pub fn is_negative(&self) -> bool {
-            self.num < 0
-        }
-
-        pub fn integral(&self) -> Option<i64> {
-            if self.den == 1 { Some(self.num) } else { None }
-        }
-    }
-
-    impl Mul for Fraction {
-        type Output = Fraction;
-
-        fn mul(self, rhs: Self) -> Self::Output {
-            Fraction::new_unchecked(self.num * rhs.num, self.den * rhs.den)
-        }
-    }
-
-    impl DivAssign for Fraction {
-        fn div_assign(&mut self, rhs: Self) {
-            // (a/b) / (c/d) = (a/b) * (d/c) = (ad)/(bc)
-            if let Some(f) = Fraction::new(self.num * rhs.den, self.den * rhs.num) {
-                self.num = f.num;
-                self.den = f.den;
-            } else {
-                panic!("Division by zero");
-            }
-        }
-    }
-
-    impl Sub for Fraction {
-        type Output = Fraction;
-
-        fn sub(self, rhs: Self) -> Self::Output {
-            Fraction::new_unchecked(self.num * rhs.den - rhs.num * self.den, self.den * rhs.den)
-        }
-    }
-
-    impl SubAssign for Fraction {
-        fn sub_assign(&mut self, rhs: Self) {
-            let f = Fraction::new_unchecked(
-                self.num * rhs.den - rhs.num * self.den,
-                self.den * rhs.den,
-            );
-            self.num = f.num;
-            self.den = f.den;
-        }
-    }
-
-    /// Implement Euclid's algorithm
-    /// https://en.wikipedia.org/wiki/Euclidean_algorithm
-    /// Props to 2023 day 8
-    /// Note: assumes a and b are non-negative, and that at least one is non-zero
-    fn compute_gcd(mut a: i64, mut b: i64) -> i64 {
-        if b < a {
-            (a, b) = (b, a);
-        }
-        while b != 0 {
-            let prev = b;
-            b = a % b;
-            a = prev;
-        }
-        a
-    }
-
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-
-        #[test]
-        fn test_gcd() {
-            assert_eq!(compute_gcd(1, 1), 1);
-            assert_eq!(compute_gcd(2, 2), 2);
-            assert_eq!(compute_gcd(4, 2), 2);
-        }
-
-        #[test]
-        fn test_new() {
-            let f = Fraction::new(0, 1).unwrap();
-            assert_eq!(f.num, 0);
-            assert_eq!(f.den, 1);
-
-            let f = Fraction::new(2, 4).unwrap();
-            assert_eq!(f.num, 1);
-            assert_eq!(f.den, 2);
-
-            let f = Fraction::new(-2, -4).unwrap();
-            assert_eq!(f.num, 1);
-            assert_eq!(f.den, 2);
-
-            assert_eq!(Fraction::new(2, -4), Fraction::new(-1, 2));
-            assert_eq!(Fraction::new(100, 200), Fraction::new(1, 2));
-            assert_eq!(Fraction::new(-40, 4), Fraction::new(-10, 1));
-
-            assert_eq!(Fraction::new(1, 0), None);
-        }
-
-        #[test]
-        fn test_is_zero() {
-            assert!(Fraction::new(0, 1).unwrap().is_zero());
-            assert!(!Fraction::new(-1, 1).unwrap().is_zero());
-            assert!(!Fraction::new(1, 1).unwrap().is_zero());
-        }
-
-        #[test]
-        fn test_is_negative() {
-            assert!(!Fraction::new(0, 1).unwrap().is_negative());
-            assert!(Fraction::new(-1, 1).unwrap().is_negative());
-            assert!(!Fraction::new(1, 1).unwrap().is_negative());
-        }
-
-        #[test]
-        fn test_integral() {
-            assert_eq!(Fraction::new(0, 1).unwrap().integral(), Some(0));
-            assert_eq!(Fraction::new(-1, 1).unwrap().integral(), Some(-1));
-            assert_eq!(Fraction::new(1, 1).unwrap().integral(), Some(1));
-            assert_eq!(Fraction::new(1, 2).unwrap().integral(), None);
-        }
-
-        #[test]
-        fn test_mul() {
-            let f1 = Fraction::new(0, 1).unwrap();
-            let f2 = Fraction::new(1, 100).unwrap();
-            let f3 = Fraction::new(-1, 2).unwrap();
-            let f4 = Fraction::new(1, 7).unwrap();
-            let f5 = Fraction::new(10, 1).unwrap();
-
-            for fx in [f1, f2, f3, f4] {
-                for fy in [f1, f2, f3, f4] {
-                    assert_eq!(fx * fy, fy * fx);
+type Fraction = Rational<i64>;
+
+/// Minimise the total number of presses, where each joltage must be hit exactly by the
+/// presses of the buttons that affect it.
+fn min_pushes(machine: &Machine) -> u64 {
+    let mut program = IntegerProgram::minimize(vec![Fraction::one(); machine.buttons.len()]);
+    for (j, joltage) in machine.joltages.iter().enumerate() {
+        let coefficients = machine
+            .buttons
+            .iter()
+            .map(|button| {
+                if button.contains(&j) {
+                    Fraction::one()
+                } else {
+                    Fraction::zero()
                 }
-            }
-
-            assert_eq!(f1 * f2, Fraction::whole_number(0));
-            assert_eq!(f1 * f3, Fraction::whole_number(0));
-
-            assert_eq!(f2 * f3, Fraction::new(-1, 200).unwrap());
-
-            assert_eq!(f2 * f2, Fraction::new(1, 10_000).unwrap());
-            assert_eq!(f3 * f3, Fraction::new(1, 4).unwrap());
-
-            assert_eq!(f2 * f4, Fraction::new(1, 700).unwrap());
-
-            assert_eq!(f2 * f5, Fraction::new(1, 10).unwrap());
-        }
-
-        #[test]
-        fn test_div_assign() {
-            let f1 = Fraction::new(0, 1).unwrap();
-            let f2 = Fraction::new(1, 100).unwrap();
-            let f3 = Fraction::new(-1, 2).unwrap();
-            let f4 = Fraction::new(1, 7).unwrap();
-            let f5 = Fraction::new(10, 1).unwrap();
-
-            let mut f = f1.clone();
-            f /= f2;
-            assert_eq!(f, f1);
-
-            let mut f = f2.clone();
-            f /= f2;
-            assert_eq!(f, Fraction::whole_number(1));
-
-            let mut f = f3.clone();
-            f /= f3;
-            assert_eq!(f, Fraction::whole_number(1));
-
-            let mut f = f2.clone();
-            f /= f3;
-            assert_eq!(f, Fraction::new(-1, 50).unwrap());
-
-            let mut f = f2.clone();
-            f /= f4;
-            assert_eq!(f, Fraction::new(7, 100).unwrap());
-
-            let mut f = f2.clone();
-            f /= f5;
-            assert_eq!(f, Fraction::new(1, 1000).unwrap());
-        }
-
-        #[test]
-        fn test_sub() {
-            let f1 = Fraction::new(0, 1).unwrap();
-            let f2 = Fraction::new(1, 100).unwrap();
-            let f3 = Fraction::new(-1, 2).unwrap();
-            let f4 = Fraction::new(1, 7).unwrap();
-            let f5 = Fraction::new(10, 1).unwrap();
-
-            assert_eq!(f1 - f2, Fraction::new(-1, 100).unwrap());
-            assert_eq!(f2 - f1, f2);
-
-            assert_eq!(f1 - f3, Fraction::new(1, 2).unwrap());
-            assert_eq!(f2 - f3, Fraction::new(51, 100).unwrap());
-            assert_eq!(f3 - f2, Fraction::new(-51, 100).unwrap());
-            assert_eq!(f2 - f4, Fraction::new(-93, 700).unwrap());
-            assert_eq!(f2 - f5, Fraction::new(-999, 100).unwrap());
-            assert_eq!(
-                Fraction::new(51, 100).unwrap() - Fraction::new(1, 100).unwrap(),
-                Fraction::new(1, 2).unwrap()
-            );
-        }
-
-        #[test]
-        fn test_sub_assign() {
-            let f1 = Fraction::new(0, 1).unwrap();
-            let f2 = Fraction::new(1, 100).unwrap();
-            let f3 = Fraction::new(-1, 2).unwrap();
-            let f4 = Fraction::new(1, 7).unwrap();
-
-            let mut f = f1.clone();
-            f -= f2;
-            assert_eq!(f, Fraction::new(-1, 100).unwrap());
-
-            let mut f = f1.clone();
-            f -= f3;
-            assert_eq!(f, Fraction::new(1, 2).unwrap());
-
-            let mut f = f2.clone();
-            f -= f3;
-            assert_eq!(f, Fraction::new(51, 100).unwrap());
-
-            let mut f = f2.clone();
-            f -= f4;
-            assert_eq!(f, Fraction::new(-93, 700).unwrap());
-
-            let mut f = Fraction::whole_number(-10);
-            f -= Fraction::whole_number(12) * Fraction::whole_number(0);
-            assert_eq!(f, Fraction::whole_number(-10));
-        }
-    }
-}
-
-use frac::Fraction;
-
-struct Matrix {
-    data: Vec<Vec<Fraction>>,
-    rows: usize,
-    cols: usize,
-    free: Vec<usize>,
-}
-
-impl Matrix {
-    fn new_from_machine(machine: Machine) -> Self {
-        let mut data = vec![
-            vec![Fraction::whole_number(0); machine.buttons.len() + 1];
-            machine.joltages.len()
-        ];
-        for (i, button) in machine.buttons.iter().enumerate() {
-            for j in button {
-                data[*j][i] = Fraction::whole_number(1);
-            }
-        }
-        for (i, joltage) in machine.joltages.into_iter().enumerate() {
-            data[i][machine.buttons.len()] = Fraction::whole_number(joltage as i64);
-        }
-        Self {
-            rows: data.len(),
-            cols: data[0].len(),
-            data: data,
-            free: vec![],
-        }
-    }
-
-    // https://rosettacode.org/wiki/Reduced_row_echelon_form#Python
-    fn reduced_row_echelon_form(&mut self) {
-        let mut lead = 0;
-        for r in 0..self.rows {
-            if lead >= self.cols {
-                return;
-            }
-            let mut i = r;
-            while self.data[i][lead].is_zero() {
-                i += 1;
-                if i == self.rows {
-                    i = r;
-                    lead += 1;
-                    if lead == self.cols {
-                        return;
-                    }
-                    // No non-zero values in the rest of the column means it's
-                    // a free variable
-                    self.free.push(lead - 1);
-                }
-            }
-            self.data.swap(i, r);
-            let lv = self.data[r][lead];
-            for val in self.data[r].iter_mut() {
-                *val /= lv;
-            }
-            for i in 0..self.rows {
-                if i != r {
-                    let lv = self.data[i][lead];
-                    for j in 0..self.cols {
-                        self.data[i][j] = self.data[i][j] - lv * self.data[r][j];
-                    }
-                }
-            }
-            lead += 1;
-        }
-        self.free.extend(lead..self.cols - 1);
-    }
-
-    fn check_solution_is_valid(&self, free_values: &[u64]) -> Option<u64> {
-        let mut num_pushes = free_values.iter().sum();
-
-        for row in self.data.iter() {
-            // Try plugging in the given values for the free variables, and see if the
-            // solution is consistent with our needs.
-            let mut pushes = row[self.cols - 1];
-            for (free_value, coefficient_idx) in free_values.iter().zip(self.free.iter()) {
-                pushes -= Fraction::whole_number(*free_value as i64) * row[*coefficient_idx];
-            }
-            if pushes.is_negative() {
-                return None;
-            }
-            if let Some(integer_push) = pushes.integral() {
-                num_pushes += integer_push as u64;
-            } else {
-                return None;
-            }
-        }
-
-        Some(num_pushes)
-    }
-}
-
-fn part_2_recursion(
-    matrix: &Matrix,
-    free_values: &mut [u64],
-    i: usize,
-    max_required_pushes: u64,
-    curr_min: &mut u64,
-) {
-    if i == free_values.len() {
-        if let Some(n) = matrix.check_solution_is_valid(free_values) {
-            if &n < curr_min {
-                *curr_min = n;
-            }
-        }
-    } else {
-        for pushes in 0..max_required_pushes {
-            free_values[i] = pushes;
-            part_2_recursion(matrix, free_values, i + 1, max_required_pushes, curr_min);
-        }
-    }
-}
-
-fn solve_part_2(matrix: &Matrix, machine: Machine) -> u64 {
-    let mut free_values = vec![0; matrix.free.len()];
-    let mut min_pushes = u64::MAX;
-
-    let max_required_pushes = *machine.joltages.iter().max().unwrap();
-
-    part_2_recursion(
-        matrix,
-        &mut free_values,
-        0,
-        max_required_pushes,
-        &mut min_pushes,
-    );
-
-    min_pushes
+            })
+            .collect();
+        program.add_constraint(
+            coefficients,
+            Relation::Equal,
+            Fraction::whole_number(*joltage as i64),
+        );
+    }
+    let solution = program.solve().expect("Unsolvable!");
+    assert!(solution.proven_optimal);
+    solution.values.iter().sum::<i64>() as u64
 }
 
 fn compute_2(contents: &str) -> u64 {
     let machines = parse_input(contents);
-    let mut total = 0;
-    for machine in machines.into_iter() {
-        let mut matrix = Matrix::new_from_machine(machine.clone());
-        matrix.reduced_row_echelon_form();
-        let summand = solve_part_2(&matrix, machine);
-        total += summand;
-    }
-    total
+    machines.iter().map(min_pushes).sum()
 }
 
 pub(crate) struct Day {}
@@ -532,3 +151,22 @@ impl Problem for Day {
         "20869".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
+[...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
+[.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 7);
+    }
+
+    #[test]
+    fn test_compute_2_example() {
+        assert_eq!(compute_2(EXAMPLE), 33);
+    }
+}
@@ -1,265 +1,82 @@
 use shared::Problem;
+use shared::polyomino::{self, Placement, Polyomino, Region};
 
-#[derive(PartialEq, Eq, Clone)]
-struct Present {
-    empties: Vec<(usize, usize)>,
+struct Tree {
+    width: usize,
+    height: usize,
+    /// How many of each present have to fit under the tree.
+    required_presents: Vec<usize>,
 }
 
-impl Present {
-    fn new(mut empties: Vec<(usize, usize)>) -> Self {
-        empties.sort();
-        Self { empties }
-    }
-
-    fn flip_horizontal(&self) -> Self {
-        let mut empties = vec![];
-        for (i, j) in &self.empties {
-            empties.push((*i, 2 - *j));
-        }
-        Present::new(empties)
-    }
-    // fn flip_vertical(&self) -> Self {
-    //     let mut empties = vec![];
-    //     for (i, j) in &self.empties {
-    //         empties.push((2 - *i, *j));
-    //     }
-    //     Present { empties }
-    // }
-    fn rotate_90_deg(&self) -> Self {
-        let mut empties = vec![];
-        for (i, j) in &self.empties {
-            empties.push(match (*i, *j) {
-                (0, 0) => (0, 2),
-                (0, 1) => (1, 2),
-                (0, 2) => (2, 2),
-                (1, 0) => (0, 1),
-                (1, 1) => (1, 1),
-                (1, 2) => (2, 1),
-                (2, 0) => (0, 0),
-                (2, 1) => (1, 0),
-                (2, 2) => (2, 0),
-                _ => panic!("Uh oh"),
-            });
-        }
-        Present::new(empties)
-    }
-    #[allow(unused)]
-    fn show(&self) {
-        for i in 0..3 {
-            for j in 0..3 {
-                if self.empties.contains(&(i, j)) {
-                    print!(".");
-                } else {
-                    print!("#");
-                }
-            }
-            print!("\n");
-        }
-        print!("\n");
-    }
-}
-
-struct Grid {
-    shape: (usize, usize),
-    required_presents: Vec<u32>,
-}
-
-fn parse_input(contents: &str) -> (Vec<Vec<Present>>, Vec<Grid>) {
-    let mut presents: Vec<Present> = vec![];
-    let mut grids: Vec<Grid> = vec![];
+fn parse_input(contents: &str) -> (Vec<Polyomino>, Vec<Tree>) {
+    let mut presents = vec![];
+    let mut trees = vec![];
     for text_group in contents.trim().split("\n\n") {
-        if text_group.lines().next().unwrap().ends_with(':') {
-            let mut empties = vec![];
-            for (i, line) in text_group.lines().skip(1).enumerate() {
-                for (j, c) in line.chars().enumerate() {
-                    if c == '.' {
-                        empties.push((i, j));
-                    }
-                }
-            }
-            presents.push(Present::new(empties));
+        let (first_line, rest) = text_group.split_once('\n').unwrap_or((text_group, ""));
+        if first_line.ends_with(':') {
+            presents.push(Polyomino::from_picture(rest));
         } else {
             for line in text_group.lines() {
-                let (grid_shape_str, requirements_str) = line.split_once(": ").unwrap();
-                let (m, n) = grid_shape_str.split_once('x').unwrap();
-                let shape = (m.parse::<usize>().unwrap(), n.parse::<usize>().unwrap());
-                let required_presents = requirements_str
-                    .split(' ')
-                    .map(|s| s.parse::<u32>().unwrap())
-                    .collect();
-                grids.push(Grid {
-                    shape,
-                    required_presents,
-                })
-            }
-        }
-    }
-    let mut all_present_options = vec![];
-    for mut present in presents {
-        let mut options = vec![];
-        let mut push_if_not_present = |new_present| {
-            if !options.contains(&new_present) {
-                options.push(new_present);
+                let (shape_str, requirements_str) = line.split_once(": ").unwrap();
+                let (width, height) = shape_str.split_once('x').unwrap();
+                trees.push(Tree {
+                    width: width.parse().unwrap(),
+                    height: height.parse().unwrap(),
+                    required_presents: requirements_str
+                        .split(' ')
+                        .map(|s| s.parse().unwrap())
+                        .collect(),
+                });
             }
-        };
-
-        for _ in 0..4 {
-            push_if_not_present(present.clone());
-            push_if_not_present(present.flip_horizontal());
-            present = present.rotate_90_deg();
         }
-        all_present_options.push(options);
     }
-    (all_present_options, grids)
+    (presents, trees)
 }
 
-#[allow(unused)]
-fn show_grid(grid: &Vec<Vec<bool>>) {
-    for line in grid.iter() {
-        for b in line.iter() {
-            if *b {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        print!("\n");
-    }
-    print!("\n");
+/// A way of fitting every required present under the tree, if there is one.
+fn arrange_presents(presents: &[Polyomino], tree: &Tree) -> Option<Placement> {
+    let pieces: Vec<(Polyomino, usize)> = presents
+        .iter()
+        .cloned()
+        .zip(tree.required_presents.iter().copied())
+        .collect();
+    polyomino::pack(&Region::new(tree.height, tree.width), &pieces)
 }
 
-fn grid_is_satisfiable(
-    present_options: &Vec<Vec<Present>>,
-    present_areas: &Vec<usize>,
-    required_presents: &mut Vec<u32>,
-    grid: &mut Vec<Vec<bool>>,
-    curr_i: usize,
-    curr_j: usize,
-) -> bool {
-    // If we've placed all required presents, it is satisfiable
-    if required_presents.iter().sum::<u32>() == 0 {
-        return true;
-    }
-    // If we are extending past the grid, it's not satisfiable
-    if curr_j + 2 >= grid[0].len() {
-        return false;
-    }
-    // If the required area of presents we have left to place is more than the
-    // area we have left to place in, it is not satisfiable
-    let required_area = required_presents
+fn fits(presents: &[Polyomino], tree: &Tree) -> bool {
+    let num_presents: usize = tree.required_presents.iter().sum();
+    let required_area: usize = presents
         .iter()
-        .zip(present_areas.iter())
-        .map(|(required_count, area)| *required_count as usize * area)
-        .sum::<usize>();
-    let available_area = (grid[0].len() - (curr_j + 1)) * grid.len() + (grid.len() - curr_i);
-    if required_area > available_area {
+        .zip(&tree.required_presents)
+        .map(|(present, count)| present.area() * count)
+        .sum();
+    if required_area > tree.width * tree.height {
         return false;
     }
-    if curr_i + 2 >= grid.len() {
-        return grid_is_satisfiable(
-            present_options,
-            present_areas,
-            required_presents,
-            grid,
-            0,
-            curr_j + 3,
-        );
-    }
-    for present_index in 0..required_presents.len() {
-        if required_presents[present_index] > 0 {
-            // It will never be optimal to NOT place a present in any given 3x3 square
-            // ASSUMPTION: The bounding box of every present is a 3x3 square.
-            for i_start in curr_i..curr_i + 3 {
-                if i_start + 2 >= grid.len() {
-                    break;
-                }
-                let j_start = curr_j;
-                'present_loop: for present in present_options[present_index].iter() {
-                    // Loop once to check if the present is placeable
-                    for i in i_start..i_start + 3 {
-                        for j in j_start..j_start + 3 {
-                            if grid[i][j] && !present.empties.contains(&(i - i_start, j - j_start))
-                            {
-                                continue 'present_loop;
-                            }
-                        }
-                    }
-                    // Loop a second time to actually place the present
-                    for i in i_start..i_start + 3 {
-                        for j in j_start..j_start + 3 {
-                            if !present.empties.contains(&(i - i_start, j - j_start)) {
-                                grid[i][j] = true;
-                            }
-                        }
-                    }
-                    // show_grid(grid);
-                    required_presents[present_index] -= 1;
-                    if grid_is_satisfiable(
-                        present_options,
-                        present_areas,
-                        required_presents,
-                        grid,
-                        i_start + 1,
-                        curr_j,
-                    ) {
-                        return true;
-                    }
-                    // That was a dead end, reset back to state before placing present
-                    required_presents[present_index] += 1;
-                    for i in i_start..i_start + 3 {
-                        for j in j_start..j_start + 3 {
-                            if !present.empties.contains(&(i - i_start, j - j_start)) {
-                                grid[i][j] = false;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    // If every present gets its own box, big enough for any of them, there's no need to
+    // search. That's the case for most trees, and they're too big to search anyway.
+    let box_height = presents.iter().map(Polyomino::height).max().unwrap_or(0);
+    let box_width = presents.iter().map(Polyomino::width).max().unwrap_or(0);
+    if box_height > 0 && (tree.height / box_height) * (tree.width / box_width) >= num_presents {
+        return true;
     }
-    grid_is_satisfiable(
-        present_options,
-        present_areas,
-        required_presents,
-        grid,
-        0,
-        curr_j + 3,
-    )
+    arrange_presents(presents, tree).is_some()
 }
 
 fn compute_1(contents: &str) -> u64 {
-    let (present_options, grids) = parse_input(contents);
-    let present_areas: Vec<usize> = present_options
-        .iter()
-        .map(|options| 9 - options[0].empties.len())
-        .collect();
-    let mut total = 0;
-    for mut grid_info in grids.into_iter() {
-        if grid_info.shape.0 / 3 * grid_info.shape.1 / 3
-            >= grid_info
-                .required_presents
-                .iter()
-                .map(|x| *x as usize)
-                .sum::<usize>()
-            && false
-        {
-            // Trivial case
-            total += 1;
-        } else {
-            let mut grid = vec![vec![false; grid_info.shape.1]; grid_info.shape.0];
-            if grid_is_satisfiable(
-                &present_options,
-                &present_areas,
-                &mut grid_info.required_presents,
-                &mut grid,
-                0,
-                0,
-            ) {
-                total += 1;
-            }
+    let (presents, trees) = parse_input(contents);
+    trees.iter().filter(|tree| fits(&presents, tree)).count() as u64
+}
+
+#[allow(dead_code)]
+fn show_arrangements(contents: &str) {
+    let (presents, trees) = parse_input(contents);
+    for tree in trees.iter() {
+        match arrange_presents(&presents, tree) {
+            Some(placement) => println!("{}", placement.render()),
+            None => println!("Doesn't fit\n"),
         }
     }
-    total
 }
 
 pub(crate) struct Day {}
@@ -281,3 +98,59 @@ impl Problem for Day {
         "Merry Christmas!".to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "0:
+###
+##.
+##.
+
+1:
+###
+##.
+.##
+
+2:
+.##
+###
+##.
+
+3:
+##.
+###
+##.
+
+4:
+###
+#..
+###
+
+5:
+###
+.#.
+###
+
+4x4: 0 0 0 0 2 0
+12x5: 1 0 1 0 2 2
+12x5: 1 0 1 0 3 2";
+
+    #[test]
+    fn test_compute_1_example() {
+        assert_eq!(compute_1(EXAMPLE), 2);
+    }
+
+    #[test]
+    fn test_arrange_presents() {
+        let (presents, trees) = parse_input(EXAMPLE);
+        let placement = arrange_presents(&presents, &trees[0]).unwrap();
+        assert_eq!(placement.pieces.len(), 2);
+        assert!(placement.pieces.iter().all(|placed| placed.piece == 4));
+        let rendered = placement.render();
+        assert_eq!((rendered.num_rows(), rendered.num_cols()), (4, 4));
+        assert_eq!(rendered.iter().filter(|&&c| c != '.').count(), 14);
+        assert!(arrange_presents(&presents, &trees[2]).is_none());
+    }
+}